@@ -1,31 +1,58 @@
-use crate::commands::config::current_unix_secs;
-use crate::models::{App, AppState};
-use tauri::State;
+use crate::commands::config::{current_unix_secs, update_config};
+use crate::error::AppError;
+use crate::models::{App, AppState, ItemType};
+use crate::utils::launcher::{self, LaunchOptions};
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub fn add_app(
     name: String,
     path: String,
-    category_id: String,
-    item_type: Option<String>,
+    category_id: Option<String>,
+    fallback_category_id: Option<String>,
+    item_type: Option<ItemType>,
     state: State<AppState>,
-) -> Result<App, String> {
+) -> Result<App, AppError> {
     let mut config = state.config.lock().unwrap();
-    let item_type = item_type.unwrap_or_else(|| "app".to_string());
+    let item_type = item_type.unwrap_or(ItemType::App);
     let app_id = uuid::Uuid::new_v4().to_string();
 
-    let icon = if item_type == "app" {
+    // 没有指定分类时按自动分类规则归类，规则都不匹配再依次使用调用方给的分类、上次使用的分类和“未分类”
+    let (category_id, rule_tags) = match category_id {
+        Some(category_id) => (category_id, Vec::new()),
+        None => {
+            let matched = crate::utils::rules::match_new_app(&config, &path, &item_type);
+            let category_id = [
+                matched.category_id,
+                fallback_category_id,
+                config.settings.last_category.clone(),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|id| config.categories.contains_key(id))
+            .unwrap_or_else(|| crate::utils::integrity::ensure_uncategorized(&mut config));
+            (category_id, matched.tags)
+        }
+    };
+
+    let icon = if item_type == ItemType::App {
         crate::utils::icon_extractor::extract_icon_to_file(&path, &app_id).ok()
     } else {
         None
     };
 
-    let app = App {
+    let mut app = App {
         id: app_id,
         name,
         path,
         category: category_id.clone(),
+        extra_categories: Vec::new(),
+        tags: Vec::new(),
         item_type,
+        args: Vec::new(),
+        working_dir: None,
+        env: Default::default(),
+        window_state: None,
         icon,
         last_launched: None,
         created_at: current_unix_secs(),
@@ -33,6 +60,7 @@ pub fn add_app(
         validation_status: None,
         last_validated_at: None,
     };
+    crate::utils::tags::add_tags(&mut app, &rule_tags)?;
 
     config.apps.insert(app.id.clone(), app.clone());
 
@@ -40,17 +68,19 @@ pub fn add_app(
         category.apps.push(app.id.clone());
     }
 
-    crate::utils::config::save_config(&config).map_err(|e| e.to_string())?;
+    crate::utils::config::save_config(&config)?;
     Ok(app)
 }
 
 #[tauri::command]
-pub fn delete_app(app_id: String, state: State<AppState>) -> Result<(), String> {
+pub fn delete_app(app_id: String, state: State<AppState>) -> Result<(), AppError> {
     let mut config = state.config.lock().unwrap();
 
     if let Some(app) = config.apps.remove(&app_id) {
-        if let Some(category) = config.categories.get_mut(&app.category) {
-            category.apps.retain(|id| id != &app_id);
+        for category_id in std::iter::once(&app.category).chain(&app.extra_categories) {
+            if let Some(category) = config.categories.get_mut(category_id) {
+                category.apps.retain(|id| id != &app_id);
+            }
         }
 
         if let Some(ref icon_filename) = app.icon {
@@ -61,46 +91,30 @@ pub fn delete_app(app_id: String, state: State<AppState>) -> Result<(), String>
         }
     }
 
-    crate::utils::config::save_config(&config).map_err(|e| e.to_string())?;
+    crate::utils::config::save_config(&config)?;
     Ok(())
 }
 
+/// 按程序的启动参数、工作目录、环境变量和窗口状态启动
 #[tauri::command]
-pub fn launch_app(app_id: String, state: State<AppState>) -> Result<(), String> {
-    let config = state.config.lock().unwrap();
-    let app = config
-        .apps
-        .get(&app_id)
-        .ok_or_else(|| "应用不存在".to_string())?;
-
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        use std::process::Command;
-
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-        Command::new("cmd")
-            .args(&["/C", "start", "", &app.path])
-            .creation_flags(CREATE_NO_WINDOW)
-            .spawn()
-            .map_err(|e| format!("启动失败: {}", e))?;
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        use std::process::Command;
-        Command::new("open")
-            .arg(&app.path)
-            .spawn()
-            .map_err(|e| format!("启动失败: {}", e))?;
-    }
+pub fn launch_app(app_id: String, state: State<AppState>) -> Result<(), AppError> {
+    launch_by_id(&state, &app_id, false)
+}
 
-    Ok(())
+fn launch_by_id(state: &AppState, app_id: &str, elevated: bool) -> Result<(), AppError> {
+    let app = state
+        .config
+        .lock()
+        .unwrap()
+        .apps
+        .get(app_id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found("应用"))?;
+    launcher::launch(state.launcher.as_ref(), &app, elevated)
 }
 
 #[tauri::command]
-pub fn extract_icon(exe_path: String) -> Result<String, String> {
+pub fn extract_icon(exe_path: String) -> Result<String, AppError> {
     crate::utils::icon_extractor::extract_icon_from_exe(&exe_path)
 }
 
@@ -112,33 +126,26 @@ pub fn get_icons_dir() -> String {
 }
 
 #[tauri::command]
-pub fn open_file_location(file_path: String) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        use std::process::Command;
-        Command::new("explorer")
-            .args(&["/select,", &file_path])
-            .spawn()
-            .map_err(|e| format!("打开文件位置失败: {}", e))?;
-    }
+pub fn open_file_location(file_path: String, state: State<AppState>) -> Result<(), AppError> {
+    state.launcher.reveal(&file_path)
+}
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        use std::process::Command;
-        let parent = std::path::Path::new(&file_path)
-            .parent()
-            .ok_or("无法获取父目录")?;
-        Command::new("open")
-            .arg(parent)
-            .spawn()
-            .map_err(|e| format!("打开文件位置失败: {}", e))?;
-    }
+/// 用系统关联的程序打开文件、文件夹或网址
+#[tauri::command]
+pub fn open_path(target: String, state: State<AppState>) -> Result<(), AppError> {
+    open_target(&state, &target)
+}
 
-    Ok(())
+fn open_target(state: &AppState, target: &str) -> Result<(), AppError> {
+    let target = target.trim();
+    if target.is_empty() || target.contains('\0') {
+        return Err(AppError::invalid_input("要打开的路径或网址无效"));
+    }
+    state.launcher.open(target)
 }
 
 #[tauri::command]
-pub fn resolve_shortcut(lnk_path: String) -> Result<String, String> {
+pub fn resolve_shortcut(lnk_path: String) -> Result<String, AppError> {
     #[cfg(target_os = "windows")]
     {
         use windows::core::Interface;
@@ -154,11 +161,11 @@ pub fn resolve_shortcut(lnk_path: String) -> Result<String, String> {
             let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
 
             let shell_link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)
-                .map_err(|e| format!("无法创建 ShellLink: {}", e))?;
+                .map_err(|e| AppError::internal(format!("无法创建 ShellLink: {}", e)))?;
 
             let persist_file: windows::Win32::System::Com::IPersistFile = shell_link
                 .cast()
-                .map_err(|e| format!("无法获取 IPersistFile: {}", e))?;
+                .map_err(|e| AppError::internal(format!("无法获取 IPersistFile: {}", e)))?;
 
             let wide_path = HSTRING::from(&lnk_path);
             persist_file
@@ -166,13 +173,13 @@ pub fn resolve_shortcut(lnk_path: String) -> Result<String, String> {
                     PCWSTR(wide_path.as_ptr()),
                     windows::Win32::System::Com::STGM(0),
                 )
-                .map_err(|e| format!("无法加载快捷方式: {}", e))?;
+                .map_err(|e| AppError::internal(format!("无法加载快捷方式: {}", e)))?;
 
             let mut path_buf = [0u16; 260];
             let mut find_data = WIN32_FIND_DATAW::default();
             shell_link
                 .GetPath(&mut path_buf, &mut find_data, 0)
-                .map_err(|e| format!("无法获取目标路径: {}", e))?;
+                .map_err(|e| AppError::internal(format!("无法获取目标路径: {}", e)))?;
 
             CoUninitialize();
 
@@ -181,10 +188,10 @@ pub fn resolve_shortcut(lnk_path: String) -> Result<String, String> {
                 .position(|&c| c == 0)
                 .unwrap_or(path_buf.len());
             let path = String::from_utf16(&path_buf[..path_len])
-                .map_err(|e| format!("路径编码转换失败: {}", e))?;
+                .map_err(|e| AppError::internal(format!("路径编码转换失败: {}", e)))?;
 
             if path.is_empty() {
-                return Err("无法获取快捷方式目标路径".to_string());
+                return Err(AppError::internal("无法获取快捷方式目标路径"));
             }
 
             Ok(path)
@@ -193,7 +200,7 @@ pub fn resolve_shortcut(lnk_path: String) -> Result<String, String> {
 
     #[cfg(not(target_os = "windows"))]
     {
-        Err("快捷方式解析仅支持 Windows".to_string())
+        Err(AppError::platform_unsupported("快捷方式解析"))
     }
 }
 
@@ -207,27 +214,154 @@ pub fn check_app_exists(path: String, state: State<AppState>) -> bool {
 }
 
 #[tauri::command]
-pub fn launch_app_as_admin(app_path: String) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        use std::process::Command;
-
-        Command::new("powershell")
-            .args(&[
-                "-NoProfile",
-                "-Command",
-                &format!("Start-Process '{}' -Verb RunAs", app_path),
-            ])
-            .creation_flags(0x08000000)
-            .spawn()
-            .map_err(|e| format!("以管理员身份启动失败: {}", e))?;
+pub fn launch_app_as_admin(app_id: String, state: State<AppState>) -> Result<(), AppError> {
+    launch_by_id(&state, &app_id, true)
+}
+
+#[tauri::command]
+pub fn update_app_launch_options(
+    app_id: String,
+    options: LaunchOptions,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<App, AppError> {
+    update_config(&state, &app_handle, |config| {
+        let app = config
+            .apps
+            .get_mut(&app_id)
+            .ok_or_else(|| AppError::not_found(format!("程序 {}", app_id)))?;
+        options.apply_to(app)?;
+        Ok(app.clone())
+    })
+}
+
+/// 复制一个程序条目，排在原条目之后，用于同一个程序的不同启动配置
+#[tauri::command]
+pub fn duplicate_app(
+    app_id: String,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<App, AppError> {
+    update_config(&state, &app_handle, |config| {
+        let source = config
+            .apps
+            .get(&app_id)
+            .ok_or_else(|| AppError::not_found(format!("程序 {}", app_id)))?;
+        let mut app = source.clone();
+        app.id = uuid::Uuid::new_v4().to_string();
+        app.name = format!("{} (副本)", source.name);
+        app.created_at = current_unix_secs();
+        app.last_launched = None;
+        app.icon = source.icon.as_ref().map(|icon| copy_icon(icon, &app.id));
+
+        for category_id in std::iter::once(&app.category).chain(&app.extra_categories) {
+            if let Some(category) = config.categories.get_mut(category_id) {
+                let index = category
+                    .apps
+                    .iter()
+                    .position(|id| id == &app_id)
+                    .map_or(category.apps.len(), |index| index + 1);
+                category.apps.insert(index, app.id.clone());
+            }
+        }
+        config.apps.insert(app.id.clone(), app.clone());
+        Ok(app)
+    })
+}
+
+/// 图标文件以程序 id 命名，复制条目时另存一份，删除其中一个不影响另一个；复制失败时沿用原文件
+fn copy_icon(icon: &str, app_id: &str) -> String {
+    if icon.starts_with("data:") {
+        return icon.to_string();
     }
+    let extension = std::path::Path::new(icon)
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let file_name = format!("{}{}", app_id, extension);
+    let source = crate::utils::config::get_icon_path(icon);
+    let target = crate::utils::config::get_icon_path(&file_name);
+    match std::fs::copy(source, target) {
+        Ok(_) => file_name,
+        Err(_) => icon.to_string(),
+    }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        return Err("管理员启动仅支持 Windows".to_string());
+#[cfg(test)]
+mod tests {
+    use super::{launch_by_id, open_target};
+    use crate::error::AppError;
+    use crate::models::{App, AppState, Config};
+    use crate::utils::launcher::{LaunchCall, LaunchOptions, MockLauncher};
+    use std::sync::{Arc, Mutex};
+
+    fn state_with(launcher: Arc<MockLauncher>, apps: Vec<App>) -> AppState {
+        let mut config = Config::default();
+        for app in apps {
+            config.apps.insert(app.id.clone(), app);
+        }
+        AppState {
+            config: Mutex::new(config),
+            launcher,
+        }
     }
 
-    Ok(())
+    fn app(id: &str, args: &[&str]) -> App {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "path": "/opt/tool/run",
+            "category": "c",
+            "args": args,
+            "env": { "MODE": "work" },
+            "createdAt": 1
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn 启动命令把程序和启动选项交给启动器() {
+        let launcher = Arc::new(MockLauncher::default());
+        let target = app("a", &["--profile", "Work Profile"]);
+        let state = state_with(launcher.clone(), vec![target.clone()]);
+
+        launch_by_id(&state, "a", false).unwrap();
+        launch_by_id(&state, "a", true).unwrap();
+        assert!(matches!(
+            launch_by_id(&state, "missing", false),
+            Err(AppError::NotFound { .. })
+        ));
+
+        let options = LaunchOptions::of(&target);
+        assert_eq!(options.args, vec!["--profile", "Work Profile"]);
+        assert_eq!(
+            launcher.calls(),
+            vec![
+                LaunchCall::Launch {
+                    app_id: "a".to_string(),
+                    elevated: false,
+                    options: options.clone(),
+                },
+                LaunchCall::Launch {
+                    app_id: "a".to_string(),
+                    elevated: true,
+                    options,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn 打开路径前去掉首尾空白并拒绝空值() {
+        let launcher = Arc::new(MockLauncher::default());
+        let state = state_with(launcher.clone(), Vec::new());
+
+        open_target(&state, " https://example.com ").unwrap();
+        assert!(open_target(&state, "  ").is_err());
+        assert!(open_target(&state, "a\0b").is_err());
+        assert_eq!(
+            launcher.calls(),
+            vec![LaunchCall::Open("https://example.com".to_string())]
+        );
+    }
 }
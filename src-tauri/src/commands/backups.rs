@@ -0,0 +1,87 @@
+use crate::error::AppError;
+use crate::models::AppState;
+use crate::storage::persisted::PersistedDataType;
+use crate::storage::{backup, events, recovery};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupRestoreResult {
+    pub success: bool,
+    pub backup_id: String,
+    pub safety_backup_dir: String,
+    pub errors: Vec<String>,
+}
+
+#[tauri::command]
+pub fn list_backups() -> Result<Vec<backup::BackupSummary>, AppError> {
+    backup::list_backups().map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn delete_backup(backup_id: String) -> Result<(), AppError> {
+    backup::delete_backup(&backup_id).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn restore_backup(
+    backup_id: String,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<BackupRestoreResult, AppError> {
+    backup::resolve_backup_dir(&backup_id)?;
+    let previous_config = {
+        let config = state.config.lock().unwrap();
+        config.clone()
+    };
+
+    let safety_backup = backup::create_restore_safety_backup()?;
+    let safety_backup_dir = safety_backup.dir.to_string_lossy().to_string();
+
+    if let Err(error) = backup::restore_backup(&backup_id) {
+        let _ = backup::restore_full_backup(&safety_backup.dir);
+        return Ok(BackupRestoreResult {
+            success: false,
+            backup_id,
+            safety_backup_dir,
+            errors: vec![error.to_string()],
+        });
+    }
+
+    let next_config = crate::utils::config::load_or_create_config();
+    if let Err(error) =
+        crate::utils::shortcuts::reload_shortcuts_or_restore(&app, &next_config, &previous_config)
+    {
+        let _ = backup::restore_full_backup(&safety_backup.dir);
+        return Ok(BackupRestoreResult {
+            success: false,
+            backup_id,
+            safety_backup_dir,
+            errors: vec![format!("恢复配置后注册快捷键失败: {}", error)],
+        });
+    }
+
+    {
+        let mut config = state.config.lock().unwrap();
+        *config = next_config.clone();
+    }
+    app.emit("config-changed", &next_config)?;
+    events::record_config_write();
+    for data_type in PersistedDataType::ALL {
+        events::record_restore(data_type);
+    }
+
+    Ok(BackupRestoreResult {
+        success: true,
+        backup_id,
+        safety_backup_dir,
+        errors: Vec::new(),
+    })
+}
+
+/// 返回并清除启动或读取时处理过的损坏文件记录，前端启动完成和收到 `data-recovered` 事件时调用
+#[tauri::command]
+pub fn take_data_recovery_reports() -> Vec<recovery::RecoveryReport> {
+    recovery::take_reports()
+}
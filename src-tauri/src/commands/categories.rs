@@ -0,0 +1,115 @@
+use crate::commands::config::update_config;
+use crate::error::AppError;
+use crate::models::{AppState, Category};
+use crate::utils::categories::{self, CategoryAppsDisposal};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn add_category(
+    name: String,
+    parent_id: Option<String>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<Category, AppError> {
+    update_config(&state, &app_handle, |config| {
+        categories::add_category(config, &name, parent_id.as_deref())
+    })
+}
+
+#[tauri::command]
+pub fn rename_category(
+    category_id: String,
+    name: String,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<(), AppError> {
+    update_config(&state, &app_handle, |config| {
+        categories::rename_category(config, &category_id, &name)
+    })
+}
+
+/// 删除分类，其中的程序按 `apps` 移到其他分类或一并删除；返回被删除的程序 id
+#[tauri::command]
+pub fn delete_category(
+    category_id: String,
+    apps: CategoryAppsDisposal,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, AppError> {
+    let removed = update_config(&state, &app_handle, |config| {
+        categories::delete_category(config, &category_id, &apps)
+    })?;
+
+    for app in &removed {
+        if let Some(ref icon_filename) = app.icon {
+            if !icon_filename.starts_with("data:") {
+                let icon_path = crate::utils::config::get_icon_path(icon_filename);
+                let _ = std::fs::remove_file(icon_path);
+            }
+        }
+    }
+    Ok(removed.into_iter().map(|app| app.id).collect())
+}
+
+#[tauri::command]
+pub fn reorder_categories(
+    parent_id: Option<String>,
+    category_ids: Vec<String>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<(), AppError> {
+    update_config(&state, &app_handle, |config| {
+        categories::reorder_categories(config, parent_id.as_deref(), &category_ids)
+    })
+}
+
+/// 修改分类的上级分类，`parent_id` 为空时移为顶级分类
+#[tauri::command]
+pub fn move_category(
+    category_id: String,
+    parent_id: Option<String>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<(), AppError> {
+    update_config(&state, &app_handle, |config| {
+        categories::move_category(config, &category_id, parent_id.as_deref())
+    })
+}
+
+#[tauri::command]
+pub fn move_app_to_category(
+    app_id: String,
+    category_id: String,
+    index: Option<usize>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<(), AppError> {
+    update_config(&state, &app_handle, |config| {
+        categories::move_app(config, &app_id, &category_id, index)
+    })
+}
+
+/// 让程序同时出现在另一个分类中，主分类不变
+#[tauri::command]
+pub fn link_app_to_category(
+    app_id: String,
+    category_id: String,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<(), AppError> {
+    update_config(&state, &app_handle, |config| {
+        categories::link_app(config, &app_id, &category_id)
+    })
+}
+
+#[tauri::command]
+pub fn unlink_app_from_category(
+    app_id: String,
+    category_id: String,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<(), AppError> {
+    update_config(&state, &app_handle, |config| {
+        categories::unlink_app(config, &app_id, &category_id)
+    })
+}
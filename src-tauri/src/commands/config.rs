@@ -1,22 +1,25 @@
-use crate::models::{AppState, Category, Config};
-use crate::storage::{backup, json_store, migration, paths};
+use crate::error::AppError;
+use crate::models::{AppState, Config};
+use crate::storage::backend::{self, StorageBackendKind};
+use crate::storage::persisted::{self, PersistedDataType};
+use crate::storage::{backup, json_store, migration, relocation};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 const LOCAL_STORAGE_MIGRATION_ID: &str = "local-storage-to-json-v1";
 
 #[tauri::command]
-pub fn load_config(state: State<AppState>) -> Result<Config, String> {
+pub fn load_config(state: State<AppState>) -> Result<Config, AppError> {
     let config = state.config.lock().unwrap();
     Ok(config.clone())
 }
 
 #[tauri::command]
-pub fn save_config(config: Config, state: State<AppState>, app: AppHandle) -> Result<(), String> {
+pub fn save_config(config: Config, state: State<AppState>, app: AppHandle) -> Result<(), AppError> {
     let previous_config = {
         let current_config = state.config.lock().unwrap();
         current_config.clone()
@@ -29,29 +32,49 @@ pub fn save_config(config: Config, state: State<AppState>, app: AppHandle) -> Re
         *current_config = config.clone();
     }
 
-    crate::utils::config::save_config(&config).map_err(|e| e.to_string())?;
-    app.emit("config-changed", &config)
-        .map_err(|error| error.to_string())?;
+    crate::utils::config::save_config(&config)?;
+    app.emit("config-changed", &config)?;
     Ok(())
 }
 
-#[tauri::command]
-pub fn add_category(name: String, state: State<AppState>) -> Result<Category, String> {
+/// 在当前配置的副本上执行修改，成功后保存并通知各窗口
+pub(crate) fn update_config<T>(
+    state: &State<AppState>,
+    app_handle: &AppHandle,
+    update: impl FnOnce(&mut Config) -> Result<T, AppError>,
+) -> Result<T, AppError> {
     let mut config = state.config.lock().unwrap();
+    let mut next_config = config.clone();
+    let result = update(&mut next_config)?;
+    crate::utils::config::save_config(&next_config)?;
+    *config = next_config;
+    app_handle.emit("config-changed", &*config)?;
+    Ok(result)
+}
 
-    let category = Category {
-        id: uuid::Uuid::new_v4().to_string(),
-        name,
-        icon: None,
-        apps: Vec::new(),
-        order: config.categories.len(),
+/// 配置文件被程序之外的修改改变后重新读取，更新内存中的配置和快捷键并通知各窗口。
+///
+/// 无法读取时保留内存中的配置，只记录日志；快捷键注册失败时保留原有快捷键，但仍以文件中的配置为准。
+pub(crate) fn reload_external_config(app_handle: &AppHandle) {
+    let state = app_handle.state::<AppState>();
+    let mut config = state.config.lock().unwrap();
+    let next_config = match backend::current()
+        .and_then(|backend| crate::utils::config::read_config(backend.as_ref()))
+    {
+        Ok(Some(next_config)) => next_config,
+        Ok(None) => return,
+        Err(error) => {
+            eprintln!("重新读取被外部修改的配置失败: {}", error);
+            return;
+        }
     };
-
-    config
-        .categories
-        .insert(category.id.clone(), category.clone());
-    crate::utils::config::save_config(&config).map_err(|e| e.to_string())?;
-    Ok(category)
+    if let Err(error) =
+        crate::utils::shortcuts::reload_shortcuts_or_restore(app_handle, &next_config, &config)
+    {
+        eprintln!("按外部修改的配置注册快捷键失败: {}", error);
+    }
+    *config = next_config;
+    let _ = app_handle.emit("config-changed", &*config);
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -93,54 +116,58 @@ pub struct LegacyMigrationResult {
     pub errors: Vec<String>,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum PersistedDataType {
-    Scenes,
-    Notes,
-    Todos,
-    Clipboard,
-    Actions,
-}
-
-impl PersistedDataType {
-    fn path(self) -> PathBuf {
-        match self {
-            PersistedDataType::Scenes => paths::scenes_path(),
-            PersistedDataType::Notes => paths::notes_path(),
-            PersistedDataType::Todos => paths::todos_path(),
-            PersistedDataType::Clipboard => paths::clipboard_path(),
-            PersistedDataType::Actions => paths::actions_path(),
-        }
-    }
-}
-
 #[tauri::command]
 pub fn read_persisted_data(
     data_type: PersistedDataType,
-) -> Result<Option<json_store::DataEnvelope<Value>>, String> {
-    let path = data_type.path();
-    if !path.exists() {
-        return Ok(None);
-    }
-
-    json_store::read_enveloped_json(&path)
-        .map(Some)
-        .map_err(|error| error.to_string())
+) -> Result<Option<json_store::DataEnvelope<Value>>, AppError> {
+    Ok(persisted::read_persisted(data_type)?)
 }
 
+/// `expected_updated_at` 为调用方上次读到的 `updatedAt`，数据已被其他窗口改写时返回带有当前数据的 `Conflict` 错误
 #[tauri::command]
 pub fn write_persisted_data(
     data_type: PersistedDataType,
     data: Value,
-) -> Result<json_store::DataEnvelope<Value>, String> {
-    let envelope = json_store::DataEnvelope {
-        schema_version: 1,
-        updated_at: json_store::now_millis(),
+    expected_updated_at: Option<u64>,
+) -> Result<json_store::DataEnvelope<Value>, AppError> {
+    Ok(persisted::write_persisted_if(
+        data_type,
         data,
-    };
-    json_store::write_json(&data_type.path(), &envelope).map_err(|error| error.to_string())?;
-    Ok(envelope)
+        expected_updated_at,
+    )?)
+}
+
+#[tauri::command]
+pub fn get_storage_backend() -> StorageBackendKind {
+    backend::current_kind()
+}
+
+/// 把全部数据迁移到另一种存储后端，迁移前会自动创建完整备份
+#[tauri::command]
+pub fn migrate_storage_backend(
+    target: StorageBackendKind,
+) -> Result<backend::StorageMigrationReport, AppError> {
+    backend::migrate(target).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_data_dir_info() -> relocation::DataDirInfo {
+    relocation::data_dir_info()
+}
+
+/// 把整个数据目录移动到用户选择的位置，校验通过后记录新位置并改为监视新目录；完成后需要重新启动程序
+#[tauri::command]
+pub fn relocate_data_dir(
+    target: String,
+    state: State<AppState>,
+) -> Result<relocation::DataDirRelocation, AppError> {
+    // 移动期间不处理修改配置的命令，避免基于旧位置的配置在移动后写入
+    let _config = state.config.lock().unwrap();
+    let relocation = relocation::relocate_data_dir(Path::new(&target))?;
+    if let Err(error) = crate::utils::data_watcher::watch_current_data_dir() {
+        eprintln!("监视新的数据目录失败: {}", error);
+    }
+    Ok(relocation)
 }
 
 #[tauri::command]
@@ -165,7 +192,7 @@ pub fn get_migration_status() -> migration::MigrationLog {
 #[tauri::command]
 pub fn migrate_legacy_local_storage(
     payload: LegacyLocalStoragePayload,
-) -> Result<LegacyMigrationResult, String> {
+) -> Result<LegacyMigrationResult, AppError> {
     if migration::has_completed(LOCAL_STORAGE_MIGRATION_ID) {
         return Ok(LegacyMigrationResult {
             migration_id: LOCAL_STORAGE_MIGRATION_ID.to_string(),
@@ -177,55 +204,48 @@ pub fn migrate_legacy_local_storage(
         });
     }
 
-    let legacy_snapshot = serde_json::to_value(&payload).map_err(|error| error.to_string())?;
+    let legacy_snapshot = serde_json::to_value(&payload)?;
     let backup = backup::create_migration_backup(
         "迁移旧 localStorage 数据到本地 JSON 文件",
         Some(&legacy_snapshot),
-    )
-    .map_err(|error| error.to_string())?;
+    )?;
 
     let mut written_files = Vec::new();
     let mut errors = payload.frontend_errors.clone();
 
     write_legacy_value(
-        paths::scenes_path(),
-        paths::SCENES_FILE,
+        PersistedDataType::Scenes,
         payload.scenes,
         &mut written_files,
         &mut errors,
     );
     write_legacy_value(
-        paths::notes_path(),
-        paths::NOTES_FILE,
+        PersistedDataType::Notes,
         payload.notes,
         &mut written_files,
         &mut errors,
     );
     write_legacy_value(
-        paths::todos_path(),
-        paths::TODOS_FILE,
+        PersistedDataType::Todos,
         payload.todos,
         &mut written_files,
         &mut errors,
     );
     write_legacy_value(
-        paths::clipboard_path(),
-        paths::CLIPBOARD_FILE,
+        PersistedDataType::Clipboard,
         payload.clipboard,
         &mut written_files,
         &mut errors,
     );
     write_legacy_value(
-        paths::actions_path(),
-        paths::ACTIONS_FILE,
+        PersistedDataType::Actions,
         payload.actions,
         &mut written_files,
         &mut errors,
     );
 
     let success = errors.is_empty();
-    migration::append_record(LOCAL_STORAGE_MIGRATION_ID, success, errors.clone())
-        .map_err(|error| error.to_string())?;
+    migration::append_record(LOCAL_STORAGE_MIGRATION_ID, success, errors.clone())?;
 
     Ok(LegacyMigrationResult {
         migration_id: LOCAL_STORAGE_MIGRATION_ID.to_string(),
@@ -238,14 +258,14 @@ pub fn migrate_legacy_local_storage(
 }
 
 fn write_legacy_value(
-    path: std::path::PathBuf,
-    file_name: &str,
+    data_type: PersistedDataType,
     value: Option<Value>,
     written_files: &mut Vec<String>,
     errors: &mut Vec<String>,
 ) {
     if let Some(value) = value {
-        if let Err(error) = json_store::write_enveloped_json(&path, 1, value) {
+        let file_name = data_type.file_name();
+        if let Err(error) = persisted::write_persisted(data_type, value) {
             errors.push(format!("写入 {} 失败: {}", file_name, error));
             return;
         }
@@ -290,10 +310,16 @@ mod tests {
             r#"{"scenes":[{"id":"scene-1"}]}"#.to_string(),
         );
         let payload = LegacyLocalStoragePayload {
-            scenes: Some(json!({ "scenes": [{ "id": "scene-1" }] })),
-            notes: Some(json!({ "notes": [{ "id": "note-1" }] })),
-            todos: Some(json!({ "items": [{ "id": "todo-1" }] })),
-            clipboard: Some(json!({ "items": [{ "id": "clip-1" }] })),
+            scenes: Some(json!({ "scenes": [{ "id": "scene-1", "name": "工作", "actions": [] }] })),
+            notes: Some(
+                json!({ "notes": [{ "id": "note-1", "content": "便签", "createdAt": 1, "updatedAt": 1 }] }),
+            ),
+            todos: Some(
+                json!({ "items": [{ "id": "todo-1", "title": "待办", "date": "2026-05-30", "createdAt": 1, "updatedAt": 1 }] }),
+            ),
+            clipboard: Some(json!({
+                "items": [{ "id": "clip-1", "content": "剪贴板", "contentType": "text", "createdAt": 1 }]
+            })),
             actions: Some(json!({ "enabled": ["open_folder"] })),
             legacy_raw: Some(legacy_raw),
             frontend_errors: Vec::new(),
@@ -0,0 +1,127 @@
+use crate::error::AppError;
+use crate::models::{AppState, Config};
+use crate::storage::error::StorageError;
+use crate::storage::history::{self, FileVersion};
+use crate::storage::paths;
+use crate::storage::persisted::PersistedDataType;
+use crate::storage::schema::{self, CONFIG_SCHEMA};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, State};
+
+/// 列出 config.json、scenes.json、notes.json 或 todos.json 的历史版本，最新的在前
+#[tauri::command]
+pub fn list_file_versions(file_name: String) -> Result<Vec<FileVersion>, AppError> {
+    history::list_versions(&file_name).map_err(AppError::from)
+}
+
+/// 把数据文件恢复到指定的历史版本，恢复前的内容会保留为新的历史版本
+#[tauri::command]
+pub fn restore_file_version(
+    file_name: String,
+    version_id: String,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    if file_name == paths::CONFIG_FILE {
+        return restore_config_version(&version_id, &state, &app);
+    }
+
+    let data_type = PersistedDataType::ALL
+        .into_iter()
+        .find(|data_type| data_type.file_name() == file_name)
+        .ok_or_else(|| AppError::invalid_input(format!("{} 没有历史版本", file_name)))?;
+    history::restore_persisted_version(data_type, &version_id)
+        .map(|_| ())
+        .map_err(AppError::from)
+}
+
+fn read_config_version(version_id: &str) -> Result<Config, StorageError> {
+    let content = history::read_version(paths::CONFIG_FILE, version_id)?;
+    let value: Value = serde_json::from_slice(&content)?;
+    let version = schema::parse_config_version(&value)?;
+    let mut value = CONFIG_SCHEMA.upgrade(version, value)?.value;
+    value["version"] = Value::String(schema::config_version_string(
+        CONFIG_SCHEMA.current_version(),
+    ));
+    Ok(serde_json::from_value(value)?)
+}
+
+/// 与 `update_config` 相同：持有配置锁，先注册新配置的快捷键，成功后才写入配置
+fn restore_config_version(
+    version_id: &str,
+    state: &State<AppState>,
+    app: &AppHandle,
+) -> Result<(), AppError> {
+    let mut config = state.config.lock().unwrap();
+    let next_config = apply_config_version(&config, version_id, |next, previous| {
+        crate::utils::shortcuts::reload_shortcuts_or_restore(app, next, previous)
+    })?;
+    *config = next_config;
+    app.emit("config-changed", &*config).map_err(AppError::from)
+}
+
+/// 读取历史版本并在 `reload_shortcuts` 成功后保存，返回恢复后的配置；任何一步失败时磁盘上的配置和历史都不变
+fn apply_config_version(
+    current: &Config,
+    version_id: &str,
+    reload_shortcuts: impl Fn(&Config, &Config) -> Result<(), AppError>,
+) -> Result<Config, AppError> {
+    let next_config = read_config_version(version_id)?;
+    reload_shortcuts(&next_config, current)?;
+    if let Err(error) = crate::utils::config::save_config(&next_config) {
+        // 配置没有写入，快捷键也回到当前配置
+        if let Err(restore_error) = reload_shortcuts(current, &next_config) {
+            eprintln!("恢复快捷键失败: {}", restore_error);
+        }
+        return Err(error.into());
+    }
+    Ok(next_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_config_version;
+    use crate::error::AppError;
+    use crate::models::Config;
+    use crate::storage::history;
+    use crate::storage::{json_store, paths};
+    use crate::utils::config::save_config;
+    use std::fs;
+
+    #[test]
+    fn 注册快捷键失败时恢复配置版本不改动配置和历史() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "program-manager-history-restore-config-{}-{}",
+            std::process::id(),
+            json_store::now_millis()
+        ));
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let mut config = Config::default();
+        config.settings.last_category = Some("old".to_string());
+        save_config(&config).unwrap();
+        config.settings.last_category = Some("current".to_string());
+        save_config(&config).unwrap();
+        let versions = history::list_versions(paths::CONFIG_FILE).unwrap();
+        assert_eq!(versions.len(), 1);
+        let stored = fs::read(paths::config_path()).unwrap();
+
+        let error = apply_config_version(&config, &versions[0].id, |_, _| {
+            Err(AppError::internal("快捷键已被占用"))
+        })
+        .expect_err("注册快捷键失败时应返回错误");
+        assert!(error.to_string().contains("快捷键已被占用"));
+        assert_eq!(
+            history::list_versions(paths::CONFIG_FILE).unwrap(),
+            versions
+        );
+        assert_eq!(fs::read(paths::config_path()).unwrap(), stored);
+
+        let restored = apply_config_version(&config, &versions[0].id, |_, _| Ok(())).unwrap();
+        assert_eq!(restored.settings.last_category.as_deref(), Some("old"));
+        assert_eq!(history::list_versions(paths::CONFIG_FILE).unwrap().len(), 2);
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
@@ -1,18 +1,20 @@
+use crate::error::AppError;
 use tauri::Manager;
 
 #[tauri::command]
-pub fn register_context_menu() -> Result<(), String> {
-    let exe_path = std::env::current_exe().map_err(|e| format!("无法获取执行文件路径: {}", e))?;
+pub fn register_context_menu() -> Result<(), AppError> {
+    let exe_path = std::env::current_exe()
+        .map_err(|e| AppError::internal(format!("无法获取执行文件路径: {}", e)))?;
 
     let exe_path_str = exe_path
         .to_str()
-        .ok_or_else(|| "无法转换执行文件路径".to_string())?;
+        .ok_or_else(|| AppError::internal("无法转换执行文件路径"))?;
 
     crate::utils::registry::register_context_menu(exe_path_str)
 }
 
 #[tauri::command]
-pub fn unregister_context_menu() -> Result<(), String> {
+pub fn unregister_context_menu() -> Result<(), AppError> {
     crate::utils::registry::unregister_context_menu()
 }
 
@@ -22,39 +24,47 @@ pub fn is_context_menu_registered() -> bool {
 }
 
 #[tauri::command]
-pub fn hide_todo_window(app: tauri::AppHandle) -> Result<(), String> {
+pub fn hide_todo_window(app: tauri::AppHandle) -> Result<(), AppError> {
     let window = app
         .get_webview_window("todo")
-        .ok_or_else(|| "待办窗口不存在".to_string())?;
+        .ok_or_else(|| AppError::not_found("待办窗口"))?;
 
     window
         .hide()
-        .map_err(|e| format!("隐藏待办窗口失败: {}", e))
+        .map_err(|e| AppError::internal(format!("隐藏待办窗口失败: {}", e)))
 }
 
 #[tauri::command]
-pub fn hide_main_window(app: tauri::AppHandle) -> Result<(), String> {
+pub fn hide_main_window(app: tauri::AppHandle) -> Result<(), AppError> {
     let window = app
         .get_webview_window("main")
-        .ok_or_else(|| "主窗口不存在".to_string())?;
+        .ok_or_else(|| AppError::not_found("主窗口"))?;
 
-    window.hide().map_err(|e| format!("隐藏主窗口失败: {}", e))
+    window
+        .hide()
+        .map_err(|e| AppError::internal(format!("隐藏主窗口失败: {}", e)))
 }
 
 #[tauri::command]
-pub fn quit_app(app: tauri::AppHandle) -> Result<(), String> {
+pub fn quit_app(app: tauri::AppHandle) -> Result<(), AppError> {
     app.exit(0);
     Ok(())
 }
 
+/// 重新启动程序，移动数据目录后调用以便所有模块使用新位置
+#[tauri::command]
+pub fn restart_app(app: tauri::AppHandle) {
+    app.restart();
+}
+
 #[tauri::command]
-pub fn show_notes_window(app: tauri::AppHandle) -> Result<(), String> {
+pub fn show_notes_window(app: tauri::AppHandle) -> Result<(), AppError> {
     crate::utils::shortcuts::show_or_create_notes_window(&app);
     Ok(())
 }
 
 #[tauri::command]
-pub fn show_todo_window(app: tauri::AppHandle) -> Result<(), String> {
+pub fn show_todo_window(app: tauri::AppHandle) -> Result<(), AppError> {
     crate::utils::shortcuts::show_or_create_todo_window(&app);
     Ok(())
 }
@@ -1,13 +1,28 @@
+use crate::error::AppError;
 use crate::models::AppState;
 use crate::storage::atomic_write::write_atomic;
-use crate::storage::{backup, json_store, paths};
+use crate::storage::backend::{self, StorageBackend};
+use crate::storage::diff::{self, ItemDiff};
+use crate::storage::encryption::{self, EncryptionParams};
+use crate::storage::error::StorageError;
+use crate::storage::json_store::DataEnvelope;
+use crate::storage::merge::{self, MergeCounts, MergePolicy};
+use crate::storage::package::{self, StagingDir};
+use crate::storage::persisted::{self, PersistedDataType};
+use crate::storage::{backup, json_store, paths, schema};
+use crate::utils::path_remap::{self, PathRemapRule, PathRemapSuggestion};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, State};
 
-const EXPORT_FORMAT_VERSION: u32 = 1;
+/// 版本 1 为松散目录导出；版本 2 起导出为单个 `.pmpkg` 数据包，并记录每个文件的 SHA-256
+const EXPORT_FORMAT_VERSION: u32 = 2;
+/// 加密数据包的外层清单只记录加密参数，实际内容是加密后的版本 2 数据包
+const ENCRYPTED_FORMAT_VERSION: u32 = 3;
+const ENCRYPTED_PAYLOAD_FILE: &str = "payload.bin";
+const ENCRYPTED_CONTENT_FILE: &str = "content.pmpkg";
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -29,6 +44,8 @@ pub struct LocalDataFileEntry {
     pub exists: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,13 +55,15 @@ pub struct LocalDataManifest {
     pub app_version: String,
     pub exported_at: u64,
     pub files: Vec<LocalDataFileEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionParams>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LocalDataExportResult {
-    pub export_dir: String,
-    pub manifest_path: String,
+    pub package_path: String,
+    pub encrypted: bool,
     pub files: Vec<LocalDataFileEntry>,
 }
 
@@ -58,6 +77,17 @@ pub struct LocalDataImportSectionPreview {
     pub item_count: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// 与本地当前数据相比的条目变化，仅在预览数据包时计算
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<LocalDataCollectionDiff>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalDataCollectionDiff {
+    /// 主配置分为 `categories` 和 `apps`，其余数据为条目列表所在字段
+    pub collection: String,
+    pub items: Vec<ItemDiff>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -67,14 +97,58 @@ pub struct LocalDataImportPreview {
     pub package_dir: String,
     pub app_version: String,
     pub exported_at: u64,
+    pub encrypted: bool,
     pub sections: Vec<LocalDataImportSectionPreview>,
+    /// 数据包中有程序路径在本机不存在时，探测得到的路径改写规则
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub path_suggestions: Vec<PathRemapSuggestion>,
     pub errors: Vec<String>,
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalDataImportMode {
+    /// 用数据包中的文件整体替换所选数据
+    #[default]
+    Overwrite,
+    /// 按 id 逐条合并，本地独有的数据保持不变
+    Merge,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LocalDataImportOptions {
     pub sections: Vec<LocalDataSection>,
+    #[serde(default)]
+    pub mode: LocalDataImportMode,
+    #[serde(default)]
+    pub conflict_policy: MergePolicy,
+    /// 按条目选择导入。某类数据出现在这里时只应用所列条目的变化，
+    /// 不再整体覆盖或合并，该类数据中的其他内容保持本地不变
+    #[serde(default)]
+    pub items: Vec<LocalDataItemSelection>,
+    /// 导入主配置前按顺序改写程序路径，用于在另一台电脑上导入
+    #[serde(default)]
+    pub path_rules: Vec<PathRemapRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalDataItemSelection {
+    pub section: LocalDataSection,
+    pub collection: String,
+    pub ids: Vec<String>,
+}
+
+/// 合并导入时每类数据的新增、更新和跳过数量
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalDataMergeSummary {
+    pub section: LocalDataSection,
+    /// 合并的数据集合，主配置分为 `categories` 和 `apps` 两项
+    pub collection: String,
+    #[serde(flatten)]
+    pub counts: MergeCounts,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -83,6 +157,8 @@ pub struct LocalDataImportResult {
     pub success: bool,
     pub backup_dir: String,
     pub imported_sections: Vec<LocalDataSection>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merge_summary: Vec<LocalDataMergeSummary>,
     pub errors: Vec<String>,
 }
 
@@ -148,51 +224,59 @@ fn section_files() -> Vec<SectionFile> {
     ]
 }
 
+/// 提供非空 `passphrase` 时，数据包内容会整体加密，清单和文件列表也不会以明文保存
 #[tauri::command]
-pub fn export_local_data(export_dir: String) -> Result<LocalDataExportResult, String> {
-    let export_dir = PathBuf::from(export_dir).join(format!(
-        "program-manager-export-{}",
-        json_store::now_millis()
+pub fn export_local_data(
+    export_dir: String,
+    passphrase: Option<String>,
+) -> Result<LocalDataExportResult, AppError> {
+    let package_path = PathBuf::from(export_dir).join(format!(
+        "program-manager-export-{}.{}",
+        json_store::now_millis(),
+        package::PACKAGE_EXTENSION
     ));
-    fs::create_dir_all(&export_dir).map_err(|error| error.to_string())?;
+    let staging = StagingDir::create("export")?;
+    let staging_dir = staging.path();
 
+    let backend = backend::current()?;
     let mut files = Vec::new();
     for item in section_files() {
-        if item.section == LocalDataSection::Icons {
-            let target = export_dir.join(item.relative_path);
-            let exists = item.source_path.exists();
-            if exists {
-                if target.exists() {
-                    fs::remove_dir_all(&target).map_err(|error| error.to_string())?;
-                }
-                backup::copy_dir_recursive(&item.source_path, &target)
-                    .map_err(|error| error.to_string())?;
-            }
+        let target = staging_dir.join(item.relative_path);
+        let exported = if item.section != LocalDataSection::Icons {
+            export_document(backend.as_ref(), item.relative_path, &target)?
+        } else if item.source_path.exists() {
+            backup::copy_dir_recursive(&item.source_path, &target)?;
+            true
+        } else {
+            false
+        };
+        if !exported {
             files.push(LocalDataFileEntry {
                 section: item.section,
                 path: item.relative_path.to_string(),
-                exists,
+                exists: false,
                 size: None,
+                sha256: None,
             });
             continue;
         }
 
-        let exists = item.source_path.exists();
-        let mut size = None;
-        if exists {
-            let target = export_dir.join(item.relative_path);
-            fs::copy(&item.source_path, &target).map_err(|error| error.to_string())?;
-            size = fs::metadata(&item.source_path)
-                .ok()
-                .map(|metadata| metadata.len());
+        if item.section == LocalDataSection::Icons {
+            for name in package::list_files(&target)? {
+                let relative_path = format!("{}/{}", item.relative_path, name);
+                files.push(exported_file_entry(
+                    item.section,
+                    relative_path,
+                    &target.join(&name),
+                )?);
+            }
+        } else {
+            files.push(exported_file_entry(
+                item.section,
+                item.relative_path.to_string(),
+                &target,
+            )?);
         }
-
-        files.push(LocalDataFileEntry {
-            section: item.section,
-            path: item.relative_path.to_string(),
-            exists,
-            size,
-        });
     }
 
     let manifest = LocalDataManifest {
@@ -200,46 +284,269 @@ pub fn export_local_data(export_dir: String) -> Result<LocalDataExportResult, St
         app_version: env!("CARGO_PKG_VERSION").to_string(),
         exported_at: json_store::now_millis(),
         files: files.clone(),
+        encryption: None,
     };
-    let manifest_path = export_dir.join("manifest.json");
-    let manifest_content =
-        serde_json::to_vec_pretty(&manifest).map_err(|error| error.to_string())?;
-    write_atomic(&manifest_path, &manifest_content).map_err(|error| error.to_string())?;
+    write_manifest(staging_dir, &manifest)?;
+
+    let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
+    match &passphrase {
+        Some(passphrase) => write_encrypted_package(staging_dir, &package_path, passphrase)?,
+        None => package::write_package(staging_dir, &package_path)?,
+    }
 
     Ok(LocalDataExportResult {
-        export_dir: export_dir.to_string_lossy().to_string(),
-        manifest_path: manifest_path.to_string_lossy().to_string(),
+        package_path: package_path.to_string_lossy().to_string(),
+        encrypted: passphrase.is_some(),
         files,
     })
 }
 
+/// 把一份数据文档写到导出目录；JSON 后端直接复制原文件，本地没有该文档时返回 `false`
+fn export_document(
+    backend: &dyn StorageBackend,
+    name: &str,
+    target: &Path,
+) -> Result<bool, AppError> {
+    if let Some(path) = backend.file_path(name) {
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::copy(&path, target)?;
+        return Ok(true);
+    }
+    match backend.read(name)? {
+        Some(value) => {
+            json_store::write_json(target, &value)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+fn write_manifest(dir: &Path, manifest: &LocalDataManifest) -> Result<(), AppError> {
+    let content = serde_json::to_vec_pretty(manifest)?;
+    write_atomic(&dir.join(package::MANIFEST_FILE), &content).map_err(AppError::from)
+}
+
+/// 先把内容目录打成普通数据包，再整体加密后与只含加密参数的外层清单一起打包
+fn write_encrypted_package(
+    content_dir: &Path,
+    package_path: &Path,
+    passphrase: &str,
+) -> Result<(), AppError> {
+    let outer = StagingDir::create("export-encrypted")?;
+    let content_path = outer.path().join(ENCRYPTED_CONTENT_FILE);
+    package::write_package(content_dir, &content_path)?;
+    let content = fs::read(&content_path)?;
+    fs::remove_file(&content_path)?;
+
+    let (params, ciphertext) = encryption::encrypt(passphrase, &content)?;
+    write_atomic(&outer.path().join(ENCRYPTED_PAYLOAD_FILE), &ciphertext)?;
+    write_manifest(
+        outer.path(),
+        &LocalDataManifest {
+            format_version: ENCRYPTED_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: json_store::now_millis(),
+            files: Vec::new(),
+            encryption: Some(params),
+        },
+    )?;
+    package::write_package(outer.path(), package_path).map_err(AppError::from)
+}
+
+fn exported_file_entry(
+    section: LocalDataSection,
+    relative_path: String,
+    path: &Path,
+) -> Result<LocalDataFileEntry, AppError> {
+    let size = fs::metadata(path)?.len();
+    let sha256 = package::sha256_file(path)?;
+    Ok(LocalDataFileEntry {
+        section,
+        path: relative_path,
+        exists: true,
+        size: Some(size),
+        sha256: Some(sha256),
+    })
+}
+
+/// 已打开的数据包：`.pmpkg` 会先解压到临时目录，松散目录则直接读取
+struct OpenedPackage {
+    dir: PathBuf,
+    manifest: LocalDataManifest,
+    encrypted: bool,
+    _staging: Option<StagingDir>,
+}
+
+/// 打开 `.pmpkg` 数据包或旧版导出目录中的 manifest.json。
+///
+/// 加密数据包需要提供口令：未提供时返回 `AppError::PassphraseRequired`，
+/// 口令错误时返回 `AppError::WrongPassphrase`。
+fn open_package(path: &Path, passphrase: Option<&str>) -> Result<OpenedPackage, AppError> {
+    let (dir, manifest_path, staging) = if package::is_package_path(path) {
+        let staging = StagingDir::create("import")?;
+        package::extract_package(path, staging.path())?;
+        let dir = staging.path().to_path_buf();
+        (dir.clone(), dir.join(package::MANIFEST_FILE), Some(staging))
+    } else {
+        let dir = path
+            .parent()
+            .ok_or_else(|| AppError::invalid_input("manifest.json 路径无效"))?
+            .to_path_buf();
+        (dir, path.to_path_buf(), None)
+    };
+    let manifest = read_manifest(&manifest_path)?;
+    let Some(params) = manifest.encryption.as_ref() else {
+        return Ok(OpenedPackage {
+            dir,
+            manifest,
+            encrypted: false,
+            _staging: staging,
+        });
+    };
+
+    let passphrase = passphrase
+        .filter(|passphrase| !passphrase.is_empty())
+        .ok_or(AppError::PassphraseRequired)?;
+    let ciphertext = fs::read(dir.join(ENCRYPTED_PAYLOAD_FILE))
+        .map_err(|_| AppError::corrupt("数据包缺少加密内容"))?;
+    let content = encryption::decrypt(passphrase, params, &ciphertext)?;
+
+    let decrypted = StagingDir::create("import-decrypted")?;
+    let content_path = decrypted.path().join(ENCRYPTED_CONTENT_FILE);
+    let content_dir = decrypted.path().join("content");
+    fs::write(&content_path, content)?;
+    package::extract_package(&content_path, &content_dir)?;
+    let manifest = read_manifest(&content_dir.join(package::MANIFEST_FILE))?;
+    if manifest.encryption.is_some() {
+        return Err(AppError::corrupt("加密数据包的内容无效"));
+    }
+    Ok(OpenedPackage {
+        dir: content_dir,
+        manifest,
+        encrypted: true,
+        _staging: Some(decrypted),
+    })
+}
+
+/// 数据包在磁盘上的位置：`.pmpkg` 为文件本身，旧版导出为 manifest.json 所在目录
+fn package_location(path: &Path) -> String {
+    if package::is_package_path(path) {
+        return path.to_string_lossy().to_string();
+    }
+    path.parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// `manifest_path` 可以是 `.pmpkg` 数据包，也可以是旧版导出目录中的 manifest.json
 #[tauri::command]
-pub fn preview_local_data_import(manifest_path: String) -> Result<LocalDataImportPreview, String> {
+pub fn preview_local_data_import(
+    manifest_path: String,
+    passphrase: Option<String>,
+) -> Result<LocalDataImportPreview, AppError> {
     let manifest_path = PathBuf::from(manifest_path);
-    let package_dir = manifest_path
-        .parent()
-        .ok_or_else(|| "manifest.json 路径无效".to_string())?
-        .to_path_buf();
-    let manifest = read_manifest(&manifest_path)?;
-    Ok(build_preview(&manifest_path, &package_dir, manifest))
+    let opened = open_package(&manifest_path, passphrase.as_deref())?;
+    let mut preview = build_preview(&manifest_path, &opened.dir, opened.manifest);
+    preview.package_dir = package_location(&manifest_path);
+    preview.encrypted = opened.encrypted;
+    attach_changes(&mut preview, &opened.dir);
+    preview.path_suggestions = suggest_path_rules(&preview, &opened.dir);
+    Ok(preview)
+}
+
+fn suggest_path_rules(
+    preview: &LocalDataImportPreview,
+    package_dir: &Path,
+) -> Vec<PathRemapSuggestion> {
+    let config_ready = preview.sections.iter().any(|section| {
+        section.section == LocalDataSection::Config && section.available && section.error.is_none()
+    });
+    if !config_ready {
+        return Vec::new();
+    }
+    let Ok(config) = read_package_data(
+        LocalDataSection::Config,
+        &package_dir.join(paths::CONFIG_FILE),
+    ) else {
+        return Vec::new();
+    };
+    let app_paths: Vec<String> = collection_items(&config, "apps")
+        .iter()
+        .filter_map(|app| app.get("path").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    path_remap::suggest_rules(&app_paths)
+}
+
+/// 为校验通过的数据计算与本地数据的条目差异；本地数据无法读取时不列出差异
+fn attach_changes(preview: &mut LocalDataImportPreview, package_dir: &Path) {
+    for item in section_files() {
+        let Some(section) = preview
+            .sections
+            .iter_mut()
+            .find(|section| section.section == item.section)
+        else {
+            continue;
+        };
+        if !section.available || section.error.is_some() {
+            continue;
+        }
+        if let Ok(changes) = section_changes(&item, &package_dir.join(item.relative_path)) {
+            section.changes = changes;
+        }
+    }
+}
+
+fn section_changes(
+    item: &SectionFile,
+    source: &Path,
+) -> Result<Vec<LocalDataCollectionDiff>, AppError> {
+    let collections = diff_collections(item.section);
+    if collections.is_empty() {
+        return Ok(Vec::new());
+    }
+    let theirs = read_package_data(item.section, source)?;
+    let mine = read_local_data(item)?.unwrap_or_else(|| empty_data(item.section, &theirs));
+    Ok(collections
+        .iter()
+        .map(|collection| LocalDataCollectionDiff {
+            collection: collection.to_string(),
+            items: diff::diff_items(
+                &collection_items(&mine, collection),
+                &collection_items(&theirs, collection),
+                ignored_fields(collection),
+            ),
+        })
+        .filter(|collection| !collection.items.is_empty())
+        .collect())
 }
 
 #[tauri::command]
 pub fn import_local_data(
     manifest_path: String,
     options: LocalDataImportOptions,
+    passphrase: Option<String>,
     state: State<AppState>,
     app: AppHandle,
-) -> Result<LocalDataImportResult, String> {
+) -> Result<LocalDataImportResult, AppError> {
     let manifest_path = PathBuf::from(manifest_path);
-    let (package_dir, selected) = validate_import_request(&manifest_path, options.sections)?;
+    let (package, selected) =
+        validate_import_request(&manifest_path, options.sections, passphrase.as_deref())?;
+    let plan = ImportPlan {
+        merge_policy: (options.mode == LocalDataImportMode::Merge)
+            .then_some(options.conflict_policy),
+        items: &options.items,
+        path_rules: &options.path_rules,
+    };
     let previous_config = {
         let config = state.config.lock().unwrap();
         config.clone()
     };
 
-    let backup = match apply_import_with_backup(&package_dir, &selected) {
-        Ok(backup) => backup,
+    let (backup, merge_summary) = match apply_import_with_backup(&package.dir, &selected, &plan) {
+        Ok(applied) => applied,
         Err(result) => return Ok(result),
     };
 
@@ -257,6 +564,7 @@ pub fn import_local_data(
                 success: false,
                 backup_dir: backup.dir.to_string_lossy().to_string(),
                 imported_sections: Vec::new(),
+                merge_summary: Vec::new(),
                 errors: vec![format!("导入配置后注册快捷键失败: {}", error)],
             });
         }
@@ -265,14 +573,14 @@ pub fn import_local_data(
             let mut config = state.config.lock().unwrap();
             *config = next_config.clone();
         }
-        app.emit("config-changed", &next_config)
-            .map_err(|error| error.to_string())?;
+        app.emit("config-changed", &next_config)?;
     }
 
     Ok(LocalDataImportResult {
         success: true,
         backup_dir: backup.dir.to_string_lossy().to_string(),
         imported_sections: selected,
+        merge_summary,
         errors: Vec::new(),
     })
 }
@@ -280,29 +588,37 @@ pub fn import_local_data(
 fn validate_import_request(
     manifest_path: &Path,
     sections: Vec<LocalDataSection>,
-) -> Result<(PathBuf, Vec<LocalDataSection>), String> {
-    let package_dir = manifest_path
-        .parent()
-        .ok_or_else(|| "manifest.json 路径无效".to_string())?
-        .to_path_buf();
-    let manifest = read_manifest(manifest_path)?;
-    let preview = build_preview(manifest_path, &package_dir, manifest);
+    passphrase: Option<&str>,
+) -> Result<(OpenedPackage, Vec<LocalDataSection>), AppError> {
+    let package = open_package(manifest_path, passphrase)?;
+    let preview = build_preview(manifest_path, &package.dir, package.manifest.clone());
     if !preview.errors.is_empty() {
-        return Err(preview.errors.join("；"));
+        return Err(AppError::corrupt(preview.errors.join("；")));
     }
 
     let selected = normalized_sections(sections);
     if selected.is_empty() {
-        return Err("至少选择一类数据导入".to_string());
+        return Err(AppError::invalid_input("至少选择一类数据导入"));
     }
 
-    Ok((package_dir, selected))
+    Ok((package, selected))
+}
+
+/// 导入所选数据的方式
+#[derive(Default)]
+struct ImportPlan<'a> {
+    /// 为空时整体覆盖所选数据，否则按该策略逐条合并
+    merge_policy: Option<MergePolicy>,
+    /// 这里列出的数据只应用所选条目
+    items: &'a [LocalDataItemSelection],
+    path_rules: &'a [PathRemapRule],
 }
 
 fn apply_import_with_backup(
     package_dir: &Path,
     selected: &[LocalDataSection],
-) -> Result<backup::BackupResult, LocalDataImportResult> {
+    plan: &ImportPlan,
+) -> Result<(backup::BackupResult, Vec<LocalDataMergeSummary>), LocalDataImportResult> {
     let backup = match backup::create_full_backup("导入本地数据包前自动备份") {
         Ok(backup) => backup,
         Err(error) => {
@@ -310,6 +626,7 @@ fn apply_import_with_backup(
                 success: false,
                 backup_dir: String::new(),
                 imported_sections: Vec::new(),
+                merge_summary: Vec::new(),
                 errors: vec![error.to_string()],
             });
         }
@@ -319,32 +636,35 @@ fn apply_import_with_backup(
             success: false,
             backup_dir: backup.dir.to_string_lossy().to_string(),
             imported_sections: Vec::new(),
+            merge_summary: Vec::new(),
             errors: vec!["自动备份清单缺失，已取消导入".to_string()],
         });
     }
 
-    if let Err(error) = apply_import(package_dir, selected) {
-        let _ = backup::restore_full_backup(&backup.dir);
-        return Err(LocalDataImportResult {
-            success: false,
-            backup_dir: backup.dir.to_string_lossy().to_string(),
-            imported_sections: Vec::new(),
-            errors: vec![error],
-        });
+    match apply_import(package_dir, selected, plan) {
+        Ok(merge_summary) => Ok((backup, merge_summary)),
+        Err(error) => {
+            let _ = backup::restore_full_backup(&backup.dir);
+            Err(LocalDataImportResult {
+                success: false,
+                backup_dir: backup.dir.to_string_lossy().to_string(),
+                imported_sections: Vec::new(),
+                merge_summary: Vec::new(),
+                errors: vec![error.to_string()],
+            })
+        }
     }
-
-    Ok(backup)
 }
 
-fn read_manifest(path: &Path) -> Result<LocalDataManifest, String> {
-    let content = fs::read_to_string(path).map_err(|error| error.to_string())?;
+fn read_manifest(path: &Path) -> Result<LocalDataManifest, AppError> {
+    let content = fs::read_to_string(path)?;
     let manifest: LocalDataManifest = serde_json::from_str(&content)
-        .map_err(|error| format!("manifest.json 无法解析: {}", error))?;
-    if manifest.format_version != EXPORT_FORMAT_VERSION {
-        return Err(format!(
+        .map_err(|error| AppError::corrupt(format!("manifest.json 无法解析: {}", error)))?;
+    if manifest.format_version == 0 || manifest.format_version > ENCRYPTED_FORMAT_VERSION {
+        return Err(AppError::corrupt(format!(
             "不支持的数据包格式版本: {}",
             manifest.format_version
-        ));
+        )));
     }
     Ok(manifest)
 }
@@ -358,29 +678,32 @@ fn build_preview(
     let mut sections = Vec::new();
 
     for item in section_files() {
-        let file_entry = manifest
+        let available = manifest
             .files
             .iter()
-            .find(|entry| entry.section == item.section);
-        let available = file_entry.map(|entry| entry.exists).unwrap_or(false);
+            .any(|entry| entry.section == item.section && entry.exists);
         let package_path = package_dir.join(item.relative_path);
 
         let mut item_error = None;
         let mut item_count = None;
         if available {
-            if item.section == LocalDataSection::Icons {
+            if let Err(error) = verify_section_files(package_dir, &manifest, item.section) {
+                item_error = Some(error.to_string());
+            } else if item.section == LocalDataSection::Icons {
                 if !package_path.is_dir() {
                     item_error = Some("图标目录不存在".to_string());
                 }
             } else if !package_path.is_file() {
                 item_error = Some("数据文件不存在".to_string());
             } else {
-                match read_json_value(&package_path) {
+                match read_json_value(&package_path)
+                    .and_then(|value| check_schema_version(item.section, value))
+                {
                     Ok(value) => {
                         item_count = count_items(item.section, &value);
                     }
                     Err(error) => {
-                        item_error = Some(error);
+                        item_error = Some(error.to_string());
                     }
                 }
             }
@@ -396,6 +719,7 @@ fn build_preview(
             available,
             item_count,
             error: item_error,
+            changes: Vec::new(),
         });
     }
 
@@ -404,30 +728,370 @@ fn build_preview(
         package_dir: package_dir.to_string_lossy().to_string(),
         app_version: manifest.app_version,
         exported_at: manifest.exported_at,
+        encrypted: false,
         sections,
+        path_suggestions: Vec::new(),
         errors,
     }
 }
 
-fn apply_import(package_dir: &Path, selected: &[LocalDataSection]) -> Result<(), String> {
+/// 核对清单中记录的大小和 SHA-256，文件缺失、被截断或内容被改动时拒绝导入。
+///
+/// 版本 1 的导出目录没有记录校验和，只检查文件是否存在。
+fn verify_section_files(
+    package_dir: &Path,
+    manifest: &LocalDataManifest,
+    section: LocalDataSection,
+) -> Result<(), AppError> {
+    if manifest.format_version < 2 {
+        return Ok(());
+    }
+
+    let entries: Vec<&LocalDataFileEntry> = manifest
+        .files
+        .iter()
+        .filter(|entry| entry.section == section && entry.exists)
+        .collect();
+    for entry in &entries {
+        let relative = package::safe_relative_path(&entry.path)
+            .ok_or_else(|| AppError::corrupt(format!("{} 路径无效", entry.path)))?;
+        let path = package_dir.join(relative);
+        if !path.is_file() {
+            return Err(AppError::corrupt(format!("数据包缺少 {}", entry.path)));
+        }
+        let expected = entry
+            .sha256
+            .as_deref()
+            .ok_or_else(|| AppError::corrupt(format!("{} 缺少校验和", entry.path)))?;
+        let size = fs::metadata(&path)?.len();
+        if entry
+            .size
+            .is_some_and(|expected_size| expected_size != size)
+        {
+            return Err(AppError::corrupt(format!(
+                "{} 大小不符，数据包可能已被截断",
+                entry.path
+            )));
+        }
+        let actual = package::sha256_file(&path)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(AppError::corrupt(format!(
+                "{} 校验和不符，数据包可能已被篡改",
+                entry.path
+            )));
+        }
+    }
+
+    // 图标按整个目录导入，目录中不能夹带清单外的文件
+    if section == LocalDataSection::Icons {
+        let icons_dir = package_dir.join("icons");
+        for name in package::list_files(&icons_dir)? {
+            let relative_path = format!("icons/{}", name);
+            if !entries.iter().any(|entry| entry.path == relative_path) {
+                return Err(AppError::corrupt(format!(
+                    "数据包包含清单外的文件 {}",
+                    relative_path
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_import(
+    package_dir: &Path,
+    selected: &[LocalDataSection],
+    plan: &ImportPlan,
+) -> Result<Vec<LocalDataMergeSummary>, AppError> {
+    let mut merge_summary = Vec::new();
     for item in section_files() {
         if !selected.contains(&item.section) {
             continue;
         }
 
         let source = package_dir.join(item.relative_path);
-        if item.section == LocalDataSection::Icons {
+        let remapped = if item.section == LocalDataSection::Config && !plan.path_rules.is_empty() {
+            Some(remap_config_source(&source, plan.path_rules)?)
+        } else {
+            None
+        };
+        let source = remapped
+            .as_ref()
+            .map(|staging| staging.path().join(paths::CONFIG_FILE))
+            .unwrap_or(source);
+        let item_selection: Vec<&LocalDataItemSelection> = plan
+            .items
+            .iter()
+            .filter(|selection| selection.section == item.section)
+            .collect();
+        if !item_selection.is_empty() {
+            merge_summary.extend(apply_item_selection(&item, &source, &item_selection)?);
+        } else if let Some(policy) = plan.merge_policy {
+            merge_summary.extend(merge_section(&item, &source, policy)?);
+        } else if item.section == LocalDataSection::Icons {
             if item.target_path.exists() {
-                fs::remove_dir_all(&item.target_path).map_err(|error| error.to_string())?;
+                fs::remove_dir_all(&item.target_path)?;
             }
-            backup::copy_dir_recursive(&source, &item.target_path)
-                .map_err(|error| error.to_string())?;
+            backup::copy_dir_recursive(&source, &item.target_path)?;
+        } else if let Some(data_type) = section_data_type(item.section) {
+            // 整体覆盖同样经由数据层写入，结构不对的数据会被拒绝
+            persisted::write_persisted(data_type, read_package_data(item.section, &source)?)?;
         } else {
-            let bytes = fs::read(&source).map_err(|error| error.to_string())?;
-            write_atomic(&item.target_path, &bytes).map_err(|error| error.to_string())?;
+            write_config_value(&read_package_data(item.section, &source)?)?;
         }
     }
-    Ok(())
+    Ok(merge_summary)
+}
+
+/// 把改写程序路径后的配置写到临时目录，数据包本身保持不变
+fn remap_config_source(source: &Path, rules: &[PathRemapRule]) -> Result<StagingDir, AppError> {
+    let mut config = read_json_value(source)?;
+    if let Some(apps) = config.get_mut("apps").and_then(Value::as_object_mut) {
+        for app in apps.values_mut() {
+            if let Some(path) = app.get("path").and_then(Value::as_str) {
+                let remapped = path_remap::apply_rules(path, rules);
+                app["path"] = Value::String(remapped);
+            }
+        }
+    }
+    let staging = StagingDir::create("import-remap")?;
+    json_store::write_json(&staging.path().join(paths::CONFIG_FILE), &config)?;
+    Ok(staging)
+}
+
+/// 按 id 把数据包中的一类数据合并进本地数据。
+///
+/// 快捷动作按动作名取并集；图标只补充本地缺少的文件，
+/// 同名文件仅在 `KeepTheirs` 策略下被覆盖。
+fn merge_section(
+    item: &SectionFile,
+    source: &Path,
+    policy: MergePolicy,
+) -> Result<Vec<LocalDataMergeSummary>, AppError> {
+    let summary = |collection: &str, counts: MergeCounts| LocalDataMergeSummary {
+        section: item.section,
+        collection: collection.to_string(),
+        counts,
+    };
+
+    match item.section {
+        LocalDataSection::Config => {
+            let theirs = read_package_data(item.section, source)?;
+            let mut mine =
+                read_local_data(item)?.unwrap_or_else(|| empty_data(item.section, &theirs));
+            let (categories, apps) = merge::merge_config(&mut mine, &theirs, policy);
+            write_config_value(&mine)?;
+            Ok(vec![
+                summary("categories", categories.counts),
+                summary("apps", apps.counts),
+            ])
+        }
+        LocalDataSection::Icons => {
+            fs::create_dir_all(&item.target_path)?;
+            let mut counts = MergeCounts::default();
+            for name in package::list_files(source)? {
+                let source_file = source.join(&name);
+                let target_file = item.target_path.join(&name);
+                let action = if !target_file.exists() {
+                    Some(&mut counts.added)
+                } else if policy == MergePolicy::KeepTheirs
+                    && fs::read(&source_file).ok() != fs::read(&target_file).ok()
+                {
+                    Some(&mut counts.updated)
+                } else {
+                    None
+                };
+                match action {
+                    Some(count) => {
+                        if let Some(parent) = target_file.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        fs::copy(&source_file, &target_file)?;
+                        *count += 1;
+                    }
+                    None => counts.skipped += 1,
+                }
+            }
+            Ok(vec![summary("icons", counts)])
+        }
+        _ => {
+            let data_type = section_data_type(item.section).expect("数据段应对应数据文件");
+            let key = items_key(item.section).expect("数据段应有条目字段");
+            let theirs = read_package_data(item.section, source)?;
+            let mut merged =
+                read_local_data(item)?.unwrap_or_else(|| empty_data(item.section, &theirs));
+            let mut items = merged
+                .get(key)
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let incoming = theirs
+                .get(key)
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let counts = if item.section == LocalDataSection::Actions {
+                merge_enabled_actions(&mut items, incoming)
+            } else {
+                merge::merge_items(&mut items, incoming, policy).counts
+            };
+            merged[key] = Value::Array(items);
+            persisted::write_persisted(data_type, merged)?;
+            Ok(vec![summary(key, counts)])
+        }
+    }
+}
+
+/// 只把所选条目的变化写入本地数据，未列出的条目和设置等其他内容保持不变
+fn apply_item_selection(
+    item: &SectionFile,
+    source: &Path,
+    selections: &[&LocalDataItemSelection],
+) -> Result<Vec<LocalDataMergeSummary>, AppError> {
+    let collections = diff_collections(item.section);
+    let theirs = read_package_data(item.section, source)?;
+    let mut mine = read_local_data(item)?.unwrap_or_else(|| empty_data(item.section, &theirs));
+
+    let mut summary = Vec::new();
+    for selection in selections {
+        if !collections.contains(&selection.collection.as_str()) {
+            return Err(AppError::invalid_input(format!(
+                "{} 不支持按条目导入 {}",
+                item.label, selection.collection
+            )));
+        }
+        let mut items = collection_items(&mine, &selection.collection);
+        let counts = diff::apply_selected(
+            &mut items,
+            &collection_items(&theirs, &selection.collection),
+            &selection.ids,
+        );
+        set_collection_items(&mut mine, &selection.collection, items);
+        summary.push(LocalDataMergeSummary {
+            section: item.section,
+            collection: selection.collection.clone(),
+            counts,
+        });
+    }
+
+    if item.section == LocalDataSection::Config {
+        merge::rebuild_category_members(&mut mine);
+        write_config_value(&mine)?;
+    } else {
+        let data_type = section_data_type(item.section).expect("数据段应对应数据文件");
+        persisted::write_persisted(data_type, mine)?;
+    }
+    Ok(summary)
+}
+
+/// 读取数据包中的一类数据并升级到当前结构，数据外壳只返回 `data` 部分
+fn read_package_data(section: LocalDataSection, source: &Path) -> Result<Value, AppError> {
+    if section == LocalDataSection::Config {
+        return upgrade_config_value(read_json_value(source)?);
+    }
+    let data_type = section_data_type(section).expect("数据段应对应数据文件");
+    let envelope: DataEnvelope<Value> = serde_json::from_value(read_json_value(source)?)?;
+    Ok(data_type
+        .schema()
+        .upgrade(envelope.schema_version, envelope.data)?
+        .value)
+}
+
+/// 读取本地当前数据，格式与 `read_package_data` 相同；本地没有该数据时返回 `None`
+fn read_local_data(item: &SectionFile) -> Result<Option<Value>, AppError> {
+    if item.section == LocalDataSection::Config {
+        return match backend::current().and_then(|backend| backend.read(item.relative_path))? {
+            Some(value) => upgrade_config_value(value).map(Some),
+            None => Ok(None),
+        };
+    }
+    let data_type = section_data_type(item.section).expect("数据段应对应数据文件");
+    Ok(persisted::read_persisted(data_type)?
+        .map(|current| current.data)
+        .filter(Value::is_object))
+}
+
+/// 本地没有数据时，以数据包中的数据为模板、清空其中的条目
+fn empty_data(section: LocalDataSection, theirs: &Value) -> Value {
+    let mut empty = theirs.clone();
+    if section == LocalDataSection::Config {
+        empty["categories"] = Value::Object(Default::default());
+        empty["apps"] = Value::Object(Default::default());
+    } else if let Some(key) = items_key(section) {
+        empty[key] = Value::Array(Vec::new());
+    }
+    empty
+}
+
+/// 与保存设置相同，经由 `save_config_value` 写入并保留历史版本
+fn write_config_value(value: &Value) -> Result<(), AppError> {
+    serde_json::from_value::<crate::models::Config>(value.clone())
+        .map_err(|error| AppError::invalid_input(format!("导入的配置无效: {}", error)))?;
+    crate::utils::config::save_config_value(value).map_err(AppError::from)
+}
+
+/// 可以逐条比较和选择导入的数据集合；快捷动作只是动作名列表，图标按文件处理
+fn diff_collections(section: LocalDataSection) -> &'static [&'static str] {
+    match section {
+        LocalDataSection::Config => &["categories", "apps"],
+        LocalDataSection::Scenes => &["scenes"],
+        LocalDataSection::Notes => &["notes"],
+        LocalDataSection::Todos | LocalDataSection::Clipboard => &["items"],
+        LocalDataSection::Actions | LocalDataSection::Icons => &[],
+    }
+}
+
+/// 分类的 `apps` 由程序的 `category` 和 `extraCategories` 推导，导入后会重新整理，不单独列为差异
+fn ignored_fields(collection: &str) -> &'static [&'static str] {
+    if collection == "categories" {
+        &["apps"]
+    } else {
+        &[]
+    }
+}
+
+/// 取出数据集合中的条目，配置中 `id -> 条目` 形式的对象也转为列表
+fn collection_items(data: &Value, collection: &str) -> Vec<Value> {
+    match data.get(collection) {
+        Some(Value::Object(map)) => merge::map_to_items(map.clone()),
+        Some(Value::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn set_collection_items(data: &mut Value, collection: &str, items: Vec<Value>) {
+    if data.get(collection).is_some_and(Value::is_object) {
+        data[collection] = Value::Object(
+            items
+                .into_iter()
+                .filter_map(|item| Some((merge::item_id(&item)?.to_string(), item)))
+                .collect(),
+        );
+    } else {
+        data[collection] = Value::Array(items);
+    }
+}
+
+fn upgrade_config_value(value: Value) -> Result<Value, AppError> {
+    let version = schema::parse_config_version(&value)?;
+    let mut value = schema::CONFIG_SCHEMA.upgrade(version, value)?.value;
+    value["version"] = Value::String(schema::config_version_string(
+        schema::CONFIG_SCHEMA.current_version(),
+    ));
+    Ok(value)
+}
+
+fn merge_enabled_actions(mine: &mut Vec<Value>, theirs: Vec<Value>) -> MergeCounts {
+    let mut counts = MergeCounts::default();
+    for action in theirs {
+        if mine.contains(&action) {
+            counts.skipped += 1;
+        } else {
+            mine.push(action);
+            counts.added += 1;
+        }
+    }
+    counts
 }
 
 fn normalized_sections(sections: Vec<LocalDataSection>) -> Vec<LocalDataSection> {
@@ -440,48 +1104,92 @@ fn normalized_sections(sections: Vec<LocalDataSection>) -> Vec<LocalDataSection>
     selected
 }
 
-fn read_json_value(path: &Path) -> Result<Value, String> {
-    let content = fs::read_to_string(path).map_err(|error| error.to_string())?;
-    serde_json::from_str(&content).map_err(|error| error.to_string())
+fn read_json_value(path: &Path) -> Result<Value, AppError> {
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(AppError::from)
 }
 
-fn count_items(section: LocalDataSection, value: &Value) -> Option<usize> {
+/// 数据包由更新版本的程序导出时拒绝导入，避免旧程序误读新结构
+fn check_schema_version(section: LocalDataSection, value: Value) -> Result<Value, AppError> {
+    let (registry, version) = match section {
+        LocalDataSection::Config => (
+            &schema::CONFIG_SCHEMA,
+            schema::parse_config_version(&value)?,
+        ),
+        LocalDataSection::Icons => return Ok(value),
+        _ => {
+            let data_type = section_data_type(section).expect("数据段应对应数据文件");
+            let version = value
+                .get("schemaVersion")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| AppError::corrupt("缺少 schemaVersion"))?;
+            (data_type.schema(), version.min(u32::MAX as u64) as u32)
+        }
+    };
+
+    if version > registry.current_version() {
+        return Err(StorageError::SchemaTooNew {
+            name: registry.name.to_string(),
+            found: version,
+            supported: registry.current_version(),
+        }
+        .into());
+    }
+    Ok(value)
+}
+
+fn section_data_type(section: LocalDataSection) -> Option<PersistedDataType> {
     match section {
-        LocalDataSection::Config => value
+        LocalDataSection::Scenes => Some(PersistedDataType::Scenes),
+        LocalDataSection::Notes => Some(PersistedDataType::Notes),
+        LocalDataSection::Todos => Some(PersistedDataType::Todos),
+        LocalDataSection::Clipboard => Some(PersistedDataType::Clipboard),
+        LocalDataSection::Actions => Some(PersistedDataType::Actions),
+        LocalDataSection::Config | LocalDataSection::Icons => None,
+    }
+}
+
+/// 数据外壳 `data` 中条目列表所在的字段
+fn items_key(section: LocalDataSection) -> Option<&'static str> {
+    match section {
+        LocalDataSection::Scenes => Some("scenes"),
+        LocalDataSection::Notes => Some("notes"),
+        LocalDataSection::Todos | LocalDataSection::Clipboard => Some("items"),
+        LocalDataSection::Actions => Some("enabled"),
+        LocalDataSection::Config | LocalDataSection::Icons => None,
+    }
+}
+
+fn count_items(section: LocalDataSection, value: &Value) -> Option<usize> {
+    if section == LocalDataSection::Config {
+        return value
             .get("apps")
             .and_then(Value::as_object)
-            .map(|apps| apps.len()),
-        LocalDataSection::Scenes => value
-            .pointer("/data/scenes")
-            .and_then(Value::as_array)
-            .map(|items| items.len()),
-        LocalDataSection::Notes => value
-            .pointer("/data/notes")
-            .and_then(Value::as_array)
-            .map(|items| items.len()),
-        LocalDataSection::Todos => value
-            .pointer("/data/items")
-            .and_then(Value::as_array)
-            .map(|items| items.len()),
-        LocalDataSection::Clipboard => value
-            .pointer("/data/items")
-            .and_then(Value::as_array)
-            .map(|items| items.len()),
-        LocalDataSection::Actions => value
-            .pointer("/data/enabled")
-            .and_then(Value::as_array)
-            .map(|items| items.len()),
-        LocalDataSection::Icons => None,
+            .map(|apps| apps.len());
     }
+    value
+        .get("data")?
+        .get(items_key(section)?)
+        .and_then(Value::as_array)
+        .map(|items| items.len())
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
         apply_import_with_backup, build_preview, count_items, export_local_data,
-        validate_import_request, LocalDataFileEntry, LocalDataManifest, LocalDataSection,
+        preview_local_data_import, validate_import_request, ImportPlan, LocalDataFileEntry,
+        LocalDataItemSelection, LocalDataManifest, LocalDataSection,
     };
+    use crate::error::AppError;
+    use crate::models::Config;
+    use crate::storage::diff::ItemChangeKind;
+    use crate::storage::history;
+    use crate::storage::merge::MergePolicy;
+    use crate::storage::package::{self, StagingDir};
     use crate::storage::{json_store, paths};
+    use crate::utils::config::save_config;
+    use crate::utils::path_remap::PathRemapRule;
     use serde_json::json;
     use std::fs;
     use std::path::PathBuf;
@@ -560,7 +1268,9 @@ mod tests {
                 path: "scenes.json".to_string(),
                 exists: false,
                 size: None,
+                sha256: None,
             }],
+            encryption: None,
         };
 
         let preview = build_preview(
@@ -632,8 +1342,10 @@ mod tests {
                 path: path.to_string(),
                 exists: true,
                 size: None,
+                sha256: None,
             })
             .collect(),
+            encryption: None,
         };
 
         let preview = build_preview(&dir.join("manifest.json"), &dir, manifest);
@@ -675,11 +1387,17 @@ mod tests {
         fs::create_dir_all(&export_parent).expect("应能创建导出父目录");
         write_sample_local_data();
 
-        let result =
-            export_local_data(export_parent.to_string_lossy().to_string()).expect("导出应成功");
-        let export_dir = PathBuf::from(&result.export_dir);
-
-        assert!(PathBuf::from(&result.manifest_path).is_file());
+        let result = export_local_data(export_parent.to_string_lossy().to_string(), None)
+            .expect("导出应成功");
+        let package_path = PathBuf::from(&result.package_path);
+        assert!(package_path.is_file());
+        assert!(package::is_package_path(&package_path));
+
+        let extracted =
+            StagingDir::create("local-data-export-extracted").expect("应能创建解压目录");
+        package::extract_package(&package_path, extracted.path()).expect("数据包应能解压");
+        let export_dir = extracted.path();
+        assert!(export_dir.join(package::MANIFEST_FILE).is_file());
         assert!(export_dir.join(paths::CONFIG_FILE).is_file());
         assert!(export_dir.join(paths::SCENES_FILE).is_file());
         assert!(export_dir.join(paths::NOTES_FILE).is_file());
@@ -688,6 +1406,10 @@ mod tests {
         assert!(export_dir.join(paths::ACTIONS_FILE).is_file());
         assert!(export_dir.join("icons").join("app-1.png").is_file());
         assert_eq!(result.files.len(), 7);
+        assert!(result
+            .files
+            .iter()
+            .all(|file| file.sha256.as_ref().map(|hash| hash.len()) == Some(64)));
 
         let _ = fs::remove_dir_all(data_dir);
         let _ = fs::remove_dir_all(export_parent);
@@ -706,9 +1428,9 @@ mod tests {
         let package_manifest = {
             let _source_guard = paths::set_test_data_dir(source_dir.clone());
             write_sample_local_data();
-            let result =
-                export_local_data(export_parent.to_string_lossy().to_string()).expect("导出应成功");
-            result.manifest_path
+            let result = export_local_data(export_parent.to_string_lossy().to_string(), None)
+                .expect("导出应成功");
+            result.package_path
         };
 
         {
@@ -726,13 +1448,14 @@ mod tests {
             .expect("应能写入旧便签");
 
             let manifest = PathBuf::from(package_manifest);
-            let (package_dir, selected) = validate_import_request(
+            let (package, selected) = validate_import_request(
                 &manifest,
                 vec![
                     LocalDataSection::Config,
                     LocalDataSection::Notes,
                     LocalDataSection::Config,
                 ],
+                None,
             )
             .expect("导入请求应有效");
             assert_eq!(
@@ -740,7 +1463,10 @@ mod tests {
                 vec![LocalDataSection::Config, LocalDataSection::Notes]
             );
 
-            let backup = apply_import_with_backup(&package_dir, &selected).expect("导入应成功");
+            let (backup, merge_summary) =
+                apply_import_with_backup(&package.dir, &selected, &ImportPlan::default())
+                    .expect("导入应成功");
+            assert!(merge_summary.is_empty());
             assert!(backup.dir.is_dir());
             assert!(backup.files.contains(&paths::CONFIG_FILE.to_string()));
             assert!(backup.files.contains(&paths::NOTES_FILE.to_string()));
@@ -775,6 +1501,7 @@ mod tests {
         let result = apply_import_with_backup(
             &package_dir,
             &[LocalDataSection::Config, LocalDataSection::Icons],
+            &ImportPlan::default(),
         )
         .expect_err("缺失图标目录应导入失败并触发恢复");
 
@@ -786,4 +1513,472 @@ mod tests {
         let _ = fs::remove_dir_all(data_dir);
         let _ = fs::remove_dir_all(package_dir);
     }
+
+    #[test]
+    fn 结构无效的数据不会被导入() {
+        let data_dir = unique_temp_dir("local-data-import-invalid");
+        let package_dir = unique_temp_dir("local-data-import-invalid-package");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        let _ = fs::remove_dir_all(&package_dir);
+        fs::create_dir_all(&package_dir).expect("应能创建数据包目录");
+
+        json_store::write_enveloped_json(
+            &paths::notes_path(),
+            1,
+            json!({ "notes": [{ "id": "old-note", "content": "旧便签", "createdAt": 1, "updatedAt": 1 }] }),
+        )
+        .expect("应能写入原便签");
+        json_store::write_enveloped_json(
+            &package_dir.join(paths::NOTES_FILE),
+            1,
+            json!({ "notes": [{ "id": "new-note", "content": "缺少时间的便签" }] }),
+        )
+        .expect("应能写入待导入便签");
+
+        for plan in [
+            ImportPlan::default(),
+            ImportPlan {
+                merge_policy: Some(MergePolicy::KeepTheirs),
+                ..ImportPlan::default()
+            },
+        ] {
+            let result = apply_import_with_backup(&package_dir, &[LocalDataSection::Notes], &plan)
+                .expect_err("结构无效的便签应被拒绝");
+            assert!(!result.success);
+            assert!(
+                result.errors[0].contains("missing field `createdAt`"),
+                "{:?}",
+                result.errors
+            );
+            let notes_text = fs::read_to_string(paths::notes_path()).expect("应能读取原便签");
+            assert!(notes_text.contains("old-note"));
+            assert!(!notes_text.contains("new-note"));
+        }
+
+        let _ = fs::remove_dir_all(data_dir);
+        let _ = fs::remove_dir_all(package_dir);
+    }
+
+    #[test]
+    fn 覆盖导入配置时保留被替换的配置并拒绝覆盖更新版本的配置() {
+        let data_dir = unique_temp_dir("local-data-import-config");
+        let package_dir = unique_temp_dir("local-data-import-config-package");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        let _ = fs::remove_dir_all(&package_dir);
+        fs::create_dir_all(&package_dir).expect("应能创建数据包目录");
+
+        let mut local = Config::default();
+        local.settings.last_category = Some("local".to_string());
+        save_config(&local).expect("应能保存本地配置");
+        let mut imported = serde_json::to_value(Config::default()).unwrap();
+        imported["settings"]["lastCategory"] = json!("imported");
+        imported["futureOption"] = json!(true);
+        json_store::write_json(&package_dir.join(paths::CONFIG_FILE), &imported)
+            .expect("应能写入待导入配置");
+
+        apply_import_with_backup(
+            &package_dir,
+            &[LocalDataSection::Config],
+            &ImportPlan::default(),
+        )
+        .expect("导入配置应成功");
+        let stored: serde_json::Value =
+            json_store::read_json(&paths::config_path()).expect("应能读取配置");
+        assert_eq!(stored["settings"]["lastCategory"], "imported");
+        assert_eq!(stored["futureOption"], true, "保留未识别的字段");
+        assert_eq!(
+            history::list_versions(paths::CONFIG_FILE).unwrap().len(),
+            1,
+            "被覆盖的配置应保留为历史版本"
+        );
+
+        let mut newer = stored.clone();
+        newer["version"] = json!("99.0.0");
+        json_store::write_json(&paths::config_path(), &newer).expect("应能写入更新版本的配置");
+        let result = apply_import_with_backup(
+            &package_dir,
+            &[LocalDataSection::Config],
+            &ImportPlan::default(),
+        )
+        .expect_err("不应覆盖更新版本的配置");
+        assert!(!result.success);
+
+        let _ = fs::remove_dir_all(data_dir);
+        let _ = fs::remove_dir_all(package_dir);
+    }
+
+    #[test]
+    fn 更新版本程序导出的数据包会在预览中报错() {
+        let dir = unique_temp_dir("local-data-preview-too-new");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("应能创建测试目录");
+        fs::write(
+            dir.join("notes.json"),
+            r#"{"schemaVersion":99,"updatedAt":1,"data":{"notes":[]}}"#,
+        )
+        .expect("应能写入便签");
+
+        let manifest = LocalDataManifest {
+            format_version: 1,
+            app_version: "9.9.9".to_string(),
+            exported_at: 1,
+            files: vec![LocalDataFileEntry {
+                section: LocalDataSection::Notes,
+                path: "notes.json".to_string(),
+                exists: true,
+                size: None,
+                sha256: None,
+            }],
+            encryption: None,
+        };
+
+        let preview = build_preview(&dir.join("manifest.json"), &dir, manifest);
+
+        assert_eq!(preview.errors.len(), 1);
+        assert!(preview.errors[0].contains("99"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn 被篡改或截断的数据包会被拒绝导入() {
+        let data_dir = unique_temp_dir("local-data-tampered-source");
+        let export_parent = unique_temp_dir("local-data-tampered-package");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        let _ = fs::remove_dir_all(&export_parent);
+        fs::create_dir_all(&export_parent).expect("应能创建导出父目录");
+        write_sample_local_data();
+
+        let result = export_local_data(export_parent.to_string_lossy().to_string(), None)
+            .expect("导出应成功");
+        let package_path = PathBuf::from(&result.package_path);
+        let intact =
+            preview_local_data_import(result.package_path.clone(), None).expect("预览应成功");
+        assert!(intact.errors.is_empty());
+
+        // 改动数据包内的便签后重新打包，清单中的校验和保持不变
+        let extracted = StagingDir::create("local-data-tampered").expect("应能创建解压目录");
+        package::extract_package(&package_path, extracted.path()).expect("数据包应能解压");
+        fs::write(
+            extracted.path().join(paths::NOTES_FILE),
+            r#"{"schemaVersion":1,"updatedAt":1,"data":{"notes":[{"id":"evil"}]}}"#,
+        )
+        .expect("应能改动便签");
+        let tampered_path = export_parent.join("tampered.pmpkg");
+        package::write_package(extracted.path(), &tampered_path).expect("应能重新打包");
+
+        let tampered = preview_local_data_import(tampered_path.to_string_lossy().to_string(), None)
+            .expect("篡改的数据包仍可预览");
+        assert_eq!(tampered.errors.len(), 1);
+        assert!(tampered.errors[0].contains(paths::NOTES_FILE));
+        assert!(
+            validate_import_request(&tampered_path, vec![LocalDataSection::Config], None).is_err()
+        );
+
+        let bytes = fs::read(&package_path).expect("应能读取数据包");
+        let truncated_path = export_parent.join("truncated.pmpkg");
+        fs::write(&truncated_path, &bytes[..bytes.len() / 2]).expect("应能写入截断的数据包");
+        assert!(
+            preview_local_data_import(truncated_path.to_string_lossy().to_string(), None).is_err()
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+        let _ = fs::remove_dir_all(export_parent);
+    }
+
+    #[test]
+    fn 加密数据包需要正确口令才能预览() {
+        let data_dir = unique_temp_dir("local-data-encrypted-source");
+        let export_parent = unique_temp_dir("local-data-encrypted-package");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        let _ = fs::remove_dir_all(&export_parent);
+        fs::create_dir_all(&export_parent).expect("应能创建导出父目录");
+        write_sample_local_data();
+
+        let result = export_local_data(
+            export_parent.to_string_lossy().to_string(),
+            Some("correct horse".to_string()),
+        )
+        .expect("导出应成功");
+        assert!(result.encrypted);
+
+        // 外层只有加密参数和密文，看不到任何数据文件
+        let outer = StagingDir::create("local-data-encrypted-outer").expect("应能创建解压目录");
+        package::extract_package(&PathBuf::from(&result.package_path), outer.path())
+            .expect("数据包应能解压");
+        assert_eq!(
+            package::list_files(outer.path()).expect("应能列出文件"),
+            vec![
+                package::MANIFEST_FILE.to_string(),
+                "payload.bin".to_string()
+            ]
+        );
+
+        let missing = preview_local_data_import(result.package_path.clone(), None)
+            .expect_err("未提供口令应失败");
+        assert!(matches!(missing, AppError::PassphraseRequired));
+        let wrong = preview_local_data_import(
+            result.package_path.clone(),
+            Some("battery staple".to_string()),
+        )
+        .expect_err("错误口令应失败");
+        assert!(matches!(wrong, AppError::WrongPassphrase));
+
+        let preview = preview_local_data_import(
+            result.package_path.clone(),
+            Some("correct horse".to_string()),
+        )
+        .expect("正确口令应能预览");
+        assert!(preview.encrypted);
+        assert!(preview.errors.is_empty());
+        assert_eq!(
+            preview
+                .sections
+                .iter()
+                .find(|section| section.section == LocalDataSection::Notes)
+                .and_then(|section| section.item_count),
+            Some(1)
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+        let _ = fs::remove_dir_all(export_parent);
+    }
+
+    #[test]
+    fn 合并导入按_id_合并并保留本地独有数据() {
+        let source_dir = unique_temp_dir("local-data-merge-source");
+        let target_dir = unique_temp_dir("local-data-merge-target");
+        let export_parent = unique_temp_dir("local-data-merge-package");
+        let _ = fs::remove_dir_all(&source_dir);
+        let _ = fs::remove_dir_all(&target_dir);
+        let _ = fs::remove_dir_all(&export_parent);
+        fs::create_dir_all(&export_parent).expect("应能创建导出父目录");
+
+        let package_path = {
+            let _source_guard = paths::set_test_data_dir(source_dir.clone());
+            write_sample_local_data();
+            export_local_data(export_parent.to_string_lossy().to_string(), None)
+                .expect("导出应成功")
+                .package_path
+        };
+
+        let _target_guard = paths::set_test_data_dir(target_dir.clone());
+        fs::write(
+            paths::config_path(),
+            r#"{"version":"1.0","categories":{},"apps":{"old-app":{"id":"old-app","name":"旧应用","path":"D:\\Old\\old.exe","category":"cat-1","itemType":"app","createdAt":1}},"settings":{"theme":"dark"}}"#,
+        )
+        .expect("应能写入本地配置");
+        json_store::write_enveloped_json(
+            &paths::notes_path(),
+            1,
+            json!({ "notes": [
+                { "id": "old-note", "content": "本地独有", "createdAt": 1, "updatedAt": 1 },
+                { "id": "note-1", "content": "本地较新", "createdAt": 1, "updatedAt": 5 }
+            ] }),
+        )
+        .expect("应能写入本地便签");
+
+        let (package, selected) = validate_import_request(
+            &PathBuf::from(package_path),
+            vec![LocalDataSection::Config, LocalDataSection::Notes],
+            None,
+        )
+        .expect("导入请求应有效");
+        let (_, summary) = apply_import_with_backup(
+            &package.dir,
+            &selected,
+            &ImportPlan {
+                merge_policy: Some(MergePolicy::Newer),
+                ..Default::default()
+            },
+        )
+        .expect("合并导入应成功");
+
+        let counts = |collection: &str| {
+            summary
+                .iter()
+                .find(|item| item.collection == collection)
+                .map(|item| item.counts.clone())
+                .expect("应有合并统计")
+        };
+        assert_eq!(counts("apps").added, 1);
+        assert_eq!(counts("notes").skipped, 1);
+        assert_eq!(counts("notes").added, 0);
+
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(paths::config_path()).unwrap()).unwrap();
+        assert!(config["apps"].get("old-app").is_some());
+        assert!(config["apps"].get("app-1").is_some());
+        assert_eq!(config["settings"]["theme"], "dark");
+        let notes = fs::read_to_string(paths::notes_path()).expect("应能读取便签");
+        assert!(notes.contains("本地独有"));
+        assert!(notes.contains("本地较新"));
+
+        let _ = fs::remove_dir_all(source_dir);
+        let _ = fs::remove_dir_all(target_dir);
+        let _ = fs::remove_dir_all(export_parent);
+    }
+
+    #[test]
+    fn 预览列出条目差异并可按条目选择导入() {
+        let source_dir = unique_temp_dir("local-data-diff-source");
+        let target_dir = unique_temp_dir("local-data-diff-target");
+        let export_parent = unique_temp_dir("local-data-diff-package");
+        let _ = fs::remove_dir_all(&source_dir);
+        let _ = fs::remove_dir_all(&target_dir);
+        let _ = fs::remove_dir_all(&export_parent);
+        fs::create_dir_all(&export_parent).expect("应能创建导出父目录");
+
+        let package_path = {
+            let _source_guard = paths::set_test_data_dir(source_dir.clone());
+            write_sample_local_data();
+            export_local_data(export_parent.to_string_lossy().to_string(), None)
+                .expect("导出应成功")
+                .package_path
+        };
+
+        let _target_guard = paths::set_test_data_dir(target_dir.clone());
+        fs::write(
+            paths::config_path(),
+            r#"{"version":"1.1.4","categories":{},"apps":{"app-1":{"id":"app-1","name":"旧编辑器","path":"D:\\Tools\\editor.exe","category":"cat-1","itemType":"app","createdAt":1},"old-app":{"id":"old-app","name":"旧应用","path":"D:\\Old\\old.exe","category":"cat-1","itemType":"app","createdAt":1}},"settings":{"cardSize":"medium","theme":"dark","sortBy":"lastLaunched"}}"#,
+        )
+        .expect("应能写入本地配置");
+
+        let preview = preview_local_data_import(package_path.clone(), None).expect("预览应成功");
+        let config = preview
+            .sections
+            .iter()
+            .find(|section| section.section == LocalDataSection::Config)
+            .expect("应存在主配置预览");
+        let apps = config
+            .changes
+            .iter()
+            .find(|collection| collection.collection == "apps")
+            .expect("应列出程序差异");
+        let changed = apps
+            .items
+            .iter()
+            .find(|item| item.id == "app-1")
+            .expect("app-1 应有差异");
+        assert_eq!(changed.kind, ItemChangeKind::Changed);
+        assert_eq!(changed.fields.len(), 1);
+        assert_eq!(changed.fields[0].field, "name");
+        assert!(apps
+            .items
+            .iter()
+            .any(|item| item.id == "old-app" && item.kind == ItemChangeKind::Removed));
+        let notes = preview
+            .sections
+            .iter()
+            .find(|section| section.section == LocalDataSection::Notes)
+            .expect("应存在便签预览");
+        assert_eq!(notes.changes[0].items[0].kind, ItemChangeKind::Added);
+
+        let (package, selected) = validate_import_request(
+            &PathBuf::from(package_path),
+            vec![LocalDataSection::Config],
+            None,
+        )
+        .expect("导入请求应有效");
+        let (_, summary) = apply_import_with_backup(
+            &package.dir,
+            &selected,
+            &ImportPlan {
+                items: &[LocalDataItemSelection {
+                    section: LocalDataSection::Config,
+                    collection: "apps".to_string(),
+                    ids: vec!["app-1".to_string()],
+                }],
+                ..Default::default()
+            },
+        )
+        .expect("按条目导入应成功");
+
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].counts.updated, 1);
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(paths::config_path()).unwrap()).unwrap();
+        assert_eq!(config["apps"]["app-1"]["name"], "编辑器");
+        assert!(
+            config["apps"].get("old-app").is_some(),
+            "未选择的删除不应生效"
+        );
+        assert_eq!(config["settings"]["theme"], "dark");
+
+        let _ = fs::remove_dir_all(source_dir);
+        let _ = fs::remove_dir_all(target_dir);
+        let _ = fs::remove_dir_all(export_parent);
+    }
+
+    #[test]
+    fn 导入配置时按规则改写程序路径并给出建议() {
+        let source_dir = unique_temp_dir("local-data-remap-source");
+        let target_dir = unique_temp_dir("local-data-remap-target");
+        let export_parent = unique_temp_dir("local-data-remap-package");
+        let _ = fs::remove_dir_all(&source_dir);
+        let _ = fs::remove_dir_all(&target_dir);
+        let _ = fs::remove_dir_all(&export_parent);
+        fs::create_dir_all(&export_parent).expect("应能创建导出父目录");
+
+        // 源电脑上的程序目录在本机换了位置
+        let old_root = source_dir.join("old-tools");
+        let new_root = target_dir.join("new-tools");
+        fs::create_dir_all(&new_root).expect("应能创建新程序目录");
+        fs::write(new_root.join("editor.exe"), b"exe").expect("应能写入程序");
+        let old_path = old_root.join("editor.exe").to_string_lossy().to_string();
+
+        let package_path = {
+            let _source_guard = paths::set_test_data_dir(source_dir.clone());
+            let config = json!({
+                "version": "1.1.4",
+                "categories": {},
+                "apps": { "app-1": { "id": "app-1", "name": "编辑器", "path": old_path, "category": "cat-1", "itemType": "app", "createdAt": 1 } },
+                "settings": { "cardSize": "medium", "theme": "auto", "sortBy": "lastLaunched" }
+            });
+            fs::write(paths::config_path(), config.to_string()).expect("应能写入配置");
+            export_local_data(export_parent.to_string_lossy().to_string(), None)
+                .expect("导出应成功")
+                .package_path
+        };
+
+        let _target_guard = paths::set_test_data_dir(target_dir.clone());
+        let preview = preview_local_data_import(package_path.clone(), None).expect("预览应成功");
+        assert!(preview.errors.is_empty());
+        let (package, selected) = validate_import_request(
+            &PathBuf::from(package_path),
+            vec![LocalDataSection::Config],
+            None,
+        )
+        .expect("导入请求应有效");
+        let rules = [PathRemapRule::Prefix {
+            from: old_root.to_string_lossy().to_string(),
+            to: new_root.to_string_lossy().to_string(),
+        }];
+        apply_import_with_backup(
+            &package.dir,
+            &selected,
+            &ImportPlan {
+                path_rules: &rules,
+                ..Default::default()
+            },
+        )
+        .expect("导入应成功");
+
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(paths::config_path()).unwrap()).unwrap();
+        assert_eq!(
+            config["apps"]["app-1"]["path"],
+            new_root.join("editor.exe").to_string_lossy().as_ref()
+        );
+        let packaged = fs::read_to_string(package.dir.join(paths::CONFIG_FILE)).unwrap();
+        assert!(packaged.contains("old-tools"), "数据包内容不应被改写");
+
+        let _ = fs::remove_dir_all(source_dir);
+        let _ = fs::remove_dir_all(target_dir);
+        let _ = fs::remove_dir_all(export_parent);
+    }
 }
@@ -1,6 +1,12 @@
 use crate::commands::config::current_unix_secs;
-use crate::models::{AppState, UpdateMetadata};
+use crate::error::AppError;
+use crate::models::{AppState, Config, ItemType, UpdateConfidence, UpdateMetadata, UpdateStatus};
+use crate::storage::paths;
+use crate::storage::persisted::{self, PersistedDataType};
+use crate::storage::usage::{self, IconFile, SectionUsage};
+use crate::utils::integrity::{self, IntegrityIssue};
 use serde::Serialize;
+use std::collections::HashSet;
 use tauri::{AppHandle, Emitter, State};
 
 #[derive(Serialize)]
@@ -33,7 +39,7 @@ pub struct UpdateCheckResult {
     pub app_id: String,
     pub app_name: String,
     pub has_update: bool,
-    pub confidence: String,
+    pub confidence: UpdateConfidence,
     pub details: UpdateCheckDetails,
 }
 
@@ -103,7 +109,7 @@ fn emit_progress(
 pub fn validate_all_apps(
     app_handle: AppHandle,
     state: State<AppState>,
-) -> Result<Vec<ValidationResult>, String> {
+) -> Result<Vec<ValidationResult>, AppError> {
     let apps_snapshot = {
         let config = state.config.lock().unwrap();
         config
@@ -154,22 +160,22 @@ pub fn validate_all_apps(
 }
 
 #[tauri::command]
-pub fn init_update_baseline(app_id: String, state: State<AppState>) -> Result<(), String> {
+pub fn init_update_baseline(app_id: String, state: State<AppState>) -> Result<(), AppError> {
     let (path, item_type) = {
         let config = state.config.lock().unwrap();
         let app = config
             .apps
             .get(&app_id)
-            .ok_or_else(|| "应用不存在".to_string())?;
+            .ok_or_else(|| AppError::not_found("应用"))?;
         (app.path.clone(), app.item_type.clone())
     };
 
-    if item_type != "app" {
+    if item_type != ItemType::App {
         return Ok(());
     }
 
     let (size, modified_time) = crate::utils::app_validator::get_file_metadata(&path)
-        .ok_or_else(|| "无法读取文件元数据".to_string())?;
+        .ok_or_else(|| AppError::internal("无法读取文件元数据"))?;
 
     #[cfg(target_os = "windows")]
     let version = crate::utils::update_checker::get_version_from_registry(&path);
@@ -181,18 +187,18 @@ pub fn init_update_baseline(app_id: String, state: State<AppState>) -> Result<()
     let app = config
         .apps
         .get_mut(&app_id)
-        .ok_or_else(|| "应用不存在".to_string())?;
+        .ok_or_else(|| AppError::not_found("应用"))?;
 
     app.update_metadata = Some(UpdateMetadata {
         baseline_version: version,
         baseline_file_size: Some(size),
         baseline_modified_time: Some(modified_time),
         last_checked_at: Some(current_unix_secs()),
-        update_status: Some("none".to_string()),
+        update_status: Some(UpdateStatus::UpToDate),
         update_confidence: None,
     });
 
-    crate::utils::config::save_config(&config).map_err(|e| e.to_string())?;
+    crate::utils::config::save_config(&config)?;
     Ok(())
 }
 
@@ -200,13 +206,13 @@ pub fn init_update_baseline(app_id: String, state: State<AppState>) -> Result<()
 pub fn init_all_baselines(
     app_handle: AppHandle,
     state: State<AppState>,
-) -> Result<BatchOperationResult, String> {
+) -> Result<BatchOperationResult, AppError> {
     let apps_snapshot = {
         let config = state.config.lock().unwrap();
         config
             .apps
             .iter()
-            .filter(|(_, app)| app.item_type == "app")
+            .filter(|(_, app)| app.item_type == ItemType::App)
             .map(|(app_id, app)| {
                 (
                     app_id.clone(),
@@ -259,7 +265,7 @@ pub fn init_all_baselines(
                     baseline_file_size: Some(size),
                     baseline_modified_time: Some(modified_time),
                     last_checked_at: Some(now),
-                    update_status: Some("none".to_string()),
+                    update_status: Some(UpdateStatus::UpToDate),
                     update_confidence: None,
                 },
             ));
@@ -303,7 +309,7 @@ pub fn init_all_baselines(
         }
     }
 
-    crate::utils::config::save_config(&config).map_err(|e| e.to_string())?;
+    crate::utils::config::save_config(&config)?;
 
     Ok(BatchOperationResult {
         total,
@@ -318,13 +324,13 @@ pub fn init_all_baselines(
 pub fn check_app_update(
     app_id: String,
     state: State<AppState>,
-) -> Result<UpdateCheckResult, String> {
+) -> Result<UpdateCheckResult, AppError> {
     let (app_name, path, item_type, metadata) = {
         let config = state.config.lock().unwrap();
         let app = config
             .apps
             .get(&app_id)
-            .ok_or_else(|| "应用不存在".to_string())?;
+            .ok_or_else(|| AppError::not_found("应用"))?;
         (
             app.name.clone(),
             app.path.clone(),
@@ -333,12 +339,12 @@ pub fn check_app_update(
         )
     };
 
-    if item_type != "app" {
+    if item_type != ItemType::App {
         return Ok(UpdateCheckResult {
             app_id: app_id.clone(),
             app_name,
             has_update: false,
-            confidence: "low".to_string(),
+            confidence: UpdateConfidence::Low,
             details: UpdateCheckDetails {
                 old_version: None,
                 new_version: None,
@@ -375,13 +381,13 @@ pub fn check_app_update(
 pub fn check_all_updates(
     app_handle: AppHandle,
     state: State<AppState>,
-) -> Result<Vec<UpdateCheckResult>, String> {
+) -> Result<Vec<UpdateCheckResult>, AppError> {
     let apps_snapshot = {
         let config = state.config.lock().unwrap();
         config
             .apps
             .iter()
-            .filter(|(_, app)| app.item_type == "app")
+            .filter(|(_, app)| app.item_type == ItemType::App)
             .map(|(app_id, app)| {
                 let metadata = app.update_metadata.clone();
                 (
@@ -466,7 +472,7 @@ pub fn batch_delete_apps(
     app_handle: AppHandle,
     app_ids: Vec<String>,
     state: State<AppState>,
-) -> Result<BatchOperationResult, String> {
+) -> Result<BatchOperationResult, AppError> {
     let mut config = state.config.lock().unwrap();
     let total = app_ids.len();
     let mut completed = 0;
@@ -522,7 +528,7 @@ pub fn batch_delete_apps(
         }
     }
 
-    crate::utils::config::save_config(&config).map_err(|e| e.to_string())?;
+    crate::utils::config::save_config(&config)?;
 
     Ok(BatchOperationResult {
         total,
@@ -532,3 +538,174 @@ pub fn batch_delete_apps(
         errors,
     })
 }
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingIcon {
+    pub app_id: String,
+    pub app_name: String,
+    pub icon: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    pub data_dir: String,
+    pub sections: Vec<SectionUsage>,
+    pub total_bytes: u64,
+    /// 图标目录中没有任何项目引用的文件
+    pub orphaned_icons: Vec<IconFile>,
+    /// 项目引用了但图标目录中不存在的文件
+    pub missing_icons: Vec<MissingIcon>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IconGcResult {
+    pub removed: Vec<IconFile>,
+    pub freed_bytes: u64,
+    /// 重新提取到图标的应用
+    pub reextracted: Vec<String>,
+    /// 无法重新提取（文件夹、网址等）而清除了图标引用的项目
+    pub cleared: Vec<String>,
+    pub errors: Vec<ErrorInfo>,
+}
+
+fn referenced_icons(config: &Config) -> HashSet<&str> {
+    config
+        .apps
+        .values()
+        .filter_map(|app| app.icon.as_deref())
+        .filter(|icon| usage::is_icon_file_name(icon))
+        .collect()
+}
+
+fn missing_icons(config: &Config) -> Vec<MissingIcon> {
+    let mut missing: Vec<MissingIcon> = config
+        .apps
+        .values()
+        .filter_map(|app| {
+            let icon = app.icon.as_deref()?;
+            (usage::is_icon_file_name(icon) && !usage::icon_exists(icon)).then(|| MissingIcon {
+                app_id: app.id.clone(),
+                app_name: app.name.clone(),
+                icon: icon.to_string(),
+            })
+        })
+        .collect();
+    missing.sort_by(|left, right| left.app_name.cmp(&right.app_name));
+    missing
+}
+
+#[tauri::command]
+pub fn get_storage_usage(state: State<AppState>) -> Result<StorageUsage, AppError> {
+    let config = state.config.lock().unwrap();
+    let sections = usage::section_usage()?;
+    Ok(StorageUsage {
+        data_dir: paths::data_dir().to_string_lossy().to_string(),
+        total_bytes: sections.iter().map(|section| section.bytes).sum(),
+        sections,
+        orphaned_icons: usage::orphaned_icons(&referenced_icons(&config))?,
+        missing_icons: missing_icons(&config),
+    })
+}
+
+/// 重新提取缺失的图标，再删除没有被引用的图标文件
+#[tauri::command]
+pub fn collect_icon_garbage(
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<IconGcResult, AppError> {
+    let mut config = state.config.lock().unwrap();
+    let mut reextracted = Vec::new();
+    let mut cleared = Vec::new();
+    let mut errors = Vec::new();
+
+    for missing in missing_icons(&config) {
+        let Some(app) = config.apps.get_mut(&missing.app_id) else {
+            continue;
+        };
+        if app.item_type != ItemType::App {
+            app.icon = None;
+            cleared.push(app.id.clone());
+            continue;
+        }
+        match crate::utils::icon_extractor::extract_icon_to_file(&app.path, &app.id) {
+            Ok(icon_filename) => {
+                app.icon = Some(icon_filename);
+                reextracted.push(app.id.clone());
+            }
+            Err(error) => errors.push(ErrorInfo {
+                app_id: app.id.clone(),
+                error: error.to_string(),
+            }),
+        }
+    }
+
+    if !reextracted.is_empty() || !cleared.is_empty() {
+        crate::utils::config::save_config(&config)?;
+        app_handle.emit("config-changed", &*config)?;
+    }
+
+    let removed = usage::remove_icons(&usage::orphaned_icons(&referenced_icons(&config))?);
+    Ok(IconGcResult {
+        freed_bytes: removed.iter().map(|icon| icon.bytes).sum(),
+        removed,
+        reextracted,
+        cleared,
+        errors,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub issues: Vec<IntegrityIssue>,
+    pub repaired: usize,
+}
+
+/// 检查配置、场景和待办之间的引用与格式以及所有数据文件的结构，`repair` 为 `true` 时修复能自动修复的问题
+#[tauri::command]
+pub fn check_data_integrity(
+    repair: bool,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<IntegrityReport, AppError> {
+    let mut config = state.config.lock().unwrap();
+    let mut next_config = config.clone();
+    let mut issues = integrity::check_config(&mut next_config, repair);
+    let config_repaired = issues.iter().any(|issue| issue.repaired);
+
+    let app_ids: HashSet<&str> = next_config.apps.keys().map(String::as_str).collect();
+    let mut persisted_repairs = Vec::new();
+    for data_type in PersistedDataType::ALL {
+        let Some(mut envelope) = persisted::read_persisted(data_type)? else {
+            continue;
+        };
+        let found = match data_type {
+            PersistedDataType::Scenes => {
+                integrity::check_scenes(&mut envelope.data, &app_ids, repair)
+            }
+            PersistedDataType::Todos => integrity::check_todos(&mut envelope.data, repair),
+            _ => integrity::check_structure(data_type, &envelope.data),
+        };
+        if found.iter().any(|issue| issue.repaired) {
+            persisted_repairs.push((data_type, envelope.data));
+        }
+        issues.extend(found);
+    }
+
+    if config_repaired {
+        crate::utils::config::save_config(&next_config)?;
+        *config = next_config;
+        app_handle.emit("config-changed", &*config)?;
+    }
+    for (data_type, data) in persisted_repairs {
+        persisted::write_persisted(data_type, data)?;
+    }
+
+    Ok(IntegrityReport {
+        repaired: issues.iter().filter(|issue| issue.repaired).count(),
+        issues,
+    })
+}
@@ -1,15 +1,25 @@
 pub mod apps;
+pub mod backups;
+pub mod categories;
 pub mod config;
+pub mod history;
 pub mod integration;
 pub mod local_data;
 pub mod maintenance;
 pub mod network;
+pub mod rules;
 pub mod scenes;
+pub mod tags;
 
 pub use apps::*;
+pub use backups::*;
+pub use categories::*;
 pub use config::*;
+pub use history::*;
 pub use integration::*;
 pub use local_data::*;
 pub use maintenance::*;
 pub use network::*;
+pub use rules::*;
 pub use scenes::*;
+pub use tags::*;
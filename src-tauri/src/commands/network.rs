@@ -1,7 +1,8 @@
+use crate::error::AppError;
 use serde::Serialize;
 
 #[tauri::command]
-pub async fn fetch_image_as_base64(url: String) -> Result<String, String> {
+pub async fn fetch_image_as_base64(url: String) -> Result<String, AppError> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -32,24 +33,27 @@ try {{
             .args(&["-NoProfile", "-Command", &script])
             .creation_flags(CREATE_NO_WINDOW)
             .output()
-            .map_err(|e| format!("执行失败: {}", e))?;
+            .map_err(|e| AppError::internal(format!("执行失败: {}", e)))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("获取图片失败: {}", stderr.trim()));
+            return Err(AppError::internal(format!(
+                "获取图片失败: {}",
+                stderr.trim()
+            )));
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
         if stdout.starts_with("data:image/") {
             Ok(stdout)
         } else {
-            Err("返回数据不是有效的图片".to_string())
+            Err(AppError::internal("返回数据不是有效的图片"))
         }
     }
 
     #[cfg(not(target_os = "windows"))]
     {
-        Err("此功能仅支持 Windows".to_string())
+        Err(AppError::platform_unsupported("获取网络图片"))
     }
 }
 
@@ -65,7 +69,7 @@ pub struct AppUpdateInfo {
 }
 
 #[tauri::command]
-pub async fn check_app_version_update() -> Result<AppUpdateInfo, String> {
+pub async fn check_app_version_update() -> Result<AppUpdateInfo, AppError> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -102,16 +106,19 @@ try {
             .args(&["-NoProfile", "-Command", script])
             .creation_flags(CREATE_NO_WINDOW)
             .output()
-            .map_err(|e| format!("检查更新失败: {}", e))?;
+            .map_err(|e| AppError::internal(format!("检查更新失败: {}", e)))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("检查更新失败: {}", stderr.trim()));
+            return Err(AppError::internal(format!(
+                "检查更新失败: {}",
+                stderr.trim()
+            )));
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let json: serde_json::Value =
-            serde_json::from_str(&stdout).map_err(|e| format!("解析更新信息失败: {}", e))?;
+        let json: serde_json::Value = serde_json::from_str(&stdout)
+            .map_err(|e| AppError::internal(format!("解析更新信息失败: {}", e)))?;
 
         let tag = json["tag_name"].as_str().unwrap_or("").to_string();
         let latest_version = tag.trim_start_matches('v').to_string();
@@ -132,7 +139,7 @@ try {
 
     #[cfg(not(target_os = "windows"))]
     {
-        Err("此功能仅支持 Windows".to_string())
+        Err(AppError::platform_unsupported("检查更新"))
     }
 }
 
@@ -0,0 +1,47 @@
+use crate::commands::config::update_config;
+use crate::error::AppError;
+use crate::models::{AppState, CategoryRule};
+use crate::utils::rules::{self, RuleChange};
+use tauri::{AppHandle, State};
+
+/// 保存全部自动分类规则，规则按列表顺序匹配
+#[tauri::command]
+pub fn save_category_rules(
+    rules: Vec<CategoryRule>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<(), AppError> {
+    update_config(&state, &app_handle, |config| {
+        rules::validate_rules(config, &rules)?;
+        config.category_rules = rules;
+        Ok(())
+    })
+}
+
+/// 预览对已有程序重新应用规则的结果，不修改配置；`app_ids` 为空时检查全部程序
+#[tauri::command]
+pub fn preview_category_rules(
+    app_ids: Option<Vec<String>>,
+    state: State<AppState>,
+) -> Result<Vec<RuleChange>, AppError> {
+    let config = state.config.lock().unwrap().clone();
+    rules::preview_changes(&config, app_ids.as_deref(), |app| {
+        rules::lookup_publisher(&app.path, &app.item_type)
+    })
+}
+
+/// 对已有程序重新应用规则，返回实际发生的变化
+#[tauri::command]
+pub fn apply_category_rules(
+    app_ids: Option<Vec<String>>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<Vec<RuleChange>, AppError> {
+    update_config(&state, &app_handle, |config| {
+        let changes = rules::preview_changes(config, app_ids.as_deref(), |app| {
+            rules::lookup_publisher(&app.path, &app.item_type)
+        })?;
+        rules::apply_changes(config, &changes)?;
+        Ok(changes)
+    })
+}
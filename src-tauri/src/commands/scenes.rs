@@ -1,3 +1,4 @@
+use crate::error::AppError;
 use serde::Serialize;
 use std::time::Instant;
 
@@ -41,7 +42,7 @@ pub fn is_process_running(process_name: String) -> bool {
 }
 
 #[tauri::command]
-pub fn send_keys(keys: String) -> Result<(), String> {
+pub fn send_keys(keys: String) -> Result<(), AppError> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -62,19 +63,19 @@ Start-Sleep -Milliseconds 100
             .args(&["-NoProfile", "-Command", &script])
             .creation_flags(CREATE_NO_WINDOW)
             .output()
-            .map_err(|e| format!("发送按键失败: {}", e))?;
+            .map_err(|e| AppError::internal(format!("发送按键失败: {}", e)))?;
 
         Ok(())
     }
 
     #[cfg(not(target_os = "windows"))]
     {
-        Err("发送按键仅支持 Windows".to_string())
+        Err(AppError::platform_unsupported("发送按键"))
     }
 }
 
 #[tauri::command]
-pub fn wait_for_window(title: String, timeout_secs: u32) -> Result<bool, String> {
+pub fn wait_for_window(title: String, timeout_secs: u32) -> Result<bool, AppError> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -141,7 +142,7 @@ if ([WindowHelper]::FindWindowByTitle("{}")) {{ "FOUND" }} else {{ "NOTFOUND" }}
                 .args(&["-NoProfile", "-Command", &script])
                 .creation_flags(CREATE_NO_WINDOW)
                 .output()
-                .map_err(|e| format!("检测窗口失败: {}", e))?;
+                .map_err(|e| AppError::internal(format!("检测窗口失败: {}", e)))?;
 
             let stdout = String::from_utf8_lossy(&output.stdout);
             if stdout.contains("FOUND") {
@@ -156,7 +157,7 @@ if ([WindowHelper]::FindWindowByTitle("{}")) {{ "FOUND" }} else {{ "NOTFOUND" }}
 
     #[cfg(not(target_os = "windows"))]
     {
-        Err("等待窗口仅支持 Windows".to_string())
+        Err(AppError::platform_unsupported("等待窗口"))
     }
 }
 
@@ -165,7 +166,7 @@ pub fn execute_action_template(
     script_content: String,
     app_path: String,
     app_name: String,
-) -> Result<ActionResult, String> {
+) -> Result<ActionResult, AppError> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -185,7 +186,7 @@ pub fn execute_action_template(
             .env("APP_NAME", &app_name)
             .creation_flags(CREATE_NO_WINDOW)
             .output()
-            .map_err(|e| format!("执行失败: {}", e))?;
+            .map_err(|e| AppError::internal(format!("执行失败: {}", e)))?;
 
         Ok(ActionResult {
             success: output.status.success(),
@@ -198,6 +199,6 @@ pub fn execute_action_template(
 
     #[cfg(not(target_os = "windows"))]
     {
-        Err("动作模板执行仅支持 Windows".to_string())
+        Err(AppError::platform_unsupported("动作模板执行"))
     }
 }
@@ -0,0 +1,60 @@
+use crate::commands::config::update_config;
+use crate::error::AppError;
+use crate::models::{App, AppState};
+use crate::utils::tags::{self, TagCount};
+use tauri::{AppHandle, State};
+
+/// 给程序添加标签，返回添加后的全部标签
+#[tauri::command]
+pub fn add_app_tags(
+    app_id: String,
+    tags: Vec<String>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, AppError> {
+    update_config(&state, &app_handle, |config| {
+        let app = config
+            .apps
+            .get_mut(&app_id)
+            .ok_or_else(|| AppError::not_found(format!("程序 {}", app_id)))?;
+        tags::add_tags(app, &tags)?;
+        Ok(app.tags.clone())
+    })
+}
+
+/// 移除程序的标签，返回移除后剩下的标签
+#[tauri::command]
+pub fn remove_app_tags(
+    app_id: String,
+    tags: Vec<String>,
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, AppError> {
+    update_config(&state, &app_handle, |config| {
+        let app = config
+            .apps
+            .get_mut(&app_id)
+            .ok_or_else(|| AppError::not_found(format!("程序 {}", app_id)))?;
+        tags::remove_tags(app, &tags);
+        Ok(app.tags.clone())
+    })
+}
+
+#[tauri::command]
+pub fn list_tags(state: State<AppState>) -> Vec<TagCount> {
+    let config = state.config.lock().unwrap();
+    tags::tag_counts(&config)
+}
+
+/// 按标签表达式查询程序，例如 `work AND (dev OR design) NOT old`
+#[tauri::command]
+pub fn query_apps_by_tags(
+    expression: String,
+    state: State<AppState>,
+) -> Result<Vec<App>, AppError> {
+    let config = state.config.lock().unwrap();
+    Ok(tags::query_apps(&config, &expression)?
+        .into_iter()
+        .cloned()
+        .collect())
+}
@@ -0,0 +1,312 @@
+use crate::storage::error::{not_found_message, StorageError};
+use crate::storage::json_store::DataEnvelope;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+
+/// 所有命令返回给前端的错误。
+///
+/// 序列化为 `{ code, message, details }`：`code` 是稳定的错误代码，前端据此判断和本地化；
+/// `message` 是中文说明，前端没有对应文案时直接显示；`details` 是各错误代码的结构化字段。
+#[derive(Debug)]
+pub enum AppError {
+    NotFound {
+        resource: String,
+    },
+    /// 文件或数据包内容无法解析、校验失败
+    Corrupt {
+        reason: String,
+    },
+    SchemaTooNew {
+        name: String,
+        found: u32,
+        supported: u32,
+    },
+    /// 写入时数据已被其他窗口改写，`current` 为当前保存的数据
+    Conflict {
+        name: String,
+        current: Option<Box<DataEnvelope<Value>>>,
+    },
+    PermissionDenied {
+        reason: String,
+    },
+    PlatformUnsupported {
+        feature: String,
+    },
+    ShortcutInvalid {
+        label: String,
+        shortcut: String,
+        reason: String,
+    },
+    InvalidInput {
+        reason: String,
+    },
+    /// 写入的数据不符合该数据文件的结构，`field` 为出问题的字段路径
+    InvalidData {
+        name: String,
+        field: String,
+        reason: String,
+    },
+    PassphraseRequired,
+    WrongPassphrase,
+    Io {
+        reason: String,
+    },
+    Database {
+        reason: String,
+    },
+    Migration {
+        reason: String,
+    },
+    /// 外部程序、系统接口或窗口操作失败等其他错误
+    Internal {
+        reason: String,
+    },
+}
+
+impl AppError {
+    pub fn not_found(resource: impl Into<String>) -> Self {
+        AppError::NotFound {
+            resource: resource.into(),
+        }
+    }
+
+    pub fn corrupt(reason: impl Into<String>) -> Self {
+        AppError::Corrupt {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn platform_unsupported(feature: impl Into<String>) -> Self {
+        AppError::PlatformUnsupported {
+            feature: feature.into(),
+        }
+    }
+
+    pub fn invalid_input(reason: impl Into<String>) -> Self {
+        AppError::InvalidInput {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn internal(reason: impl Into<String>) -> Self {
+        AppError::Internal {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "NotFound",
+            AppError::Corrupt { .. } => "Corrupt",
+            AppError::SchemaTooNew { .. } => "SchemaTooNew",
+            AppError::Conflict { .. } => "Conflict",
+            AppError::PermissionDenied { .. } => "PermissionDenied",
+            AppError::PlatformUnsupported { .. } => "PlatformUnsupported",
+            AppError::ShortcutInvalid { .. } => "ShortcutInvalid",
+            AppError::InvalidInput { .. } => "InvalidInput",
+            AppError::InvalidData { .. } => "InvalidData",
+            AppError::PassphraseRequired => "PassphraseRequired",
+            AppError::WrongPassphrase => "WrongPassphrase",
+            AppError::Io { .. } => "Io",
+            AppError::Database { .. } => "Database",
+            AppError::Migration { .. } => "Migration",
+            AppError::Internal { .. } => "Internal",
+        }
+    }
+
+    fn details(&self) -> Value {
+        match self {
+            AppError::NotFound { resource } => json!({ "resource": resource }),
+            AppError::SchemaTooNew {
+                name,
+                found,
+                supported,
+            } => json!({ "name": name, "found": found, "supported": supported }),
+            AppError::Conflict { name, current } => json!({ "name": name, "current": current }),
+            AppError::PlatformUnsupported { feature } => json!({ "feature": feature }),
+            AppError::ShortcutInvalid {
+                label,
+                shortcut,
+                reason,
+            } => json!({ "label": label, "shortcut": shortcut, "reason": reason }),
+            AppError::InvalidData {
+                name,
+                field,
+                reason,
+            } => json!({ "name": name, "field": field, "reason": reason }),
+            AppError::Corrupt { reason }
+            | AppError::PermissionDenied { reason }
+            | AppError::InvalidInput { reason }
+            | AppError::Io { reason }
+            | AppError::Database { reason }
+            | AppError::Migration { reason }
+            | AppError::Internal { reason } => json!({ "reason": reason }),
+            AppError::PassphraseRequired | AppError::WrongPassphrase => json!({}),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound { resource } => f.write_str(&not_found_message(resource)),
+            AppError::Corrupt { reason } => write!(f, "数据无效: {}", reason),
+            AppError::SchemaTooNew {
+                name,
+                found,
+                supported,
+            } => write!(
+                f,
+                "{} 的数据结构版本为 {}，当前程序最高支持 {}，请升级程序后再打开",
+                name, found, supported
+            ),
+            AppError::Conflict { name, .. } => {
+                write!(f, "{} 已被其他窗口修改，请合并后重试", name)
+            }
+            AppError::PermissionDenied { reason } => write!(f, "没有权限: {}", reason),
+            AppError::PlatformUnsupported { feature } => write!(f, "当前平台不支持{}", feature),
+            AppError::ShortcutInvalid {
+                label,
+                shortcut,
+                reason,
+            } => write!(f, "{} 快捷键无法使用（{}）: {}", label, shortcut, reason),
+            AppError::InvalidInput { reason } => write!(f, "{}", reason),
+            AppError::InvalidData {
+                name,
+                field,
+                reason,
+            } => write!(f, "{} 的字段 {} 无效: {}", name, field, reason),
+            AppError::PassphraseRequired => write!(f, "数据包已加密，请输入密码"),
+            AppError::WrongPassphrase => write!(f, "数据包密码错误"),
+            AppError::Io { reason } => write!(f, "文件读写失败: {}", reason),
+            AppError::Database { reason } => write!(f, "数据库读写失败: {}", reason),
+            AppError::Migration { reason } => write!(f, "数据结构升级失败: {}", reason),
+            AppError::Internal { reason } => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<StorageError> for AppError {
+    fn from(error: StorageError) -> Self {
+        match error {
+            StorageError::Io(error) => error.into(),
+            StorageError::Json(error) => AppError::corrupt(error.to_string()),
+            StorageError::NotFound(resource) => AppError::NotFound { resource },
+            StorageError::SchemaTooNew {
+                name,
+                found,
+                supported,
+            } => AppError::SchemaTooNew {
+                name,
+                found,
+                supported,
+            },
+            StorageError::Migration(reason) => AppError::Migration { reason },
+            StorageError::Package(reason) => AppError::Corrupt { reason },
+            StorageError::WrongPassphrase => AppError::WrongPassphrase,
+            StorageError::Database(reason) => AppError::Database { reason },
+            StorageError::InvalidData {
+                name,
+                field,
+                reason,
+            } => AppError::InvalidData {
+                name,
+                field,
+                reason,
+            },
+            StorageError::Conflict { name, current } => AppError::Conflict { name, current },
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        let reason = error.to_string();
+        match error.kind() {
+            // 标准库的错误说明不带路径，只能笼统地说明资源，并附上系统给出的原因
+            std::io::ErrorKind::NotFound => {
+                AppError::not_found(format!("文件或目录（{}）", reason))
+            }
+            std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied { reason },
+            std::io::ErrorKind::InvalidInput => AppError::InvalidInput { reason },
+            _ => AppError::Io { reason },
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::corrupt(error.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(error: tauri::Error) -> Self {
+        AppError::internal(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AppError;
+    use crate::storage::error::StorageError;
+    use serde_json::json;
+
+    #[test]
+    fn 序列化为错误代码说明和结构化字段() {
+        let error = AppError::from(StorageError::SchemaTooNew {
+            name: "notes".to_string(),
+            found: 3,
+            supported: 2,
+        });
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "SchemaTooNew",
+                "message": "notes 的数据结构版本为 3，当前程序最高支持 2，请升级程序后再打开",
+                "details": { "name": "notes", "found": 3, "supported": 2 }
+            })
+        );
+
+        let missing = AppError::from(StorageError::NotFound("备份 manual-1".to_string()));
+        assert_eq!(missing.code(), "NotFound");
+        assert_eq!(missing.to_string(), "备份 manual-1 不存在");
+        assert_eq!(AppError::not_found("主窗口").to_string(), "主窗口不存在");
+        let denied = AppError::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert_eq!(denied.code(), "PermissionDenied");
+        let missing_file = AppError::from(StorageError::Io(std::io::Error::from(
+            std::io::ErrorKind::NotFound,
+        )));
+        assert_eq!(missing_file.code(), "NotFound");
+        let missing_file = AppError::from(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "scenes.json 已被移走",
+        ));
+        assert_eq!(
+            missing_file.to_string(),
+            "文件或目录（scenes.json 已被移走）不存在"
+        );
+        assert_eq!(
+            AppError::platform_unsupported("发送按键").to_string(),
+            "当前平台不支持发送按键"
+        );
+        assert_eq!(
+            serde_json::to_value(AppError::PassphraseRequired).unwrap()["details"],
+            json!({})
+        );
+    }
+}
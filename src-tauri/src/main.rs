@@ -2,6 +2,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
+mod error;
 mod models;
 mod storage;
 mod utils;
@@ -15,6 +16,14 @@ use tauri::{
 };
 
 fn main() {
+    // 处理上次写入中断时残留的临时文件
+    if let Err(error) = storage::atomic_write::reconcile_temp_files(&storage::paths::data_dir()) {
+        eprintln!("清理残留临时文件失败: {}", error);
+    }
+
+    // 检查数据文件，损坏的文件会被隔离并尝试从备份恢复
+    storage::recovery::check_envelope_files();
+
     // 加载或创建配置
     let config = utils::config::load_or_create_config();
 
@@ -49,17 +58,33 @@ fn main() {
         }))
         .manage(AppState {
             config: Mutex::new(config),
+            launcher: utils::launcher::system(),
         })
         .invoke_handler(tauri::generate_handler![
             load_config,
             save_config,
             add_category,
+            rename_category,
+            delete_category,
+            reorder_categories,
+            move_category,
+            move_app_to_category,
+            link_app_to_category,
+            unlink_app_from_category,
+            add_app_tags,
+            remove_app_tags,
+            list_tags,
+            query_apps_by_tags,
+            save_category_rules,
+            preview_category_rules,
+            apply_category_rules,
             add_app,
             delete_app,
             launch_app,
             extract_icon,
             get_icons_dir,
             open_file_location,
+            open_path,
             register_context_menu,
             unregister_context_menu,
             is_context_menu_registered,
@@ -68,6 +93,8 @@ fn main() {
             execute_action_template,
             // 应用操作命令
             launch_app_as_admin,
+            update_app_launch_options,
+            duplicate_app,
             // 进程检测命令
             is_process_running,
             // 场景动作命令
@@ -80,9 +107,13 @@ fn main() {
             check_app_update,
             check_all_updates,
             batch_delete_apps,
+            get_storage_usage,
+            collect_icon_garbage,
+            check_data_integrity,
             // 图床图片获取命令
             fetch_image_as_base64,
             hide_main_window,
+            restart_app,
             hide_todo_window,
             quit_app,
             show_notes_window,
@@ -93,9 +124,20 @@ fn main() {
             get_migration_status,
             read_persisted_data,
             write_persisted_data,
+            get_storage_backend,
+            migrate_storage_backend,
+            get_data_dir_info,
+            relocate_data_dir,
             export_local_data,
             preview_local_data_import,
             import_local_data,
+            // 备份管理命令
+            list_backups,
+            restore_backup,
+            list_file_versions,
+            restore_file_version,
+            delete_backup,
+            take_data_recovery_reports,
         ])
         .setup(|app| {
             // 处理启动时的命令行参数
@@ -181,6 +223,26 @@ fn main() {
             utils::shortcuts::register_configured_shortcuts(app.handle(), &shortcut_config)
                 .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
 
+            // 启动定时备份与备份清理
+            utils::backup_scheduler::start_backup_scheduler(app.handle().clone());
+
+            // 数据写入或被外部修改时通知所有窗口
+            let change_handle = app.handle().clone();
+            storage::events::set_listener(move |change| {
+                let _ = change_handle.emit("persisted-data-changed", change);
+            });
+            let config_handle = app.handle().clone();
+            storage::events::set_config_listener(move || {
+                commands::config::reload_external_config(&config_handle);
+            });
+            utils::data_watcher::start_data_watcher();
+
+            // 运行中读取数据时发现并处理了损坏文件，提醒前端取走处理记录；启动时的记录由前端加载完成后主动取走
+            let recovery_handle = app.handle().clone();
+            storage::recovery::set_listener(move || {
+                let _ = recovery_handle.emit("data-recovered", ());
+            });
+
             Ok(())
         })
         .run(tauri::generate_context!())
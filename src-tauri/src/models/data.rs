@@ -0,0 +1,288 @@
+use crate::storage::error::StorageError;
+use crate::storage::persisted::PersistedDataType;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// 场景、便签、待办、剪贴板和动作数据文件的结构，与 `src/types` 中的前端类型对应。
+// 每个结构都用 `extra` 收集未识别的字段，读出再写回时原样保留，新版本增加的字段不会丢失。
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SceneActionType {
+    Launch,
+    LaunchAdmin,
+    OpenUrl,
+    OpenFolder,
+    OpenFile,
+    CloseApp,
+    Delay,
+    Notify,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SceneFailureStrategy {
+    Continue,
+    Stop,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneActionParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_window: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_timeout: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_keys: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_after: Option<f64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SceneAction {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub action_type: SceneActionType,
+    #[serde(default)]
+    pub params: SceneActionParams,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Scene {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub icon: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcut: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_strategy: Option<SceneFailureStrategy>,
+    pub actions: Vec<SceneAction>,
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub updated_at: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScenesData {
+    #[serde(default)]
+    pub scenes: Vec<Scene>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickNote {
+    pub id: String,
+    pub content: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    #[serde(default)]
+    pub color: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NotesData {
+    #[serde(default)]
+    pub notes: Vec<QuickNote>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoItem {
+    pub id: String,
+    pub title: String,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    /// `HH:MM`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub completed: bool,
+    pub created_at: u64,
+    pub updated_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TodosData {
+    #[serde(default)]
+    pub items: Vec<TodoItem>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardContentType {
+    Text,
+    Image,
+    Html,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardItem {
+    pub id: String,
+    pub content: String,
+    pub content_type: ClipboardContentType,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardData {
+    #[serde(default)]
+    pub items: Vec<ClipboardItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ActionsData {
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 按对应结构解析数据，失败时错误中带有出问题的字段路径，例如 `scenes[0].actions[1].type`
+pub fn parse<T: DeserializeOwned>(
+    data_type: PersistedDataType,
+    data: &Value,
+) -> Result<T, StorageError> {
+    serde_path_to_error::deserialize(data).map_err(|error| StorageError::InvalidData {
+        name: data_type.file_name().to_string(),
+        field: error.path().to_string(),
+        reason: error.inner().to_string(),
+    })
+}
+
+/// 写入前校验数据是否符合该类数据的结构
+pub fn validate(data_type: PersistedDataType, data: &Value) -> Result<(), StorageError> {
+    match data_type {
+        PersistedDataType::Scenes => parse::<ScenesData>(data_type, data).map(drop),
+        PersistedDataType::Notes => parse::<NotesData>(data_type, data).map(drop),
+        PersistedDataType::Todos => parse::<TodosData>(data_type, data).map(drop),
+        PersistedDataType::Clipboard => parse::<ClipboardData>(data_type, data).map(drop),
+        PersistedDataType::Actions => parse::<ActionsData>(data_type, data).map(drop),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, validate, ScenesData};
+    use crate::storage::error::StorageError;
+    use crate::storage::persisted::PersistedDataType;
+    use serde_json::json;
+
+    #[test]
+    fn 未识别的字段在读出写回后保留() {
+        let stored = json!({
+            "scenes": [{
+                "id": "morning",
+                "name": "上班",
+                "icon": "sun",
+                "actions": [{
+                    "id": "1",
+                    "type": "launch",
+                    "params": { "appId": "a", "monitor": 2 },
+                    "note": "新版本字段"
+                }],
+                "createdAt": 1,
+                "updatedAt": 2,
+                "color": "#fff"
+            }],
+            "layout": "grid"
+        });
+
+        let scenes: ScenesData = parse(PersistedDataType::Scenes, &stored).expect("应能解析");
+        assert_eq!(
+            scenes.scenes[0].actions[0].params.app_id.as_deref(),
+            Some("a")
+        );
+        assert_eq!(serde_json::to_value(&scenes).unwrap(), stored);
+    }
+
+    #[test]
+    fn 校验失败时指出出问题的字段() {
+        let scenes = json!({
+            "scenes": [{
+                "id": "s",
+                "name": "场景",
+                "actions": [
+                    { "id": "1", "type": "delay", "params": { "seconds": 2 } },
+                    { "id": "2", "type": "lunch", "params": {} }
+                ]
+            }]
+        });
+        let Err(StorageError::InvalidData {
+            name,
+            field,
+            reason,
+        }) = validate(PersistedDataType::Scenes, &scenes)
+        else {
+            panic!("未知的动作类型应被拒绝");
+        };
+        assert_eq!(name, "scenes.json");
+        assert_eq!(field, "scenes[0].actions[1].type");
+        assert!(reason.contains("lunch"));
+
+        let todos = json!({ "items": [{ "id": "t", "title": "待办", "date": 20240101, "createdAt": 1, "updatedAt": 1 }] });
+        let Err(StorageError::InvalidData { field, .. }) =
+            validate(PersistedDataType::Todos, &todos)
+        else {
+            panic!("日期类型错误应被拒绝");
+        };
+        assert_eq!(field, "items[0].date");
+
+        assert!(validate(
+            PersistedDataType::Clipboard,
+            &json!({ "items": [], "maxItems": 100 })
+        )
+        .is_ok());
+        assert!(validate(PersistedDataType::Notes, &json!({ "notes": {} })).is_err());
+    }
+}
@@ -1,9 +1,131 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Mutex;
+pub mod data;
 
-fn default_item_type() -> String {
-    "app".to_string()
+use crate::utils::launcher::Launcher;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// 定义与前端字符串字面量一一对应的枚举。
+///
+/// 未识别的值读取为 `Unknown` 并原样写回，更新版本写入的新取值不会让整个配置读取失败；
+/// 旧配置中大小写或分隔符不一致的取值由配置结构升级统一改正。
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+            /// 未识别的取值
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)+
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => $name::$variant,)+
+                    _ => $name::Unknown(value.to_string()),
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok(String::deserialize(deserializer)?.as_str().into())
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// 条目类型，只有程序会提取图标和检测更新
+    ItemType {
+        App => "app",
+        Folder => "folder",
+        File => "file",
+    }
+}
+
+string_enum! {
+    /// 程序启动时的窗口状态，只在 Windows 上生效
+    WindowState {
+        Normal => "normal",
+        Minimized => "minimized",
+        Maximized => "maximized",
+    }
+}
+
+string_enum! {
+    ValidationStatus {
+        Valid => "valid",
+        Invalid => "invalid",
+        /// 网络路径等暂时无法访问
+        Unreachable => "unreachable",
+    }
+}
+
+string_enum! {
+    UpdateStatus {
+        /// 未发现更新，前端取值为 `none`
+        UpToDate => "none",
+        Suspected => "suspected",
+        Confirmed => "confirmed",
+    }
+}
+
+string_enum! {
+    UpdateConfidence {
+        High => "high",
+        Medium => "medium",
+        Low => "low",
+    }
+}
+
+string_enum! {
+    SortBy {
+        Name => "name",
+        LastLaunched => "lastLaunched",
+        Custom => "custom",
+    }
+}
+
+string_enum! {
+    Theme {
+        Light => "light",
+        Dark => "dark",
+        Auto => "auto",
+    }
+}
+
+string_enum! {
+    CardSize {
+        Small => "small",
+        Medium => "medium",
+        Large => "large",
+    }
+}
+
+fn default_item_type() -> ItemType {
+    ItemType::App
 }
 
 fn default_theme_preset() -> Option<String> {
@@ -26,10 +148,10 @@ pub struct UpdateMetadata {
     pub last_checked_at: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "updateStatus")]
-    pub update_status: Option<String>,
+    pub update_status: Option<UpdateStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "updateConfidence")]
-    pub update_confidence: Option<String>,
+    pub update_confidence: Option<UpdateConfidence>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,10 +159,30 @@ pub struct App {
     pub id: String,
     pub name: String,
     pub path: String,
+    /// 主分类
     pub category: String,
+    /// 除主分类外同时出现的分类
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(rename = "extraCategories")]
+    pub extra_categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     #[serde(default = "default_item_type")]
     #[serde(rename = "itemType")]
-    pub item_type: String,
+    pub item_type: ItemType,
+    /// 启动参数，逐个传给程序，不经过 shell 拆分
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// 启动时的工作目录，为空时沿用本程序的工作目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "workingDir")]
+    pub working_dir: Option<String>,
+    /// 在继承的环境变量之上追加或覆盖的变量
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "windowState")]
+    pub window_state: Option<WindowState>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -55,7 +197,7 @@ pub struct App {
     // 有效性状态（新增）
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "validationStatus")]
-    pub validation_status: Option<String>,
+    pub validation_status: Option<ValidationStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "lastValidatedAt")]
     pub last_validated_at: Option<u64>,
@@ -68,24 +210,29 @@ pub struct Category {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<String>,
     pub apps: Vec<String>,
+    /// 同级分类之间的排列顺序
     pub order: usize,
+    /// 上级分类，为空时是顶级分类
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AppSettings {
     #[serde(rename = "cardSize")]
-    pub card_size: String,
+    pub card_size: CardSize,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "lastCategory")]
     pub last_category: Option<String>,
-    pub theme: String,
+    pub theme: Theme,
     #[serde(default = "default_theme_preset")]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "themePreset")]
     pub theme_preset: Option<String>,
     #[serde(rename = "sortBy")]
-    pub sort_by: String,
+    pub sort_by: SortBy,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "themeColor")]
     pub theme_color: Option<String>,
@@ -141,16 +288,32 @@ pub struct AppSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "backgroundApiUrl")]
     pub background_api_url: Option<String>,
+    // 备份保留策略与定时快照
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "autoBackupEnabled")]
+    pub auto_backup_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "autoBackupIntervalHours")]
+    pub auto_backup_interval_hours: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "backupKeepLast")]
+    pub backup_keep_last: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "backupKeepDailyDays")]
+    pub backup_keep_daily_days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "backupMaxTotalMb")]
+    pub backup_max_total_mb: Option<u64>,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
-            card_size: "medium".to_string(),
+            card_size: CardSize::Medium,
             last_category: None,
-            theme: "auto".to_string(),
+            theme: Theme::Auto,
             theme_preset: Some("fresh-dawn".to_string()),
-            sort_by: "lastLaunched".to_string(),
+            sort_by: SortBy::LastLaunched,
             theme_color: Some("#007AFF".to_string()),
             background_image: None,
             background_opacity: Some(0.3),
@@ -170,10 +333,46 @@ impl Default for AppSettings {
             todo_shortcut: Some("Alt+T".to_string()),
             background_source: Some("local".to_string()),
             background_api_url: None,
+            // 备份默认值
+            auto_backup_enabled: Some(true),
+            auto_backup_interval_hours: Some(24),
+            backup_keep_last: Some(10),
+            backup_keep_daily_days: Some(7),
+            backup_max_total_mb: None,
         }
     }
 }
 
+/// 自动分类规则：条件全部满足时把程序归入 `category_id` 并添加 `tags`，未设置的条件不参与判断
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryRule {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+    /// 路径通配符，`*` 不跨越目录、`**` 可以跨越目录，不区分大小写，例如 `C:/Program Files/JetBrains/**`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_glob: Option<String>,
+    /// 匹配文件名（含扩展名）的正则表达式，不区分大小写
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name_pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<ItemType>,
+    /// 发布者名称中包含的文字，不区分大小写；只在 Windows 上能读取到发布者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default = "default_config_version")]
@@ -184,30 +383,38 @@ pub struct Config {
     pub apps: HashMap<String, App>,
     #[serde(default)]
     pub settings: AppSettings,
+    /// 按顺序匹配的自动分类规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(rename = "categoryRules")]
+    pub category_rules: Vec<CategoryRule>,
 }
 
 fn default_config_version() -> String {
-    "1.0".to_string()
+    crate::storage::schema::config_version_string(
+        crate::storage::schema::CONFIG_SCHEMA.current_version(),
+    )
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            version: "1.0".to_string(),
+            version: default_config_version(),
             categories: HashMap::new(),
             apps: HashMap::new(),
             settings: AppSettings::default(),
+            category_rules: Vec::new(),
         }
     }
 }
 
 pub struct AppState {
     pub config: Mutex<Config>,
+    pub launcher: Arc<dyn Launcher>,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{AppSettings, Config};
+    use super::{App, AppSettings, Config, ItemType, SortBy};
 
     #[test]
     fn 旧设置缺少主题预设时会回退到默认值() {
@@ -224,4 +431,23 @@ mod tests {
 
         assert_eq!(json["settings"]["themePreset"].as_str(), Some("fresh-dawn"));
     }
+
+    #[test]
+    fn 未识别的取值读取为未知并原样写回() {
+        let app: App = serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "name": "快捷方式",
+            "path": "C:/a.lnk",
+            "category": "c",
+            "itemType": "shortcut",
+            "createdAt": 1
+        }))
+        .expect("未知的条目类型不应导致读取失败");
+
+        assert_eq!(app.item_type, ItemType::Unknown("shortcut".to_string()));
+        let json = serde_json::to_value(&app).expect("应能序列化");
+        assert_eq!(json["itemType"].as_str(), Some("shortcut"));
+        assert_eq!(SortBy::from("lastLaunched"), SortBy::LastLaunched);
+        assert_eq!(SortBy::LastLaunched.as_str(), "lastLaunched");
+    }
 }
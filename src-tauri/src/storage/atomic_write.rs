@@ -1,31 +1,140 @@
 use crate::storage::error::StorageError;
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+const TMP_EXTENSION: &str = "tmp";
+
+/// 先写入同目录下的临时文件并落盘，再用一次重命名替换目标文件。
+///
+/// 重命名会直接覆盖已有文件，任何时刻磁盘上都至少保留一份完整的目标文件；
+/// 替换后同步父目录，确保重命名本身也已写入磁盘。
 pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), StorageError> {
-    if let Some(parent) = path.parent() {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty());
+    if let Some(parent) = parent {
         fs::create_dir_all(parent)?;
     }
 
-    let tmp_path = path.with_extension("tmp");
+    let tmp_path = temp_path_for(path);
     {
         let mut file = File::create(&tmp_path)?;
         file.write_all(content)?;
         file.sync_all()?;
     }
 
-    if path.exists() {
-        fs::remove_file(path)?;
+    if let Err(error) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(error.into());
+    }
+    if let Some(parent) = parent {
+        sync_dir(parent)?;
     }
-    fs::rename(tmp_path, path)?;
     Ok(())
 }
 
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(TMP_EXTENSION);
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<(), StorageError> {
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+// Windows 上无法直接打开目录句柄同步，NTFS 的元数据日志会保证重命名落盘
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<(), StorageError> {
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TempFileAction {
+    /// 临时文件比目标文件新且有效，已替换目标文件
+    Promoted { temp: PathBuf, target: PathBuf },
+    /// 临时文件无效或比目标文件旧，已删除
+    Discarded { temp: PathBuf },
+}
+
+/// 启动时清理目录中残留的 `*.tmp` 文件。
+///
+/// 写入中断时临时文件可能是完整的新数据，也可能只写了一半：
+/// 临时文件能解析且目标缺失、损坏或更旧时提升为目标文件，否则直接删除。
+/// 旧版本使用 `config.tmp` 这样替换扩展名的临时文件名，同样按 `.json` 目标处理。
+pub fn reconcile_temp_files(dir: &Path) -> Result<Vec<TempFileAction>, StorageError> {
+    let mut actions = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let temp = entry?.path();
+        if !temp.is_file() || temp.extension().and_then(|ext| ext.to_str()) != Some(TMP_EXTENSION) {
+            continue;
+        }
+        let Some(target) = target_path_for(&temp) else {
+            continue;
+        };
+
+        let temp_valid = is_valid_json(&temp);
+        let target_valid = is_valid_json(&target);
+        let temp_newer = match (modified_at(&temp), modified_at(&target)) {
+            (Some(temp_time), Some(target_time)) => temp_time >= target_time,
+            _ => true,
+        };
+
+        if temp_valid && (!target_valid || temp_newer) {
+            fs::rename(&temp, &target)?;
+            actions.push(TempFileAction::Promoted { temp, target });
+        } else {
+            fs::remove_file(&temp)?;
+            actions.push(TempFileAction::Discarded { temp });
+        }
+    }
+    if actions
+        .iter()
+        .any(|action| matches!(action, TempFileAction::Promoted { .. }))
+    {
+        sync_dir(dir)?;
+    }
+    Ok(actions)
+}
+
+fn target_path_for(temp: &Path) -> Option<PathBuf> {
+    let stem = temp.file_stem()?;
+    if Path::new(stem).extension().is_some() {
+        Some(temp.with_file_name(stem))
+    } else {
+        Some(temp.with_extension("json"))
+    }
+}
+
+fn is_valid_json(path: &Path) -> bool {
+    fs::read(path)
+        .ok()
+        .map(|content| serde_json::from_slice::<serde_json::Value>(&content).is_ok())
+        .unwrap_or(false)
+}
+
+fn modified_at(path: &Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::write_atomic;
+    use super::{reconcile_temp_files, temp_path_for, write_atomic, TempFileAction};
     use std::fs;
+    use std::path::PathBuf;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            crate::storage::json_store::now_millis()
+        ))
+    }
 
     #[test]
     fn 原子写入会创建目标文件并保留内容() {
@@ -42,4 +151,63 @@ mod tests {
         assert_eq!(content, r#"{"ok":true}"#);
         let _ = fs::remove_dir_all(dir);
     }
+
+    #[test]
+    fn 覆盖已有文件时不会残留临时文件() {
+        let dir = unique_temp_dir("atomic-replace");
+        let _ = fs::create_dir_all(&dir);
+        let file = dir.join("config.json");
+        fs::write(&file, r#"{"old":true}"#).expect("应能写入旧文件");
+
+        write_atomic(&file, br#"{"new":true}"#).expect("写入应成功");
+
+        assert_eq!(
+            fs::read_to_string(&file).expect("应能读取文件"),
+            r#"{"new":true}"#
+        );
+        assert!(!temp_path_for(&file).exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn 中断写入留下的完整临时文件会替换缺失的目标文件() {
+        let dir = unique_temp_dir("atomic-interrupted-complete");
+        let _ = fs::create_dir_all(&dir);
+        let file = dir.join("config.json");
+        // 模拟旧实现在删除目标文件之后、重命名之前崩溃
+        fs::write(temp_path_for(&file), r#"{"new":true}"#).expect("应能写入临时文件");
+
+        let actions = reconcile_temp_files(&dir).expect("清理应成功");
+
+        assert!(matches!(actions[0], TempFileAction::Promoted { .. }));
+        assert_eq!(
+            fs::read_to_string(&file).expect("应能读取恢复文件"),
+            r#"{"new":true}"#
+        );
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn 写了一半的临时文件会被丢弃并保留原文件() {
+        let dir = unique_temp_dir("atomic-interrupted-partial");
+        let _ = fs::create_dir_all(&dir);
+        let file = dir.join("notes.json");
+        fs::write(&file, r#"{"old":true}"#).expect("应能写入旧文件");
+        fs::write(temp_path_for(&file), r#"{"new":tr"#).expect("应能写入临时文件");
+        // 旧版本的临时文件命名
+        fs::write(dir.join("todos.tmp"), r#"{"items":[]}"#).expect("应能写入旧临时文件");
+
+        let mut actions = reconcile_temp_files(&dir).expect("清理应成功");
+        actions.sort_by_key(|action| matches!(action, TempFileAction::Promoted { .. }));
+
+        assert!(matches!(actions[0], TempFileAction::Discarded { .. }));
+        assert!(matches!(actions[1], TempFileAction::Promoted { .. }));
+        assert_eq!(
+            fs::read_to_string(&file).expect("应能读取原文件"),
+            r#"{"old":true}"#
+        );
+        assert!(dir.join("todos.json").is_file());
+        assert!(!temp_path_for(&file).exists());
+        let _ = fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,47 @@
+use super::{StorageBackend, StorageBackendKind};
+use crate::storage::error::StorageError;
+use crate::storage::json_store;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// 每份文档保存为数据目录中的一个 JSON 文件，写入经由原子替换完成
+pub struct JsonFileBackend {
+    dir: PathBuf,
+}
+
+impl JsonFileBackend {
+    pub fn new(dir: PathBuf) -> Self {
+        JsonFileBackend { dir }
+    }
+}
+
+impl StorageBackend for JsonFileBackend {
+    fn kind(&self) -> StorageBackendKind {
+        StorageBackendKind::Json
+    }
+
+    fn read(&self, name: &str) -> Result<Option<Value>, StorageError> {
+        let path = self.dir.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        json_store::read_json(&path).map(Some)
+    }
+
+    fn write(&self, name: &str, value: &Value) -> Result<(), StorageError> {
+        json_store::write_json(&self.dir.join(name), value)
+    }
+
+    fn remove(&self, name: &str) -> Result<(), StorageError> {
+        let path = self.dir.join(name);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn file_path(&self, name: &str) -> Option<PathBuf> {
+        Some(self.dir.join(name))
+    }
+}
@@ -0,0 +1,34 @@
+use super::{StorageBackend, StorageBackendKind};
+use crate::storage::error::StorageError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 只保存在内存中的后端，供测试使用；报告为 JSON 后端，但没有对应的磁盘文件
+#[derive(Default)]
+pub struct MemoryBackend {
+    documents: Mutex<HashMap<String, Value>>,
+}
+
+impl StorageBackend for MemoryBackend {
+    fn kind(&self) -> StorageBackendKind {
+        StorageBackendKind::Json
+    }
+
+    fn read(&self, name: &str) -> Result<Option<Value>, StorageError> {
+        Ok(self.documents.lock().unwrap().get(name).cloned())
+    }
+
+    fn write(&self, name: &str, value: &Value) -> Result<(), StorageError> {
+        self.documents
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), value.clone());
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) -> Result<(), StorageError> {
+        self.documents.lock().unwrap().remove(name);
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+mod json;
+#[cfg(test)]
+mod memory;
+mod sqlite;
+
+pub use json::JsonFileBackend;
+#[cfg(test)]
+pub use memory::MemoryBackend;
+pub use sqlite::SqliteBackend;
+
+use crate::storage::error::StorageError;
+use crate::storage::{backup, json_store, paths, persisted};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::{Arc, PoisonError, RwLock};
+
+#[cfg(test)]
+use std::cell::RefCell;
+
+/// 由存储后端保存的数据文档，名称沿用 JSON 文件名
+pub const DOCUMENTS: &[&str] = &[
+    paths::CONFIG_FILE,
+    paths::SCENES_FILE,
+    paths::NOTES_FILE,
+    paths::TODOS_FILE,
+    paths::CLIPBOARD_FILE,
+    paths::ACTIONS_FILE,
+];
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackendKind {
+    /// 每类数据一个 JSON 文件
+    #[default]
+    Json,
+    /// 数据目录中的 `data.db`，条目列表按行保存，只改写变化的条目
+    Sqlite,
+}
+
+/// 数据文档的读写接口。
+///
+/// 文档以 JSON 值整体读写，结构版本检查和升级由调用方负责。
+pub trait StorageBackend: Send + Sync {
+    fn kind(&self) -> StorageBackendKind;
+
+    /// 文档不存在时返回 `None`，内容无法解析时返回 `StorageError::Json`
+    fn read(&self, name: &str) -> Result<Option<Value>, StorageError>;
+
+    fn write(&self, name: &str, value: &Value) -> Result<(), StorageError>;
+
+    fn remove(&self, name: &str) -> Result<(), StorageError>;
+
+    /// 文档对应的磁盘文件，文件损坏时据此隔离并从备份恢复；不以单独文件保存的后端返回 `None`
+    fn file_path(&self, _name: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_BACKEND: RefCell<Option<Arc<dyn StorageBackend>>> = RefCell::new(None);
+}
+
+#[cfg(test)]
+pub struct TestBackendGuard {
+    previous: Option<Arc<dyn StorageBackend>>,
+}
+
+#[cfg(test)]
+impl Drop for TestBackendGuard {
+    fn drop(&mut self) {
+        TEST_BACKEND.with(|cell| {
+            cell.replace(self.previous.take());
+        });
+    }
+}
+
+/// 测试期间替换当前线程使用的存储后端
+#[cfg(test)]
+pub fn set_test_backend(backend: Arc<dyn StorageBackend>) -> TestBackendGuard {
+    let previous = TEST_BACKEND.with(|cell| cell.replace(Some(backend)));
+    TestBackendGuard { previous }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageSettings {
+    backend: StorageBackendKind,
+}
+
+/// 当前使用的存储后端
+pub fn current_kind() -> StorageBackendKind {
+    current()
+        .map(|backend| backend.kind())
+        .unwrap_or_else(|_| recorded_kind())
+}
+
+/// 数据目录中记录的存储后端，没有记录时使用 JSON 文件
+fn recorded_kind() -> StorageBackendKind {
+    json_store::read_json::<StorageSettings>(&paths::data_file(paths::STORAGE_FILE))
+        .map(|settings| settings.backend)
+        .unwrap_or_default()
+}
+
+pub fn open(kind: StorageBackendKind) -> Result<Arc<dyn StorageBackend>, StorageError> {
+    match kind {
+        StorageBackendKind::Json => Ok(Arc::new(JsonFileBackend::new(paths::data_dir()))),
+        StorageBackendKind::Sqlite => Ok(Arc::new(SqliteBackend::open(&paths::data_file(
+            paths::DATABASE_FILE,
+        ))?)),
+    }
+}
+
+/// 已打开的存储后端及其所在的数据目录。
+///
+/// 第一次使用时按数据目录中的记录打开，之后一直复用；切换后端、恢复备份或移动数据目录时替换。
+static OPENED: RwLock<Option<(PathBuf, Arc<dyn StorageBackend>)>> = RwLock::new(None);
+
+pub fn current() -> Result<Arc<dyn StorageBackend>, StorageError> {
+    #[cfg(test)]
+    if let Some(backend) = TEST_BACKEND.with(|cell| cell.borrow().clone()) {
+        return Ok(backend);
+    }
+
+    let dir = paths::data_dir();
+    let opened_in = |opened: &Option<(PathBuf, Arc<dyn StorageBackend>)>| {
+        opened
+            .as_ref()
+            .filter(|(opened_dir, _)| *opened_dir == dir)
+            .map(|(_, backend)| backend.clone())
+    };
+    if let Some(backend) = opened_in(&OPENED.read().unwrap_or_else(PoisonError::into_inner)) {
+        return Ok(backend);
+    }
+
+    let mut opened = OPENED.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(backend) = opened_in(&opened) {
+        return Ok(backend);
+    }
+    let backend = open(recorded_kind())?;
+    *opened = Some((dir, backend.clone()));
+    Ok(backend)
+}
+
+/// 关闭已打开的存储后端，下次使用时按数据目录中的记录重新打开。
+///
+/// 数据目录中的文件被整体替换（恢复备份、移动数据目录）后调用，避免继续使用旧的数据库连接。
+pub fn close_current() {
+    *OPENED.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageMigrationReport {
+    pub from: StorageBackendKind,
+    pub to: StorageBackendKind,
+    pub documents: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_dir: Option<String>,
+}
+
+/// 把所有数据文档迁移到目标后端并切换。
+///
+/// 迁移前创建完整备份；每份文档写入后读回比对，全部成功才记录新的后端。
+/// 原后端中的数据保持不动，迁移失败时继续使用原后端。
+pub fn migrate(target: StorageBackendKind) -> Result<StorageMigrationReport, StorageError> {
+    let from = current_kind();
+    if from == target {
+        return Ok(StorageMigrationReport {
+            from,
+            to: target,
+            documents: Vec::new(),
+            backup_dir: None,
+        });
+    }
+
+    // 迁移期间的写入会落在原后端而丢失，整个迁移过程中暂停数据写入
+    let _writes = persisted::lock_writes();
+    let backup = backup::create_full_backup("切换存储后端前自动备份")?;
+    let source = current()?;
+    let opened = open(target)?;
+    let documents = migrate_documents(source.as_ref(), opened.as_ref())?;
+    json_store::write_json(
+        &paths::data_file(paths::STORAGE_FILE),
+        &StorageSettings { backend: target },
+    )?;
+    *OPENED.write().unwrap_or_else(PoisonError::into_inner) = Some((paths::data_dir(), opened));
+    Ok(StorageMigrationReport {
+        from,
+        to: target,
+        documents,
+        backup_dir: Some(backup.dir.to_string_lossy().to_string()),
+    })
+}
+
+/// 逐份复制文档，源后端没有的文档会从目标后端删除；返回复制的文档名
+pub fn migrate_documents(
+    source: &dyn StorageBackend,
+    target: &dyn StorageBackend,
+) -> Result<Vec<String>, StorageError> {
+    let mut migrated = Vec::new();
+    for name in DOCUMENTS {
+        let Some(value) = source.read(name)? else {
+            target.remove(name)?;
+            continue;
+        };
+        target.write(name, &value)?;
+        if target.read(name)?.as_ref() != Some(&value) {
+            return Err(StorageError::Migration(format!("{} 迁移后校验失败", name)));
+        }
+        migrated.push((*name).to_string());
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        current, current_kind, migrate, migrate_documents, JsonFileBackend, MemoryBackend,
+        SqliteBackend, StorageBackend, StorageBackendKind,
+    };
+    use crate::storage::{json_store, paths};
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    fn assert_roundtrip(backend: &dyn StorageBackend) {
+        let notes = json!({
+            "schemaVersion": 1,
+            "updatedAt": 1,
+            "data": { "notes": [{ "id": "note-1", "content": "便签" }, { "id": "note-2", "content": "第二条" }] }
+        });
+        assert_eq!(backend.read(paths::NOTES_FILE).expect("读取应成功"), None);
+
+        backend
+            .write(paths::NOTES_FILE, &notes)
+            .expect("写入应成功");
+        assert_eq!(
+            backend.read(paths::NOTES_FILE).expect("读取应成功"),
+            Some(notes)
+        );
+
+        let config = json!({ "version": "1.0", "apps": {}, "categories": {} });
+        backend
+            .write(paths::CONFIG_FILE, &config)
+            .expect("写入应成功");
+        backend.remove(paths::NOTES_FILE).expect("删除应成功");
+        assert_eq!(backend.read(paths::NOTES_FILE).expect("读取应成功"), None);
+        assert_eq!(
+            backend.read(paths::CONFIG_FILE).expect("读取应成功"),
+            Some(config)
+        );
+    }
+
+    #[test]
+    fn 各存储后端读写删除行为一致() {
+        let dir = unique_temp_dir("backend-roundtrip");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("应能创建测试目录");
+
+        assert_roundtrip(&MemoryBackend::default());
+        assert_roundtrip(&JsonFileBackend::new(dir.clone()));
+        assert_roundtrip(&SqliteBackend::open(&dir.join("data.db")).expect("应能打开数据库"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn 迁移到_sqlite_后切换后端且原文件保留() {
+        let data_dir = unique_temp_dir("backend-migration");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        json_store::write_enveloped_json(
+            &paths::todos_path(),
+            1,
+            json!({ "items": [{ "id": "todo-1", "title": "待办" }] }),
+        )
+        .expect("应能写入待办");
+
+        let report = migrate(StorageBackendKind::Sqlite).expect("迁移应成功");
+
+        assert_eq!(report.documents, vec![paths::TODOS_FILE.to_string()]);
+        assert!(report.backup_dir.is_some());
+        assert_eq!(current_kind(), StorageBackendKind::Sqlite);
+        assert!(paths::todos_path().exists());
+        let database =
+            SqliteBackend::open(&paths::data_file(paths::DATABASE_FILE)).expect("应能打开数据库");
+        let todos = database
+            .read(paths::TODOS_FILE)
+            .expect("读取应成功")
+            .expect("待办应已迁移");
+        assert_eq!(todos["data"]["items"][0]["title"], "待办");
+
+        let back = migrate(StorageBackendKind::Json).expect("迁移回 JSON 应成功");
+        assert_eq!(back.from, StorageBackendKind::Sqlite);
+        assert_eq!(current_kind(), StorageBackendKind::Json);
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 存储后端只打开一次并在迁移后替换() {
+        let data_dir = unique_temp_dir("backend-opened");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let first = current().expect("应能打开存储后端");
+        assert!(Arc::ptr_eq(&first, &current().expect("应能打开存储后端")));
+
+        migrate(StorageBackendKind::Sqlite).expect("迁移应成功");
+        let migrated = current().expect("应能打开存储后端");
+        assert_eq!(migrated.kind(), StorageBackendKind::Sqlite);
+        assert!(Arc::ptr_eq(
+            &migrated,
+            &current().expect("应能打开存储后端")
+        ));
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 源后端缺少的文档会从目标后端删除() {
+        let source = MemoryBackend::default();
+        let target = MemoryBackend::default();
+        source
+            .write(paths::CONFIG_FILE, &json!({ "apps": {} }))
+            .expect("写入应成功");
+        target
+            .write(paths::NOTES_FILE, &json!({ "data": { "notes": [] } }))
+            .expect("写入应成功");
+
+        let migrated = migrate_documents(&source, &target).expect("迁移应成功");
+
+        assert_eq!(migrated, vec![paths::CONFIG_FILE.to_string()]);
+        assert_eq!(target.read(paths::NOTES_FILE).expect("读取应成功"), None);
+    }
+}
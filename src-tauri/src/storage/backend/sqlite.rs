@@ -0,0 +1,275 @@
+use super::{StorageBackend, StorageBackendKind};
+use crate::storage::error::StorageError;
+use crate::storage::paths;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS documents (
+    name TEXT PRIMARY KEY,
+    content TEXT NOT NULL,
+    items_key TEXT
+);
+CREATE TABLE IF NOT EXISTS document_items (
+    name TEXT NOT NULL,
+    item_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    PRIMARY KEY (name, item_id)
+);
+";
+
+/// 所有文档保存在同一个 SQLite 数据库中。
+///
+/// 场景、便签、待办和剪贴板的条目列表逐条保存为单独的行，写入时只改写内容或顺序变化的条目，
+/// 其余部分作为整体保存在 `documents` 表中。
+pub struct SqliteBackend {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteBackend {
+    pub fn open(path: &Path) -> Result<Self, StorageError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, StorageError> {
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(SqliteBackend {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// 把数据库的一致快照写入 `target`，正在进行的写入事务完成后才会复制。
+    ///
+    /// 数据库可能正被程序打开并写入，直接复制文件可能得到写了一半的内容。
+    pub fn copy_database(source: &Path, target: &Path) -> Result<(), StorageError> {
+        let connection = Connection::open(source)?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute("VACUUM INTO ?1", params![target.to_string_lossy()])?;
+        Ok(())
+    }
+
+    /// 写入文档并返回实际改写的条目行数
+    fn write_document(&self, name: &str, value: &Value) -> Result<usize, StorageError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        let changed = match itemized(name, value) {
+            Some((key, skeleton, items)) => {
+                transaction.execute(
+                    "INSERT OR REPLACE INTO documents (name, content, items_key) VALUES (?1, ?2, ?3)",
+                    params![name, serde_json::to_string(&skeleton)?, key],
+                )?;
+                write_items(&transaction, name, items)?
+            }
+            None => {
+                transaction.execute(
+                    "INSERT OR REPLACE INTO documents (name, content, items_key) VALUES (?1, ?2, NULL)",
+                    params![name, serde_json::to_string(value)?],
+                )?;
+                transaction.execute("DELETE FROM document_items WHERE name = ?1", params![name])?
+            }
+        };
+        transaction.commit()?;
+        Ok(changed)
+    }
+}
+
+impl StorageBackend for SqliteBackend {
+    fn kind(&self) -> StorageBackendKind {
+        StorageBackendKind::Sqlite
+    }
+
+    fn read(&self, name: &str) -> Result<Option<Value>, StorageError> {
+        let connection = self.connection.lock().unwrap();
+        let row: Option<(String, Option<String>)> = connection
+            .query_row(
+                "SELECT content, items_key FROM documents WHERE name = ?1",
+                params![name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((content, items_key)) = row else {
+            return Ok(None);
+        };
+
+        let mut value: Value = serde_json::from_str(&content)?;
+        if let Some(key) = items_key {
+            let mut statement = connection
+                .prepare("SELECT content FROM document_items WHERE name = ?1 ORDER BY position")?;
+            let items = statement
+                .query_map(params![name], |row| row.get::<_, String>(0))?
+                .map(|content| Ok(serde_json::from_str::<Value>(&content?)?))
+                .collect::<Result<Vec<Value>, StorageError>>()?;
+            if let Some(data) = value.get_mut("data").and_then(Value::as_object_mut) {
+                data.insert(key, Value::Array(items));
+            }
+        }
+        Ok(Some(value))
+    }
+
+    fn write(&self, name: &str, value: &Value) -> Result<(), StorageError> {
+        self.write_document(name, value).map(|_| ())
+    }
+
+    fn remove(&self, name: &str) -> Result<(), StorageError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM documents WHERE name = ?1", params![name])?;
+        transaction.execute("DELETE FROM document_items WHERE name = ?1", params![name])?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+fn items_key(name: &str) -> Option<&'static str> {
+    match name {
+        paths::SCENES_FILE => Some("scenes"),
+        paths::NOTES_FILE => Some("notes"),
+        paths::TODOS_FILE | paths::CLIPBOARD_FILE => Some("items"),
+        _ => None,
+    }
+}
+
+/// 拆出可以逐条保存的条目列表；条目必须都是带唯一字符串 `id` 的对象，否则整体保存
+fn itemized<'a>(name: &str, value: &'a Value) -> Option<(&'static str, Value, &'a [Value])> {
+    let key = items_key(name)?;
+    let items = value.get("data")?.get(key)?.as_array()?;
+    let mut ids = HashSet::new();
+    let unique = items.iter().all(|item| {
+        item.get("id")
+            .and_then(Value::as_str)
+            .is_some_and(|id| ids.insert(id))
+    });
+    if !unique {
+        return None;
+    }
+
+    let mut skeleton = value.clone();
+    skeleton["data"].as_object_mut()?.remove(key);
+    Some((key, skeleton, items))
+}
+
+fn write_items(
+    transaction: &Transaction,
+    name: &str,
+    items: &[Value],
+) -> Result<usize, StorageError> {
+    let mut existing: HashMap<String, (i64, String)> = HashMap::new();
+    {
+        let mut statement = transaction
+            .prepare("SELECT item_id, position, content FROM document_items WHERE name = ?1")?;
+        let rows = statement.query_map(params![name], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+        for row in rows {
+            let (id, stored) = row?;
+            existing.insert(id, stored);
+        }
+    }
+
+    let mut changed = 0;
+    for (position, item) in items.iter().enumerate() {
+        let id = item.get("id").and_then(Value::as_str).unwrap_or_default();
+        let position = position as i64;
+        let content = serde_json::to_string(item)?;
+        match existing.remove(id) {
+            Some((stored_position, stored_content)) if stored_content == content => {
+                if stored_position != position {
+                    transaction.execute(
+                        "UPDATE document_items SET position = ?3 WHERE name = ?1 AND item_id = ?2",
+                        params![name, id, position],
+                    )?;
+                    changed += 1;
+                }
+            }
+            _ => {
+                transaction.execute(
+                    "INSERT OR REPLACE INTO document_items (name, item_id, position, content) VALUES (?1, ?2, ?3, ?4)",
+                    params![name, id, position, content],
+                )?;
+                changed += 1;
+            }
+        }
+    }
+
+    for id in existing.keys() {
+        transaction.execute(
+            "DELETE FROM document_items WHERE name = ?1 AND item_id = ?2",
+            params![name, id],
+        )?;
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqliteBackend;
+    use crate::storage::backend::StorageBackend;
+    use crate::storage::paths;
+    use serde_json::json;
+
+    #[test]
+    fn 只改写内容或顺序变化的条目() {
+        let backend = SqliteBackend::open_in_memory().expect("应能打开内存数据库");
+        let document = |items: serde_json::Value| json!({ "schemaVersion": 1, "updatedAt": 1, "data": { "items": items } });
+
+        let first = document(json!([
+            { "id": "a", "title": "一" },
+            { "id": "b", "title": "二" },
+            { "id": "c", "title": "三" }
+        ]));
+        assert_eq!(
+            backend
+                .write_document(paths::TODOS_FILE, &first)
+                .expect("写入应成功"),
+            3
+        );
+        assert_eq!(
+            backend
+                .write_document(paths::TODOS_FILE, &first)
+                .expect("写入应成功"),
+            0
+        );
+
+        let second = document(json!([
+            { "id": "b", "title": "二" },
+            { "id": "a", "title": "一" },
+            { "id": "d", "title": "四" }
+        ]));
+        assert_eq!(
+            backend
+                .write_document(paths::TODOS_FILE, &second)
+                .expect("写入应成功"),
+            4,
+            "两条移动、一条新增、一条删除"
+        );
+        assert_eq!(
+            backend.read(paths::TODOS_FILE).expect("读取应成功"),
+            Some(second)
+        );
+
+        let duplicated = document(json!([{ "id": "x" }, { "id": "x" }]));
+        backend
+            .write(paths::TODOS_FILE, &duplicated)
+            .expect("重复 id 时应整体保存");
+        assert_eq!(
+            backend.read(paths::TODOS_FILE).expect("读取应成功"),
+            Some(duplicated)
+        );
+    }
+}
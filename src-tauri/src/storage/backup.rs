@@ -1,7 +1,9 @@
 use crate::storage::atomic_write::write_atomic;
+use crate::storage::backend::{self, SqliteBackend};
 use crate::storage::error::StorageError;
-use crate::storage::json_store::now_millis;
+use crate::storage::json_store::{now_millis, read_json};
 use crate::storage::paths;
+use crate::storage::persisted;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
@@ -13,6 +15,18 @@ pub struct BackupManifest {
     pub created_at: u64,
     pub reason: String,
     pub files: Vec<String>,
+    /// 备份的用途，保留策略据此识别需要额外保留的备份；旧清单没有该字段，按普通备份处理
+    #[serde(default)]
+    pub purpose: BackupPurpose,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupPurpose {
+    #[default]
+    Regular,
+    /// 恢复备份前为当前数据创建的备份，保留策略会始终保留其中最新的一份
+    RestoreSafety,
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +35,31 @@ pub struct BackupResult {
     pub files: Vec<String>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupKind {
+    Full,
+    Migration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub id: String,
+    pub kind: BackupKind,
+    pub dir: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<BackupManifest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+const RESTORE_SAFETY_REASON: &str = "恢复备份前自动备份";
+
+const FULL_BACKUP_PREFIX: &str = "full-";
+const MIGRATION_BACKUP_PREFIX: &str = "migration-";
+
 const FULL_BACKUP_FILES: &[&str] = &[
     paths::CONFIG_FILE,
     paths::SCENES_FILE,
@@ -29,13 +68,15 @@ const FULL_BACKUP_FILES: &[&str] = &[
     paths::CLIPBOARD_FILE,
     paths::ACTIONS_FILE,
     paths::MIGRATIONS_FILE,
+    paths::STORAGE_FILE,
+    paths::DATABASE_FILE,
 ];
 
 pub fn create_migration_backup(
     reason: &str,
     legacy_payload: Option<&Value>,
 ) -> Result<BackupResult, StorageError> {
-    let dir = paths::backups_dir().join(format!("migration-{}", now_millis()));
+    let dir = paths::backups_dir().join(format!("{}{}", MIGRATION_BACKUP_PREFIX, now_millis()));
     fs::create_dir_all(&dir)?;
 
     let mut files = Vec::new();
@@ -55,6 +96,7 @@ pub fn create_migration_backup(
         created_at: now_millis(),
         reason: reason.to_string(),
         files: files.clone(),
+        purpose: BackupPurpose::Regular,
     };
     let manifest_content = serde_json::to_vec_pretty(&manifest)?;
     write_atomic(&dir.join("manifest.json"), &manifest_content)?;
@@ -64,16 +106,33 @@ pub fn create_migration_backup(
 }
 
 pub fn create_full_backup(reason: &str) -> Result<BackupResult, StorageError> {
-    let dir = paths::backups_dir().join(format!("full-{}", now_millis()));
+    create_full_backup_for(reason, BackupPurpose::Regular)
+}
+
+/// 恢复备份前为当前数据创建完整备份
+pub fn create_restore_safety_backup() -> Result<BackupResult, StorageError> {
+    create_full_backup_for(RESTORE_SAFETY_REASON, BackupPurpose::RestoreSafety)
+}
+
+fn create_full_backup_for(
+    reason: &str,
+    purpose: BackupPurpose,
+) -> Result<BackupResult, StorageError> {
+    let dir = paths::backups_dir().join(format!("{}{}", FULL_BACKUP_PREFIX, now_millis()));
     fs::create_dir_all(&dir)?;
 
     let mut files = Vec::new();
     for file_name in FULL_BACKUP_FILES {
         let source = paths::data_file(file_name);
-        if source.exists() {
+        if !source.exists() {
+            continue;
+        }
+        if *file_name == paths::DATABASE_FILE {
+            SqliteBackend::copy_database(&source, &dir.join(file_name))?;
+        } else {
             fs::copy(&source, dir.join(file_name))?;
-            files.push((*file_name).to_string());
         }
+        files.push((*file_name).to_string());
     }
 
     let icons_source = paths::icons_dir();
@@ -83,12 +142,28 @@ pub fn create_full_backup(reason: &str) -> Result<BackupResult, StorageError> {
         files.push("icons/".to_string());
     }
 
-    write_manifest(&dir, reason, &mut files)?;
+    write_manifest(&dir, reason, purpose, &mut files)?;
     Ok(BackupResult { dir, files })
 }
 
+/// 用备份中的文件替换数据目录中的文件，期间暂停数据写入；完成后重新打开存储后端
 pub fn restore_full_backup(backup_dir: &Path) -> Result<(), StorageError> {
-    for file_name in FULL_BACKUP_FILES {
+    let _writes = persisted::lock_writes();
+    // 先关闭数据库连接，数据库文件才能被替换
+    backend::close_current();
+    let result = restore_files(backup_dir);
+    backend::close_current();
+    result
+}
+
+/// 恢复时不回写的文件：迁移记录描述的是当前数据目录已经完成的迁移，回写旧记录会让迁移重复执行
+const RESTORE_EXCLUDED_FILES: &[&str] = &[paths::MIGRATIONS_FILE];
+
+fn restore_files(backup_dir: &Path) -> Result<(), StorageError> {
+    for file_name in FULL_BACKUP_FILES
+        .iter()
+        .filter(|file_name| !RESTORE_EXCLUDED_FILES.contains(file_name))
+    {
         let source = backup_dir.join(file_name);
         let target = paths::data_file(file_name);
         if source.exists() {
@@ -112,6 +187,117 @@ pub fn restore_full_backup(backup_dir: &Path) -> Result<(), StorageError> {
     Ok(())
 }
 
+/// 列出备份目录下的全部备份，按创建时间倒序排列
+pub fn list_backups() -> Result<Vec<BackupSummary>, StorageError> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(paths::backups_dir())? {
+        let entry = entry?;
+        let dir = entry.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        let Some(kind) = backup_kind(&id) else {
+            continue;
+        };
+
+        let (manifest, error) = match read_json::<BackupManifest>(&dir.join("manifest.json")) {
+            Ok(manifest) => (Some(manifest), None),
+            Err(error) => (None, Some(error.to_string())),
+        };
+        backups.push(BackupSummary {
+            id,
+            kind,
+            dir: dir.to_string_lossy().to_string(),
+            size: dir_size(&dir),
+            manifest,
+            error,
+        });
+    }
+
+    backups.sort_by(|left, right| {
+        backup_timestamp(&right.id)
+            .cmp(&backup_timestamp(&left.id))
+            .then_with(|| right.id.cmp(&left.id))
+    });
+    Ok(backups)
+}
+
+/// 将前端传入的备份 id 解析为备份目录，拒绝任何越出备份目录的路径
+pub fn resolve_backup_dir(id: &str) -> Result<(PathBuf, BackupKind), StorageError> {
+    let kind = backup_kind(id)
+        .filter(|_| !id.contains(['/', '\\']) && !id.contains(".."))
+        .ok_or_else(|| invalid_backup_id(id))?;
+    let dir = paths::backups_dir().join(id);
+    if !dir.is_dir() {
+        return Err(StorageError::NotFound(format!("备份 {}", id)));
+    }
+    Ok((dir, kind))
+}
+
+pub fn delete_backup(id: &str) -> Result<(), StorageError> {
+    let (dir, _) = resolve_backup_dir(id)?;
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+/// 恢复指定备份。迁移备份只包含旧配置，因此只回写其中存在的配置文件。
+pub fn restore_backup(id: &str) -> Result<(), StorageError> {
+    let (dir, kind) = resolve_backup_dir(id)?;
+    match kind {
+        BackupKind::Full => restore_full_backup(&dir),
+        BackupKind::Migration => {
+            let source = dir.join(paths::CONFIG_FILE);
+            if source.exists() {
+                let config: Value = read_json(&source)?;
+                backend::current()?.write(paths::CONFIG_FILE, &config)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                entry.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+            }
+        })
+        .sum()
+}
+
+fn backup_kind(id: &str) -> Option<BackupKind> {
+    if id.starts_with(FULL_BACKUP_PREFIX) {
+        Some(BackupKind::Full)
+    } else if id.starts_with(MIGRATION_BACKUP_PREFIX) {
+        Some(BackupKind::Migration)
+    } else {
+        None
+    }
+}
+
+pub fn backup_timestamp(id: &str) -> u64 {
+    id.rsplit('-')
+        .next()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+fn invalid_backup_id(id: &str) -> StorageError {
+    StorageError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("备份 id 无效: {}", id),
+    ))
+}
+
 pub fn copy_dir_recursive(source: &Path, target: &Path) -> Result<(), StorageError> {
     fs::create_dir_all(target)?;
     for entry in fs::read_dir(source)? {
@@ -130,14 +316,135 @@ pub fn copy_dir_recursive(source: &Path, target: &Path) -> Result<(), StorageErr
     Ok(())
 }
 
-fn write_manifest(dir: &Path, reason: &str, files: &mut Vec<String>) -> Result<(), StorageError> {
+fn write_manifest(
+    dir: &Path,
+    reason: &str,
+    purpose: BackupPurpose,
+    files: &mut Vec<String>,
+) -> Result<(), StorageError> {
     let manifest = BackupManifest {
         created_at: now_millis(),
         reason: reason.to_string(),
         files: files.clone(),
+        purpose,
     };
     let manifest_content = serde_json::to_vec_pretty(&manifest)?;
     write_atomic(&dir.join("manifest.json"), &manifest_content)?;
     files.push("manifest.json".to_string());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{create_full_backup, delete_backup, list_backups, restore_backup, BackupKind};
+    use crate::storage::backend::{SqliteBackend, StorageBackend};
+    use crate::storage::error::StorageError;
+    use crate::storage::{json_store, paths};
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 可以列出恢复并删除完整备份() {
+        let data_dir = unique_temp_dir("backup-browser");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        fs::write(paths::config_path(), r#"{"apps":{"old-app":{}}}"#).expect("应能写入配置");
+        fs::write(paths::icons_dir().join("old-app.png"), b"icon").expect("应能写入图标");
+        fs::write(paths::migrations_path(), r#"{"completed":[]}"#).expect("应能写入迁移记录");
+        let backup = create_full_backup("测试备份").expect("备份应成功");
+        let id = backup
+            .dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .expect("备份目录应有名称");
+
+        let backups = list_backups().expect("应能列出备份");
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].id, id);
+        assert_eq!(backups[0].kind, BackupKind::Full);
+        assert!(backups[0].size > 0);
+        assert_eq!(
+            backups[0]
+                .manifest
+                .as_ref()
+                .map(|manifest| manifest.reason.as_str()),
+            Some("测试备份")
+        );
+
+        fs::write(paths::config_path(), r#"{"apps":{}}"#).expect("应能覆盖配置");
+        fs::remove_file(paths::icons_dir().join("old-app.png")).expect("应能删除图标");
+        let migrations = r#"{"completed":["local-storage-to-json-v1"]}"#;
+        fs::write(paths::migrations_path(), migrations).expect("应能更新迁移记录");
+        restore_backup(&id).expect("恢复应成功");
+        let config_text = fs::read_to_string(paths::config_path()).expect("应能读取恢复配置");
+        assert!(config_text.contains("old-app"));
+        assert!(paths::icons_dir().join("old-app.png").is_file());
+        assert_eq!(
+            fs::read_to_string(paths::migrations_path()).expect("应能读取迁移记录"),
+            migrations,
+            "恢复备份不回写迁移记录"
+        );
+        assert!(matches!(
+            restore_backup("full-1"),
+            Err(StorageError::NotFound(resource)) if resource == "备份 full-1"
+        ));
+
+        delete_backup(&id).expect("删除应成功");
+        assert!(list_backups().expect("应能列出备份").is_empty());
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 备份_sqlite_数据库时写入一致的快照() {
+        let data_dir = unique_temp_dir("backup-sqlite");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let database =
+            SqliteBackend::open(&paths::data_file(paths::DATABASE_FILE)).expect("应能打开数据库");
+        let notes = json!({ "schemaVersion": 1, "updatedAt": 1, "data": { "notes": [{ "id": "note-1" }] } });
+        database
+            .write(paths::NOTES_FILE, &notes)
+            .expect("写入应成功");
+
+        let backup = create_full_backup("测试备份").expect("备份应成功");
+        assert!(backup.files.contains(&paths::DATABASE_FILE.to_string()));
+        let copied = SqliteBackend::open(&backup.dir.join(paths::DATABASE_FILE))
+            .expect("应能打开备份数据库");
+        assert_eq!(
+            copied.read(paths::NOTES_FILE).expect("读取应成功"),
+            Some(notes)
+        );
+
+        drop(database);
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 越出备份目录的备份_id_会被拒绝() {
+        let data_dir = unique_temp_dir("backup-invalid-id");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(paths::backups_dir().join("full-1")).expect("应能创建备份目录");
+
+        assert!(delete_backup("../icons").is_err());
+        assert!(delete_backup("full-1/../../icons").is_err());
+        assert!(delete_backup("icons").is_err());
+        assert!(delete_backup("full-404").is_err());
+        assert!(paths::backups_dir().join("full-1").is_dir());
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
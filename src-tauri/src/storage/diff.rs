@@ -0,0 +1,211 @@
+use crate::storage::merge::{item_id, MergeCounts};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+const LABEL_MAX_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemChangeKind {
+    /// 只存在于数据包中
+    Added,
+    /// 两边都有但内容不同
+    Changed,
+    /// 只存在于本地，整体覆盖导入时会被删除
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemDiff {
+    pub id: String,
+    /// 便于识别条目的名称，依次取 `name`、`title`、`content`，都没有时为 id
+    pub label: String,
+    pub kind: ItemChangeKind,
+    /// 仅 `Changed` 条目记录字段差异
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldChange>,
+}
+
+/// 按 `id` 比较本地条目和数据包条目，内容相同的条目不出现在结果中。
+///
+/// `ignored` 中的字段不参与比较，用于跳过由其他数据推导出的字段。
+pub fn diff_items(mine: &[Value], theirs: &[Value], ignored: &[&str]) -> Vec<ItemDiff> {
+    let mut diffs = Vec::new();
+    let mut seen = HashSet::new();
+    for item in theirs {
+        let Some(id) = item_id(item) else {
+            continue;
+        };
+        seen.insert(id);
+        match mine.iter().find(|current| item_id(current) == Some(id)) {
+            None => diffs.push(ItemDiff {
+                id: id.to_string(),
+                label: item_label(item),
+                kind: ItemChangeKind::Added,
+                fields: Vec::new(),
+            }),
+            Some(current) => {
+                let fields = diff_fields(current, item, ignored);
+                if !fields.is_empty() {
+                    diffs.push(ItemDiff {
+                        id: id.to_string(),
+                        label: item_label(item),
+                        kind: ItemChangeKind::Changed,
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    for item in mine {
+        let Some(id) = item_id(item) else {
+            continue;
+        };
+        if !seen.contains(id) {
+            diffs.push(ItemDiff {
+                id: id.to_string(),
+                label: item_label(item),
+                kind: ItemChangeKind::Removed,
+                fields: Vec::new(),
+            });
+        }
+    }
+    diffs
+}
+
+/// 比较两个条目的顶层字段，嵌套对象和数组按整体比较
+pub fn diff_fields(before: &Value, after: &Value, ignored: &[&str]) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter(|field| !ignored.contains(&field.as_str()))
+        .filter(|field| before.get(*field) != after.get(*field))
+        .map(|field| FieldChange {
+            field: field.clone(),
+            before: before.get(field).cloned(),
+            after: after.get(field).cloned(),
+        })
+        .collect()
+}
+
+fn item_label(item: &Value) -> String {
+    let label = ["name", "title", "content"]
+        .iter()
+        .find_map(|field| item.get(*field).and_then(Value::as_str))
+        .filter(|label| !label.trim().is_empty())
+        .or_else(|| item_id(item))
+        .unwrap_or_default()
+        .trim();
+    if label.chars().count() > LABEL_MAX_CHARS {
+        let truncated: String = label.chars().take(LABEL_MAX_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        label.to_string()
+    }
+}
+
+/// 只把 `ids` 对应条目的变化应用到本地列表，其余本地条目保持不变。
+///
+/// 数据包中有该 id 时新增或替换本地条目，没有时从本地删除。
+pub fn apply_selected(mine: &mut Vec<Value>, theirs: &[Value], ids: &[String]) -> MergeCounts {
+    let mut counts = MergeCounts::default();
+    for id in ids {
+        let incoming = theirs
+            .iter()
+            .find(|item| item_id(item) == Some(id.as_str()));
+        let index = mine
+            .iter()
+            .position(|item| item_id(item) == Some(id.as_str()));
+        match (incoming, index) {
+            (Some(incoming), None) => {
+                mine.push(incoming.clone());
+                counts.added += 1;
+            }
+            (Some(incoming), Some(index)) if mine[index] != *incoming => {
+                mine[index] = incoming.clone();
+                counts.updated += 1;
+            }
+            (None, Some(index)) => {
+                mine.remove(index);
+                counts.removed += 1;
+            }
+            _ => counts.skipped += 1,
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_selected, diff_items, ItemChangeKind};
+    use serde_json::json;
+
+    #[test]
+    fn 按_id_列出新增修改和删除的条目及字段差异() {
+        let mine = vec![
+            json!({ "id": "a", "name": "编辑器", "path": "C:\\a.exe", "apps": ["x"] }),
+            json!({ "id": "b", "name": "终端" }),
+            json!({ "id": "same", "name": "相同" }),
+        ];
+        let theirs = vec![
+            json!({ "id": "a", "name": "编辑器", "path": "D:\\a.exe", "apps": ["y"] }),
+            json!({ "id": "same", "name": "相同" }),
+            json!({ "id": "c", "title": "新待办" }),
+        ];
+
+        let diffs = diff_items(&mine, &theirs, &["apps"]);
+
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[0].kind, ItemChangeKind::Changed);
+        assert_eq!(diffs[0].fields.len(), 1, "被忽略的字段不应出现在差异中");
+        assert_eq!(diffs[0].fields[0].field, "path");
+        assert_eq!(diffs[0].fields[0].after, Some(json!("D:\\a.exe")));
+        assert_eq!(diffs[1].kind, ItemChangeKind::Added);
+        assert_eq!(diffs[1].label, "新待办");
+        assert_eq!(diffs[2].kind, ItemChangeKind::Removed);
+        assert_eq!(diffs[2].id, "b");
+    }
+
+    #[test]
+    fn 只应用所选条目的变化() {
+        let mut mine = vec![
+            json!({ "id": "keep", "name": "本地" }),
+            json!({ "id": "drop", "name": "待删除" }),
+            json!({ "id": "edit", "name": "旧名称" }),
+        ];
+        let theirs = vec![
+            json!({ "id": "edit", "name": "新名称" }),
+            json!({ "id": "new", "name": "新增" }),
+            json!({ "id": "skip", "name": "未选择" }),
+        ];
+        let ids = ["drop", "edit", "new"].map(str::to_string);
+
+        let counts = apply_selected(&mut mine, &theirs, &ids);
+
+        assert_eq!((counts.added, counts.updated, counts.removed), (1, 1, 1));
+        let names: Vec<&str> = mine
+            .iter()
+            .map(|item| item["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["本地", "新名称", "新增"]);
+    }
+}
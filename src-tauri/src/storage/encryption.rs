@@ -0,0 +1,191 @@
+use crate::storage::error::StorageError;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+pub const CIPHER_AES_256_GCM: &str = "aes-256-gcm";
+pub const KDF_ARGON2ID: &str = "argon2id";
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+// OWASP 推荐的 Argon2id 最低参数：19 MiB 内存、2 次迭代、1 个并行度
+const DEFAULT_MEMORY_KIB: u32 = 19 * 1024;
+const DEFAULT_ITERATIONS: u32 = 2;
+const DEFAULT_PARALLELISM: u32 = 1;
+/// 清单中的参数来自数据包，不可信；超过默认值 4 倍的参数按数据包无效处理，避免打开数据包时耗尽内存或长时间卡住
+const MAX_PARAM_FACTOR: u32 = 4;
+/// 用派生出的密钥加密这段固定内容，解密时先用它区分“密码错误”和“内容被篡改”
+const KEY_CHECK_PLAINTEXT: &[u8] = b"program-manager-key-check";
+
+/// 加密参数，随数据包清单一起保存；盐、随机数和校验值均为 base64
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionParams {
+    pub cipher: String,
+    pub kdf: String,
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub nonce: String,
+    pub key_check: String,
+}
+
+/// 用口令加密内容，返回加密参数和密文。每次调用都会生成新的盐和随机数。
+pub fn encrypt(
+    passphrase: &str,
+    plaintext: &[u8],
+) -> Result<(EncryptionParams, Vec<u8>), StorageError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = Aes256Gcm::new(&derive_key(
+        passphrase,
+        &salt,
+        DEFAULT_MEMORY_KIB,
+        DEFAULT_ITERATIONS,
+        DEFAULT_PARALLELISM,
+    )?);
+
+    let check_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let mut key_check = check_nonce.to_vec();
+    key_check.extend(
+        cipher
+            .encrypt(&check_nonce, KEY_CHECK_PLAINTEXT)
+            .map_err(|_| StorageError::Package("加密失败".to_string()))?,
+    );
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| StorageError::Package("加密失败".to_string()))?;
+
+    let params = EncryptionParams {
+        cipher: CIPHER_AES_256_GCM.to_string(),
+        kdf: KDF_ARGON2ID.to_string(),
+        salt: STANDARD.encode(salt),
+        memory_kib: DEFAULT_MEMORY_KIB,
+        iterations: DEFAULT_ITERATIONS,
+        parallelism: DEFAULT_PARALLELISM,
+        nonce: STANDARD.encode(nonce),
+        key_check: STANDARD.encode(key_check),
+    };
+    Ok((params, ciphertext))
+}
+
+/// 按清单中的参数解密。
+///
+/// 密钥校验值无法解开时返回 `WrongPassphrase`；校验值正确但内容无法通过认证时，
+/// 说明密文被改动或截断，按数据包无效处理。
+pub fn decrypt(
+    passphrase: &str,
+    params: &EncryptionParams,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, StorageError> {
+    if params.cipher != CIPHER_AES_256_GCM || params.kdf != KDF_ARGON2ID {
+        return Err(StorageError::Package(format!(
+            "不支持的加密方式 {} / {}",
+            params.cipher, params.kdf
+        )));
+    }
+    let salt = decode_field("salt", &params.salt)?;
+    let nonce = decode_field("nonce", &params.nonce)?;
+    let key_check = decode_field("keyCheck", &params.key_check)?;
+    if nonce.len() != NONCE_LEN || key_check.len() <= NONCE_LEN {
+        return Err(StorageError::Package("加密参数无效".to_string()));
+    }
+    for (name, value, default) in [
+        ("memoryKib", params.memory_kib, DEFAULT_MEMORY_KIB),
+        ("iterations", params.iterations, DEFAULT_ITERATIONS),
+        ("parallelism", params.parallelism, DEFAULT_PARALLELISM),
+    ] {
+        if value > default * MAX_PARAM_FACTOR {
+            return Err(StorageError::Package(format!(
+                "加密参数 {} 过大: {}",
+                name, value
+            )));
+        }
+    }
+
+    let cipher = Aes256Gcm::new(&derive_key(
+        passphrase,
+        &salt,
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+    )?);
+    let (check_nonce, check_ciphertext) = key_check.split_at(NONCE_LEN);
+    match cipher.decrypt(Nonce::from_slice(check_nonce), check_ciphertext) {
+        Ok(check) if check == KEY_CHECK_PLAINTEXT => {}
+        _ => return Err(StorageError::WrongPassphrase),
+    }
+
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext)
+        .map_err(|_| StorageError::Package("加密内容校验失败，数据包可能已被篡改".to_string()))
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> Result<Key<Aes256Gcm>, StorageError> {
+    let params = Params::new(memory_kib, iterations, parallelism, Some(KEY_LEN))
+        .map_err(|error| StorageError::Package(format!("密钥派生参数无效: {}", error)))?;
+    let mut key = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|error| StorageError::Package(format!("密钥派生失败: {}", error)))?;
+    Ok(key.into())
+}
+
+fn decode_field(name: &str, value: &str) -> Result<Vec<u8>, StorageError> {
+    STANDARD
+        .decode(value)
+        .map_err(|_| StorageError::Package(format!("加密参数 {} 无效", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt, EncryptionParams};
+    use crate::storage::error::StorageError;
+
+    #[test]
+    fn 正确口令可以解密而错误口令和篡改内容分别报错() {
+        let (params, ciphertext) = encrypt("correct horse", b"{\"notes\":[]}").expect("加密应成功");
+        assert_ne!(ciphertext, b"{\"notes\":[]}");
+
+        let plaintext = decrypt("correct horse", &params, &ciphertext).expect("解密应成功");
+        assert_eq!(plaintext, b"{\"notes\":[]}");
+
+        let wrong = decrypt("battery staple", &params, &ciphertext).expect_err("错误口令应失败");
+        assert!(matches!(wrong, StorageError::WrongPassphrase));
+
+        let mut tampered = ciphertext.clone();
+        tampered[0] ^= 0xff;
+        let tampered_error =
+            decrypt("correct horse", &params, &tampered).expect_err("篡改内容应失败");
+        assert!(matches!(tampered_error, StorageError::Package(_)));
+    }
+
+    #[test]
+    fn 拒绝超出上限的密钥派生参数() {
+        let (params, ciphertext) = encrypt("correct horse", b"{}").expect("加密应成功");
+        for tamper in [
+            |params: &mut EncryptionParams| params.memory_kib = 4 * 1024 * 1024,
+            |params: &mut EncryptionParams| params.iterations = 1000,
+            |params: &mut EncryptionParams| params.parallelism = 64,
+        ] {
+            let mut params = params.clone();
+            tamper(&mut params);
+            let error = decrypt("correct horse", &params, &ciphertext).expect_err("参数过大应失败");
+            assert!(matches!(error, StorageError::Package(_)), "{:?}", error);
+        }
+    }
+}
@@ -1,9 +1,42 @@
+use crate::storage::json_store::DataEnvelope;
+use serde_json::Value;
 use std::fmt;
 
 #[derive(Debug)]
 pub enum StorageError {
     Io(std::io::Error),
     Json(serde_json::Error),
+    /// 请求的备份、历史版本等不存在，内容为资源说明
+    NotFound(String),
+    SchemaTooNew {
+        name: String,
+        found: u32,
+        supported: u32,
+    },
+    Migration(String),
+    Package(String),
+    WrongPassphrase,
+    Database(String),
+    /// 数据不符合该类数据的结构，`field` 为出问题的字段路径
+    InvalidData {
+        name: String,
+        field: String,
+        reason: String,
+    },
+    /// 写入时数据已被其他窗口改写，`current` 为当前保存的数据
+    Conflict {
+        name: String,
+        current: Option<Box<DataEnvelope<Value>>>,
+    },
+}
+
+/// 资源不存在的提示；资源说明以编号等字母数字结尾时补一个空格，与后面的中文分开
+pub(crate) fn not_found_message(resource: &str) -> String {
+    let separator = match resource.chars().last() {
+        Some(last) if last.is_ascii_alphanumeric() => " ",
+        _ => "",
+    };
+    format!("{}{}不存在", resource, separator)
 }
 
 impl fmt::Display for StorageError {
@@ -11,6 +44,28 @@ impl fmt::Display for StorageError {
         match self {
             StorageError::Io(error) => write!(f, "文件读写失败: {}", error),
             StorageError::Json(error) => write!(f, "JSON 解析失败: {}", error),
+            StorageError::NotFound(resource) => f.write_str(&not_found_message(resource)),
+            StorageError::SchemaTooNew {
+                name,
+                found,
+                supported,
+            } => write!(
+                f,
+                "{} 的数据结构版本为 {}，当前程序最高支持 {}，请升级程序后再打开",
+                name, found, supported
+            ),
+            StorageError::Migration(message) => write!(f, "数据结构升级失败: {}", message),
+            StorageError::Package(message) => write!(f, "数据包无效: {}", message),
+            StorageError::WrongPassphrase => write!(f, "数据包密码错误"),
+            StorageError::Database(message) => write!(f, "数据库读写失败: {}", message),
+            StorageError::InvalidData {
+                name,
+                field,
+                reason,
+            } => write!(f, "{} 的字段 {} 无效: {}", name, field, reason),
+            StorageError::Conflict { name, .. } => {
+                write!(f, "{} 已被其他窗口修改，请合并后重试", name)
+            }
         }
     }
 }
@@ -28,3 +83,9 @@ impl From<serde_json::Error> for StorageError {
         StorageError::Json(error)
     }
 }
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(error: rusqlite::Error) -> Self {
+        StorageError::Database(error.to_string())
+    }
+}
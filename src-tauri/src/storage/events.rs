@@ -0,0 +1,262 @@
+use crate::storage::backend;
+use crate::storage::json_store::DataEnvelope;
+use crate::storage::paths::CONFIG_FILE;
+use crate::storage::persisted::PersistedDataType;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError, RwLock};
+
+/// 数据写入后广播给所有窗口的 `persisted-data-changed` 事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedDataChanged {
+    pub data_type: PersistedDataType,
+    pub updated_at: u64,
+    /// 不是由某个窗口的写入触发，而是来自程序之外的修改（同步工具、文本编辑器等）或恢复备份，
+    /// 窗口收到后总是重新读取
+    pub external: bool,
+}
+
+type ChangeListener = Box<dyn Fn(&PersistedDataChanged) + Send + Sync>;
+
+static LISTENER: RwLock<Option<ChangeListener>> = RwLock::new(None);
+
+type ConfigListener = Box<dyn Fn() + Send + Sync>;
+
+/// 配置文件被外部修改时的接收者；配置不经过数据外壳，由主程序重新读取后发送 `config-changed`
+static CONFIG_LISTENER: RwLock<Option<ConfigListener>> = RwLock::new(None);
+
+/// 每个数据文件最近一次写入或检查到的内容指纹，用来区分程序自己的写入和外部修改
+static KNOWN_CONTENT: Mutex<Option<HashMap<PathBuf, u64>>> = Mutex::new(None);
+
+/// 设置数据变化的接收者，启动时由主程序注册为向所有窗口发送事件
+pub fn set_listener(listener: impl Fn(&PersistedDataChanged) + Send + Sync + 'static) {
+    *LISTENER.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(listener));
+}
+
+/// 设置配置文件被外部修改时的接收者
+pub fn set_config_listener(listener: impl Fn() + Send + Sync + 'static) {
+    *CONFIG_LISTENER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(listener));
+}
+
+fn notify(change: &PersistedDataChanged) {
+    if let Some(listener) = LISTENER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        listener(change);
+    }
+}
+
+fn fingerprint(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 记录文件内容指纹，内容与上次记录相同时返回 `false`
+fn remember_content(path: PathBuf, content: &[u8]) -> bool {
+    let fingerprint = fingerprint(content);
+    let mut known = KNOWN_CONTENT.lock().unwrap_or_else(PoisonError::into_inner);
+    known
+        .get_or_insert_with(HashMap::new)
+        .insert(path, fingerprint)
+        != Some(fingerprint)
+}
+
+/// 程序写入数据后调用：记录写入的内容并通知所有窗口
+pub fn record_write(data_type: PersistedDataType, updated_at: u64) {
+    remember_current(data_type);
+    notify(&PersistedDataChanged {
+        data_type,
+        updated_at,
+        external: false,
+    });
+}
+
+/// 恢复备份后调用：记录恢复后的内容并通知所有窗口重新读取。
+///
+/// 恢复的数据通常比窗口中已有的版本旧，因此按外部修改通知，不会被窗口当作自己写入的版本跳过。
+pub fn record_restore(data_type: PersistedDataType) {
+    remember_current(data_type);
+    let updated_at = backend::current()
+        .and_then(|backend| backend.read(data_type.file_name()))
+        .ok()
+        .flatten()
+        .and_then(|value| value.get("updatedAt").and_then(Value::as_u64))
+        .unwrap_or_default();
+    notify(&PersistedDataChanged {
+        data_type,
+        updated_at,
+        external: true,
+    });
+}
+
+/// 程序写入配置后调用，之后不会把这次写入当作外部修改
+pub fn record_config_write() {
+    remember_file(CONFIG_FILE);
+}
+
+/// 当前存储后端中保存该文档的文件。
+///
+/// SQLite 后端把所有数据保存在同一个数据库中，没有可供外部编辑的单独文件，因此不检测外部修改。
+fn data_file(name: &str) -> Option<PathBuf> {
+    backend::current().ok()?.file_path(name)
+}
+
+fn remember_file(name: &str) {
+    let Some(path) = data_file(name) else {
+        return;
+    };
+    if let Ok(content) = fs::read(&path) {
+        remember_content(path, &content);
+    }
+}
+
+/// 记录数据文件的当前内容但不通知，开始监视数据目录前调用
+pub fn remember_current(data_type: PersistedDataType) {
+    remember_file(data_type.file_name());
+}
+
+/// 记录配置文件的当前内容但不通知，开始监视数据目录前调用
+pub fn remember_current_config() {
+    remember_file(CONFIG_FILE);
+}
+
+/// 检查配置文件是否被程序之外的修改改变，是则通知配置的接收者并返回 `true`。
+///
+/// 与 `check_external_change` 相同，内容未变或无法解析为 JSON 的文件会被忽略。
+pub fn check_external_config_change() -> bool {
+    let Some(path) = data_file(CONFIG_FILE) else {
+        return false;
+    };
+    let Ok(content) = fs::read(&path) else {
+        return false;
+    };
+    if !remember_content(path, &content) || serde_json::from_slice::<Value>(&content).is_err() {
+        return false;
+    }
+    if let Some(listener) = CONFIG_LISTENER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        listener();
+    }
+    true
+}
+
+/// 检查数据文件是否被程序之外的修改改变，是则通知所有窗口并返回对应事件。
+///
+/// 内容与程序上次写入或上次检查时相同的文件会被忽略，内容无法解析的文件留给读取时的损坏恢复处理。
+pub fn check_external_change(data_type: PersistedDataType) -> Option<PersistedDataChanged> {
+    let path = data_file(data_type.file_name())?;
+    let content = fs::read(&path).ok()?;
+    if !remember_content(path, &content) {
+        return None;
+    }
+    let envelope = serde_json::from_slice::<DataEnvelope<Value>>(&content).ok()?;
+    let change = PersistedDataChanged {
+        data_type,
+        updated_at: envelope.updated_at,
+        external: true,
+    };
+    notify(&change);
+    Some(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_external_change, check_external_config_change};
+    use crate::models::Config;
+    use crate::storage::backend::{self, SqliteBackend};
+    use crate::storage::persisted::{write_persisted, PersistedDataType};
+    use crate::storage::{json_store, paths};
+    use crate::utils::config::save_config;
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 只有程序之外的修改会被识别为外部变化() {
+        let data_dir = unique_temp_dir("events-external");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        write_persisted(PersistedDataType::Clipboard, json!({ "items": [] })).expect("写入应成功");
+        assert_eq!(check_external_change(PersistedDataType::Clipboard), None);
+
+        let edited = r#"{"schemaVersion":1,"updatedAt":42,"data":{"items":[{"id":"clip-1"}]}}"#;
+        fs::write(paths::clipboard_path(), edited).expect("应能修改剪贴板文件");
+        let change = check_external_change(PersistedDataType::Clipboard).expect("应识别外部修改");
+        assert_eq!(change.updated_at, 42);
+        assert!(change.external);
+        assert_eq!(
+            check_external_change(PersistedDataType::Clipboard),
+            None,
+            "同一内容只通知一次"
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 使用_sqlite_存储时不检测数据文件的外部修改() {
+        let data_dir = unique_temp_dir("events-sqlite");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        let _backend = backend::set_test_backend(Arc::new(
+            SqliteBackend::open_in_memory().expect("应能打开数据库"),
+        ));
+
+        // 切换前留下的 JSON 文件不再代表当前数据
+        let edited = r#"{"schemaVersion":1,"updatedAt":42,"data":{"items":[]}}"#;
+        fs::write(paths::clipboard_path(), edited).expect("应能写入剪贴板文件");
+        assert_eq!(check_external_change(PersistedDataType::Clipboard), None);
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 识别程序之外对配置文件的修改() {
+        let data_dir = unique_temp_dir("events-external-config");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        save_config(&Config::default()).expect("保存应成功");
+        assert!(
+            !check_external_config_change(),
+            "程序自己的写入不算外部修改"
+        );
+
+        let mut edited = Config::default();
+        edited.settings.last_category = Some("synced".to_string());
+        fs::write(paths::config_path(), serde_json::to_vec(&edited).unwrap())
+            .expect("应能修改配置文件");
+        assert!(check_external_config_change());
+        assert!(!check_external_config_change(), "同一内容只通知一次");
+
+        fs::write(paths::config_path(), "{\"version\":").expect("应能写入不完整的配置");
+        assert!(!check_external_config_change(), "写到一半的文件不通知");
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
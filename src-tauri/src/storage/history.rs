@@ -0,0 +1,365 @@
+use crate::storage::atomic_write::write_atomic;
+use crate::storage::error::StorageError;
+use crate::storage::json_store::{now_millis, DataEnvelope};
+use crate::storage::paths;
+use crate::storage::persisted::{self, PersistedDataType};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// 保留历史版本的数据文件
+pub const TRACKED_FILES: &[&str] = &[
+    paths::CONFIG_FILE,
+    paths::SCENES_FILE,
+    paths::NOTES_FILE,
+    paths::TODOS_FILE,
+];
+
+/// 每个文件最多保留的历史版本数量
+pub const HISTORY_LIMIT: usize = 20;
+
+const VERSION_EXTENSION: &str = "json.gz";
+
+/// 启动程序、校验路径和每次写入都会刷新的时间字段；只有这些字段变化的写入不保存历史版本，
+/// 避免频繁的时间更新挤掉真正有内容变化的版本
+const TIMESTAMP_FIELDS: &[&str] = &["updatedAt", "lastLaunched", "lastValidatedAt"];
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    pub id: String,
+    /// 这份内容被新内容替换的时间
+    pub replaced_at: u64,
+    /// 压缩后的大小
+    pub size: u64,
+}
+
+pub fn history_dir(file_name: &str) -> PathBuf {
+    paths::data_dir().join("history").join(file_name)
+}
+
+fn ensure_tracked(file_name: &str) -> Result<(), StorageError> {
+    if TRACKED_FILES.contains(&file_name) {
+        Ok(())
+    } else {
+        Err(StorageError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} 没有历史版本", file_name),
+        )))
+    }
+}
+
+/// 受追踪的文档即将被 `next` 替换时，把旧内容压缩保存为一个历史版本。
+///
+/// 与存储后端无关，由写入数据的一方在写入前调用；内容没有变化或只有 `TIMESTAMP_FIELDS` 变化时不保存，
+/// 超出 `HISTORY_LIMIT` 的最旧版本会被删除。
+/// 历史版本只是附带的保护，保存失败时记录日志而不影响写入本身。
+pub fn record_previous_version(name: &str, previous: Option<&Value>, next: &Value) {
+    let Some(previous) = previous.filter(|previous| !same_except_timestamps(previous, next)) else {
+        return;
+    };
+    if !TRACKED_FILES.contains(&name) {
+        return;
+    }
+    if let Err(error) = save_version(name, previous) {
+        eprintln!("保存 {} 的历史版本失败: {}", name, error);
+    }
+}
+
+fn same_except_timestamps(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            content_fields(left).count() == content_fields(right).count()
+                && content_fields(left).all(|(key, value)| {
+                    right
+                        .get(key)
+                        .is_some_and(|other| same_except_timestamps(value, other))
+                })
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| same_except_timestamps(left, right))
+        }
+        _ => left == right,
+    }
+}
+
+fn content_fields(map: &Map<String, Value>) -> impl Iterator<Item = (&String, &Value)> {
+    map.iter()
+        .filter(|(key, _)| !TIMESTAMP_FIELDS.contains(&key.as_str()))
+}
+
+fn save_version(file_name: &str, previous: &Value) -> Result<(), StorageError> {
+    let dir = history_dir(file_name);
+    fs::create_dir_all(&dir)?;
+    let mut replaced_at = now_millis();
+    while version_path(&dir, &replaced_at.to_string()).exists() {
+        replaced_at += 1;
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serde_json::to_vec_pretty(previous)?)?;
+    write_atomic(
+        &version_path(&dir, &replaced_at.to_string()),
+        &encoder.finish()?,
+    )?;
+    prune(file_name, HISTORY_LIMIT)
+}
+
+fn version_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.{}", id, VERSION_EXTENSION))
+}
+
+/// 列出文件的历史版本，最新的在前
+pub fn list_versions(file_name: &str) -> Result<Vec<FileVersion>, StorageError> {
+    ensure_tracked(file_name)?;
+    let Ok(entries) = fs::read_dir(history_dir(file_name)) else {
+        return Ok(Vec::new());
+    };
+
+    let suffix = format!(".{}", VERSION_EXTENSION);
+    let mut versions = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(replaced_at) = name
+            .strip_suffix(&suffix)
+            .and_then(|id| id.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        versions.push(FileVersion {
+            id: replaced_at.to_string(),
+            replaced_at,
+            size: entry.metadata()?.len(),
+        });
+    }
+    versions.sort_by_key(|version| std::cmp::Reverse(version.replaced_at));
+    Ok(versions)
+}
+
+/// 读取并解压一个历史版本
+pub fn read_version(file_name: &str, id: &str) -> Result<Vec<u8>, StorageError> {
+    ensure_tracked(file_name)?;
+    if id.is_empty() || !id.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(StorageError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("历史版本 id 无效: {}", id),
+        )));
+    }
+
+    let path = version_path(&history_dir(file_name), id);
+    if !path.is_file() {
+        return Err(StorageError::NotFound(format!(
+            "{} 的历史版本 {}",
+            file_name, id
+        )));
+    }
+    let compressed = fs::read(path)?;
+    let mut content = Vec::new();
+    GzDecoder::new(compressed.as_slice()).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// 把便签、待办或场景恢复到指定的历史版本，旧结构版本会先升级到当前结构。
+///
+/// 恢复本身也是一次写入：恢复前的内容会成为新的历史版本，`updatedAt` 取新值以便其他窗口重新读取。
+pub fn restore_persisted_version(
+    data_type: PersistedDataType,
+    id: &str,
+) -> Result<DataEnvelope<Value>, StorageError> {
+    let content = read_version(data_type.file_name(), id)?;
+    let stored: DataEnvelope<Value> = serde_json::from_slice(&content)?;
+    let upgrade = data_type
+        .schema()
+        .upgrade(stored.schema_version, stored.data)?;
+    persisted::write_persisted(data_type, upgrade.value)
+}
+
+fn prune(file_name: &str, limit: usize) -> Result<(), StorageError> {
+    let dir = history_dir(file_name);
+    for version in list_versions(file_name)?.into_iter().skip(limit) {
+        fs::remove_file(version_path(&dir, &version.id))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        list_versions, read_version, record_previous_version, restore_persisted_version,
+        HISTORY_LIMIT,
+    };
+    use crate::models::Config;
+    use crate::storage::backend::{self, SqliteBackend};
+    use crate::storage::persisted::{read_persisted, write_persisted, PersistedDataType};
+    use crate::storage::{json_store, paths};
+    use crate::utils::config::save_config;
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 写入时保存旧内容并只保留最近的版本() {
+        let data_dir = unique_temp_dir("history-record");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let first = json!({ "round": 0 });
+        record_previous_version(paths::NOTES_FILE, Some(&first), &first);
+        assert!(
+            list_versions(paths::NOTES_FILE)
+                .expect("应能列出版本")
+                .is_empty(),
+            "内容相同的写入不产生版本"
+        );
+
+        for round in 1..=HISTORY_LIMIT + 2 {
+            record_previous_version(
+                paths::NOTES_FILE,
+                Some(&json!({ "round": round - 1 })),
+                &json!({ "round": round }),
+            );
+        }
+
+        let versions = list_versions(paths::NOTES_FILE).expect("应能列出版本");
+        assert_eq!(versions.len(), HISTORY_LIMIT);
+        assert!(versions[0].replaced_at > versions[1].replaced_at);
+        let latest = read_version(paths::NOTES_FILE, &versions[0].id).expect("应能读取版本");
+        let latest: serde_json::Value = serde_json::from_slice(&latest).unwrap();
+        assert_eq!(latest["round"], HISTORY_LIMIT + 1);
+        let oldest =
+            read_version(paths::NOTES_FILE, &versions[HISTORY_LIMIT - 1].id).expect("应能读取版本");
+        let oldest: serde_json::Value = serde_json::from_slice(&oldest).unwrap();
+        assert_eq!(oldest["round"], 2, "最旧的版本已被清理");
+
+        assert!(read_version(paths::NOTES_FILE, "../config").is_err());
+        assert!(list_versions(paths::CLIPBOARD_FILE).is_err());
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 只刷新时间字段的写入不占用历史版本() {
+        let data_dir = unique_temp_dir("history-timestamps");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let mut config: Config = serde_json::from_value(json!({
+            "apps": {
+                "a": { "id": "a", "name": "A", "path": "/bin/a", "category": "tools", "createdAt": 1 }
+            }
+        }))
+        .unwrap();
+        save_config(&config).expect("应能保存配置");
+        for launched in 1..=HISTORY_LIMIT as u64 + 5 {
+            let app = config.apps.get_mut("a").unwrap();
+            app.last_launched = Some(launched);
+            app.last_validated_at = Some(launched);
+            save_config(&config).expect("应能保存配置");
+        }
+        assert!(
+            list_versions(paths::CONFIG_FILE).unwrap().is_empty(),
+            "只更新启动时间不产生版本"
+        );
+
+        config.apps.get_mut("a").unwrap().name = "B".to_string();
+        save_config(&config).expect("应能保存配置");
+        let versions = list_versions(paths::CONFIG_FILE).unwrap();
+        assert_eq!(versions.len(), 1);
+        let previous: serde_json::Value =
+            serde_json::from_slice(&read_version(paths::CONFIG_FILE, &versions[0].id).unwrap())
+                .unwrap();
+        assert_eq!(previous["apps"]["a"]["name"], "A");
+        assert_eq!(
+            previous["apps"]["a"]["lastLaunched"],
+            HISTORY_LIMIT as u64 + 5,
+            "版本中保留最近一次的时间"
+        );
+
+        // 数据外壳的 updatedAt 每次写入都会变化，内容不变时同样不产生版本
+        let notes = json!({ "notes": [] });
+        write_persisted(PersistedDataType::Notes, notes.clone()).unwrap();
+        write_persisted(PersistedDataType::Notes, notes).unwrap();
+        assert!(list_versions(paths::NOTES_FILE).unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 使用_sqlite_存储时同样保留历史版本() {
+        let data_dir = unique_temp_dir("history-sqlite");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        let _backend = backend::set_test_backend(Arc::new(
+            SqliteBackend::open_in_memory().expect("应能打开数据库"),
+        ));
+
+        write_persisted(PersistedDataType::Notes, json!({ "notes": [] })).expect("写入应成功");
+        write_persisted(
+            PersistedDataType::Notes,
+            json!({ "notes": [{ "id": "note-1", "content": "便签", "createdAt": 1, "updatedAt": 1 }] }),
+        )
+        .expect("写入应成功");
+
+        let versions = list_versions(paths::NOTES_FILE).expect("应能列出版本");
+        assert_eq!(versions.len(), 1);
+        let previous: serde_json::Value =
+            serde_json::from_slice(&read_version(paths::NOTES_FILE, &versions[0].id).unwrap())
+                .unwrap();
+        assert_eq!(previous["data"], json!({ "notes": [] }));
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 恢复历史版本后旧内容成为当前数据() {
+        let data_dir = unique_temp_dir("history-restore");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let todos = json!({ "items": [{ "id": "a", "title": "待办", "date": "2026-05-30", "createdAt": 1, "updatedAt": 1 }] });
+        write_persisted(PersistedDataType::Todos, todos.clone()).expect("写入应成功");
+        let replaced =
+            write_persisted(PersistedDataType::Todos, json!({ "items": [] })).expect("写入应成功");
+        let versions = list_versions(paths::TODOS_FILE).expect("应能列出版本");
+        assert_eq!(versions.len(), 1);
+
+        let restored = restore_persisted_version(PersistedDataType::Todos, &versions[0].id)
+            .expect("恢复应成功");
+        assert_eq!(restored.data, todos);
+        assert!(restored.updated_at > replaced.updated_at);
+        assert_eq!(
+            read_persisted(PersistedDataType::Todos)
+                .unwrap()
+                .unwrap()
+                .data,
+            restored.data
+        );
+        assert_eq!(
+            list_versions(paths::TODOS_FILE).unwrap().len(),
+            2,
+            "恢复前的内容也保留为历史版本"
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
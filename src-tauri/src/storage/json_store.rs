@@ -32,8 +32,7 @@ pub fn write_json<T>(path: &Path, value: &T) -> Result<(), StorageError>
 where
     T: Serialize,
 {
-    let content = serde_json::to_vec_pretty(value)?;
-    write_atomic(path, &content)
+    write_atomic(path, &serde_json::to_vec_pretty(value)?)
 }
 
 pub fn write_enveloped_json<T>(
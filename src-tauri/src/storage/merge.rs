@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// 导入数据与本地数据 id 相同但内容不同时的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MergePolicy {
+    /// 按 `updatedAt`（缺失时取 `createdAt`）保留较新的一方，时间相同时保留本地数据
+    #[default]
+    Newer,
+    KeepMine,
+    KeepTheirs,
+    /// 两份都保留，导入的一份换用新 id
+    KeepBoth,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeCounts {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    /// 只在按条目选择导入时出现，合并导入不会删除本地数据
+    pub removed: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct MergeOutcome {
+    pub counts: MergeCounts,
+    /// 以新 id 保留的导入条目：原 id -> 新 id
+    pub renamed: HashMap<String, String>,
+    /// 合并后内容来自导入数据的条目 id，包括新增、覆盖和换 id 保留的条目
+    pub taken: Vec<String>,
+}
+
+pub fn item_id(item: &Value) -> Option<&str> {
+    item.get("id").and_then(Value::as_str)
+}
+
+fn item_timestamp(item: &Value) -> u64 {
+    item.get("updatedAt")
+        .or_else(|| item.get("createdAt"))
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+/// 按 `id` 把导入条目合并进本地列表，本地独有的条目保持不变。
+///
+/// 内容完全相同或缺少 `id` 的导入条目计为跳过。
+pub fn merge_items(mine: &mut Vec<Value>, theirs: Vec<Value>, policy: MergePolicy) -> MergeOutcome {
+    let mut outcome = MergeOutcome::default();
+    for item in theirs {
+        let Some(id) = item_id(&item).map(str::to_string) else {
+            outcome.counts.skipped += 1;
+            continue;
+        };
+        let Some(index) = mine
+            .iter()
+            .position(|current| item_id(current) == Some(id.as_str()))
+        else {
+            mine.push(item);
+            outcome.counts.added += 1;
+            outcome.taken.push(id);
+            continue;
+        };
+        if mine[index] == item {
+            outcome.counts.skipped += 1;
+            continue;
+        }
+
+        let take_theirs = match policy {
+            MergePolicy::Newer => item_timestamp(&item) > item_timestamp(&mine[index]),
+            MergePolicy::KeepMine => false,
+            MergePolicy::KeepTheirs => true,
+            MergePolicy::KeepBoth => {
+                let new_id = uuid::Uuid::new_v4().to_string();
+                let mut copy = item;
+                copy["id"] = Value::String(new_id.clone());
+                mine.push(copy);
+                outcome.counts.added += 1;
+                outcome.renamed.insert(id, new_id.clone());
+                outcome.taken.push(new_id);
+                continue;
+            }
+        };
+        if take_theirs {
+            mine[index] = item;
+            outcome.counts.updated += 1;
+            outcome.taken.push(id);
+        } else {
+            outcome.counts.skipped += 1;
+        }
+    }
+    outcome
+}
+
+/// 与 `merge_items` 相同，但条目以 `id -> 条目` 的对象形式保存，例如配置中的 apps
+pub fn merge_item_map(
+    mine: &mut Map<String, Value>,
+    theirs: Map<String, Value>,
+    policy: MergePolicy,
+) -> MergeOutcome {
+    let mut items = map_to_items(std::mem::take(mine));
+    let outcome = merge_items(&mut items, map_to_items(theirs), policy);
+    for item in items {
+        if let Some(id) = item_id(&item).map(str::to_string) {
+            mine.insert(id, item);
+        }
+    }
+    outcome
+}
+
+/// 把 `id -> 条目` 对象转为条目列表，缺少 `id` 字段的条目以键名补上
+pub fn map_to_items(map: Map<String, Value>) -> Vec<Value> {
+    map.into_iter()
+        .map(|(key, mut item)| {
+            if item_id(&item).is_none() {
+                if let Some(object) = item.as_object_mut() {
+                    object.insert("id".to_string(), Value::String(key));
+                }
+            }
+            item
+        })
+        .collect()
+}
+
+/// 合并配置中的分类和程序，返回 `(分类结果, 程序结果)`，本地设置保持不变。
+///
+/// 换 id 保留的分类会同步更新导入程序的 `category` 和 `extraCategories`，
+/// 合并后按程序的 `category` 和 `extraCategories` 重新整理各分类的 `apps` 列表。
+pub fn merge_config(
+    mine: &mut Value,
+    theirs: &Value,
+    policy: MergePolicy,
+) -> (MergeOutcome, MergeOutcome) {
+    let categories = merge_item_map(
+        object_field(mine, "categories"),
+        theirs
+            .get("categories")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default(),
+        policy,
+    );
+    let apps = merge_item_map(
+        object_field(mine, "apps"),
+        theirs
+            .get("apps")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default(),
+        policy,
+    );
+
+    let apps_map = object_field(mine, "apps");
+    for id in &apps.taken {
+        let Some(app) = apps_map.get_mut(id) else {
+            continue;
+        };
+        let renamed = app
+            .get("category")
+            .and_then(Value::as_str)
+            .and_then(|category| categories.renamed.get(category))
+            .cloned();
+        if let Some(category) = renamed {
+            app["category"] = Value::String(category);
+        }
+        if let Some(extra) = app.get_mut("extraCategories").and_then(Value::as_array_mut) {
+            for category in extra.iter_mut() {
+                let renamed = category
+                    .as_str()
+                    .and_then(|category| categories.renamed.get(category));
+                if let Some(renamed) = renamed {
+                    *category = Value::String(renamed.clone());
+                }
+            }
+        }
+    }
+
+    rebuild_category_members(mine);
+    (categories, apps)
+}
+
+fn object_field<'a>(value: &'a mut Value, key: &str) -> &'a mut Map<String, Value> {
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    let object = value.as_object_mut().expect("已确保为对象");
+    let field = object
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !field.is_object() {
+        *field = Value::Object(Map::new());
+    }
+    field.as_object_mut().expect("已确保为对象")
+}
+
+/// 让每个分类的 `apps` 只包含 `category` 或 `extraCategories` 指向它的程序，保留原有顺序并追加缺失的程序
+pub fn rebuild_category_members(config: &mut Value) {
+    let mut members: HashMap<String, Vec<String>> = HashMap::new();
+    for (id, app) in object_field(config, "apps").iter() {
+        let extra = app
+            .get("extraCategories")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for category in app.get("category").into_iter().chain(extra) {
+            if let Some(category) = category.as_str() {
+                members
+                    .entry(category.to_string())
+                    .or_default()
+                    .push(id.clone());
+            }
+        }
+    }
+
+    for (id, category) in object_field(config, "categories").iter_mut() {
+        let mut expected = members.remove(id).unwrap_or_default();
+        expected.sort();
+        let expected_set: HashSet<&String> = expected.iter().collect();
+        let mut ordered: Vec<String> = category
+            .get("apps")
+            .and_then(Value::as_array)
+            .map(|apps| {
+                apps.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .filter(|app| expected_set.contains(app))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut seen: HashSet<String> = HashSet::new();
+        ordered.retain(|app| seen.insert(app.clone()));
+        for app in &expected {
+            if !seen.contains(app) {
+                ordered.push(app.clone());
+            }
+        }
+        category["apps"] = Value::from(ordered);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_config, merge_items, MergeCounts, MergePolicy};
+    use serde_json::{json, Value};
+
+    fn notes() -> (Vec<Value>, Vec<Value>) {
+        let mine = vec![
+            json!({ "id": "mine-only", "content": "本地", "updatedAt": 1 }),
+            json!({ "id": "shared", "content": "本地修改", "updatedAt": 5 }),
+            json!({ "id": "same", "content": "相同", "updatedAt": 1 }),
+        ];
+        let theirs = vec![
+            json!({ "id": "shared", "content": "导入修改", "updatedAt": 9 }),
+            json!({ "id": "same", "content": "相同", "updatedAt": 1 }),
+            json!({ "id": "theirs-only", "content": "导入", "updatedAt": 1 }),
+        ];
+        (mine, theirs)
+    }
+
+    #[test]
+    fn 默认按更新时间保留较新的条目() {
+        let (mut mine, theirs) = notes();
+
+        let outcome = merge_items(&mut mine, theirs, MergePolicy::Newer);
+
+        assert_eq!(
+            outcome.counts,
+            MergeCounts {
+                added: 1,
+                updated: 1,
+                skipped: 1,
+                removed: 0
+            }
+        );
+        assert_eq!(mine.len(), 4);
+        assert_eq!(mine[1]["content"], "导入修改");
+        assert_eq!(mine[0]["id"], "mine-only");
+    }
+
+    #[test]
+    fn 保留双方时导入条目换用新_id() {
+        let (mut mine, theirs) = notes();
+
+        let outcome = merge_items(&mut mine, theirs, MergePolicy::KeepBoth);
+
+        assert_eq!(outcome.counts.added, 2);
+        assert_eq!(outcome.counts.skipped, 1);
+        assert_eq!(mine[1]["content"], "本地修改");
+        let new_id = outcome.renamed.get("shared").expect("应记录新 id");
+        assert!(mine
+            .iter()
+            .any(|item| item["id"] == new_id.as_str() && item["content"] == "导入修改"));
+    }
+
+    #[test]
+    fn 合并配置会同步分类中的程序列表() {
+        let mut mine = json!({
+            "version": "1.0",
+            "settings": { "theme": "dark" },
+            "categories": { "cat-1": { "id": "cat-1", "name": "工具", "apps": ["app-1"], "order": 0 } },
+            "apps": { "app-1": { "id": "app-1", "name": "编辑器", "path": "C:\\a.exe", "category": "cat-1", "createdAt": 1 } }
+        });
+        let theirs = json!({
+            "categories": {
+                "cat-1": { "id": "cat-1", "name": "开发", "apps": ["app-2"], "order": 0 },
+                "cat-2": { "id": "cat-2", "name": "终端", "apps": ["app-2"], "order": 1 }
+            },
+            "apps": {
+                "app-1": { "id": "app-1", "name": "新编辑器", "path": "D:\\a.exe", "category": "cat-1", "createdAt": 2 },
+                "app-2": { "id": "app-2", "name": "终端", "path": "D:\\t.exe", "category": "cat-2", "extraCategories": ["cat-1"], "createdAt": 1 }
+            }
+        });
+
+        let (categories, apps) = merge_config(&mut mine, &theirs, MergePolicy::KeepBoth);
+
+        assert_eq!(categories.counts.added, 2);
+        assert_eq!(apps.counts.added, 2);
+        assert_eq!(mine["settings"]["theme"], "dark");
+        let new_category = categories.renamed.get("cat-1").expect("分类应换用新 id");
+        let moved = mine["apps"]
+            .as_object()
+            .unwrap()
+            .values()
+            .filter(|app| app["category"] == new_category.as_str())
+            .count();
+        assert_eq!(moved, 1, "导入的程序应归入换 id 后的分类");
+        assert_eq!(
+            mine["apps"]["app-2"]["extraCategories"],
+            json!([new_category]),
+            "关联的分类同样换用新 id"
+        );
+        assert_eq!(mine["categories"]["cat-2"]["apps"], json!(["app-2"]));
+        assert_eq!(mine["categories"]["cat-1"]["apps"], json!(["app-1"]));
+        assert_eq!(
+            mine["categories"][new_category.as_str()]["apps"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
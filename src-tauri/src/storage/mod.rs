@@ -1,6 +1,19 @@
 pub mod atomic_write;
+pub mod backend;
 pub mod backup;
+pub mod diff;
+pub mod encryption;
 pub mod error;
+pub mod events;
+pub mod history;
 pub mod json_store;
+pub mod merge;
 pub mod migration;
+pub mod package;
 pub mod paths;
+pub mod persisted;
+pub mod recovery;
+pub mod relocation;
+pub mod retention;
+pub mod schema;
+pub mod usage;
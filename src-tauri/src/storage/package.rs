@@ -0,0 +1,293 @@
+use crate::storage::atomic_write::temp_path_for;
+use crate::storage::error::StorageError;
+use crate::storage::json_store::now_millis;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const PACKAGE_EXTENSION: &str = "pmpkg";
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 解压数据包时的上限，防止构造的压缩包在解压时耗尽磁盘
+#[derive(Debug, Clone, Copy)]
+struct ExtractLimits {
+    max_entries: usize,
+    max_total_bytes: u64,
+    max_ratio: u64,
+    /// 小于该大小的条目不检查压缩比，小文件压缩比再高也占不了多少空间
+    ratio_min_bytes: u64,
+}
+
+const EXTRACT_LIMITS: ExtractLimits = ExtractLimits {
+    max_entries: 10_000,
+    max_total_bytes: 1024 * 1024 * 1024,
+    max_ratio: 100,
+    ratio_min_bytes: 1024 * 1024,
+};
+
+/// 导出或导入时使用的临时目录，离开作用域时自动删除
+pub struct StagingDir {
+    path: PathBuf,
+}
+
+impl StagingDir {
+    pub fn create(purpose: &str) -> Result<Self, StorageError> {
+        let path = std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}-{}",
+            purpose,
+            std::process::id(),
+            now_millis(),
+            STAGING_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path)?;
+        Ok(StagingDir { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+pub fn is_package_path(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some(PACKAGE_EXTENSION)
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, StorageError> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// 校验数据包清单中的相对路径，拒绝绝对路径和 `..` 等越出数据包目录的写法
+pub fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(path);
+    let is_safe = !path.is_empty()
+        && relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    is_safe.then_some(relative)
+}
+
+/// 列出目录下所有文件，返回以 `/` 分隔的相对路径并按名称排序
+pub fn list_files(dir: &Path) -> Result<Vec<String>, StorageError> {
+    let mut files = Vec::new();
+    collect_files(dir, "", &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> Result<(), StorageError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, &format!("{}/", name), files)?;
+        } else if path.is_file() {
+            files.push(name);
+        }
+    }
+    Ok(())
+}
+
+/// 把目录打包为单个数据包文件。
+///
+/// 先写入同目录的临时文件并落盘，完成后再重命名，导出中断时不会留下半个数据包。
+pub fn write_package(source_dir: &Path, target: &Path) -> Result<(), StorageError> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = temp_path_for(target);
+    let result = write_archive(source_dir, &tmp_path).and_then(|_| {
+        fs::rename(&tmp_path, target)?;
+        Ok(())
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+fn write_archive(source_dir: &Path, target: &Path) -> Result<(), StorageError> {
+    let mut writer = ZipWriter::new(File::create(target)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for name in list_files(source_dir)? {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(package_error)?;
+        io::copy(&mut File::open(source_dir.join(&name))?, &mut writer)?;
+    }
+    writer.finish().map_err(package_error)?.sync_all()?;
+    Ok(())
+}
+
+/// 把数据包解压到目标目录。
+///
+/// 条目路径越出目标目录时拒绝解压；数据包被截断时无法读取目录区，
+/// 条目内容损坏时 CRC 校验失败，两种情况都会返回错误。
+/// 条目数、解压后的总大小或单个条目的压缩比超出上限时返回 `StorageError::InvalidData`。
+pub fn extract_package(archive: &Path, target_dir: &Path) -> Result<(), StorageError> {
+    extract_with_limits(archive, target_dir, &EXTRACT_LIMITS)
+}
+
+fn extract_with_limits(
+    archive_path: &Path,
+    target_dir: &Path,
+    limits: &ExtractLimits,
+) -> Result<(), StorageError> {
+    let package_name = archive_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "数据包".to_string());
+    let too_large = |field: &str, reason: String| StorageError::InvalidData {
+        name: package_name.clone(),
+        field: field.to_string(),
+        reason,
+    };
+
+    let mut archive = ZipArchive::new(File::open(archive_path)?).map_err(package_error)?;
+    if archive.len() > limits.max_entries {
+        return Err(too_large(
+            "条目数",
+            format!("共 {} 个，超过上限 {}", archive.len(), limits.max_entries),
+        ));
+    }
+    let mut total_bytes = 0u64;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(package_error)?;
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| StorageError::Package(format!("包含非法路径 {}", entry.name())))?;
+        let target = target_dir.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        // 写入前按目录区记录的大小检查，写入时再按实际读出的字节数检查，防止目录区的大小被篡改
+        let size = entry.size();
+        if size >= limits.ratio_min_bytes
+            && size / entry.compressed_size().max(1) > limits.max_ratio
+        {
+            return Err(too_large(
+                entry.name(),
+                format!("压缩比超过上限 {}", limits.max_ratio),
+            ));
+        }
+        let remaining = limits.max_total_bytes - total_bytes;
+        if size > remaining {
+            return Err(too_large(
+                entry.name(),
+                format!("解压后的总大小超过上限 {} 字节", limits.max_total_bytes),
+            ));
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let name = entry.name().to_string();
+        let written = io::copy(
+            &mut (&mut entry).take(size + 1),
+            &mut File::create(&target)?,
+        )
+        .map_err(|error| StorageError::Package(format!("{} 读取失败: {}", name, error)))?;
+        if written > size {
+            return Err(too_large(&name, "解压后的大小与记录不符".to_string()));
+        }
+        total_bytes += written;
+    }
+    Ok(())
+}
+
+fn package_error(error: zip::result::ZipError) -> StorageError {
+    StorageError::Package(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        extract_package, extract_with_limits, safe_relative_path, sha256_file, write_package,
+        ExtractLimits, StagingDir, EXTRACT_LIMITS,
+    };
+    use crate::storage::error::StorageError;
+    use std::fs;
+
+    #[test]
+    fn 打包后解压可还原目录内容() {
+        let source = StagingDir::create("package-source").expect("应能创建源目录");
+        let target = StagingDir::create("package-target").expect("应能创建目标目录");
+        fs::create_dir_all(source.path().join("icons")).expect("应能创建图标目录");
+        fs::write(source.path().join("notes.json"), r#"{"notes":[]}"#).expect("应能写入便签");
+        fs::write(source.path().join("icons").join("app-1.png"), b"icon").expect("应能写入图标");
+        let archive = target.path().join("export.pmpkg");
+
+        write_package(source.path(), &archive).expect("打包应成功");
+        let extracted = target.path().join("extracted");
+        extract_package(&archive, &extracted).expect("解压应成功");
+
+        assert_eq!(
+            sha256_file(&extracted.join("notes.json")).unwrap(),
+            sha256_file(&source.path().join("notes.json")).unwrap()
+        );
+        assert!(extracted.join("icons").join("app-1.png").is_file());
+    }
+
+    #[test]
+    fn 清单路径不能越出数据包目录() {
+        assert!(safe_relative_path("icons/app-1.png").is_some());
+        assert!(safe_relative_path("../config.json").is_none());
+        assert!(safe_relative_path("/etc/passwd").is_none());
+        assert!(safe_relative_path("").is_none());
+    }
+
+    #[test]
+    fn 超出条目数大小或压缩比上限的数据包被拒绝() {
+        let source = StagingDir::create("package-bomb-source").expect("应能创建源目录");
+        let target = StagingDir::create("package-bomb-target").expect("应能创建目标目录");
+        fs::write(source.path().join("notes.json"), r#"{"notes":[]}"#).expect("应能写入便签");
+        fs::write(source.path().join("zeros.bin"), vec![0u8; 2 * 1024 * 1024])
+            .expect("应能写入大文件");
+        let archive = target.path().join("bomb.pmpkg");
+        write_package(source.path(), &archive).expect("打包应成功");
+
+        let ratio = extract_package(&archive, &target.path().join("ratio"));
+        assert!(matches!(
+            ratio,
+            Err(StorageError::InvalidData { ref field, .. }) if field == "zeros.bin"
+        ));
+
+        let few_entries = ExtractLimits {
+            max_entries: 1,
+            ..EXTRACT_LIMITS
+        };
+        let entries = extract_with_limits(&archive, &target.path().join("entries"), &few_entries);
+        assert!(matches!(entries, Err(StorageError::InvalidData { .. })));
+
+        let small_total = ExtractLimits {
+            max_total_bytes: 1024,
+            max_ratio: u64::MAX,
+            ..EXTRACT_LIMITS
+        };
+        let total = extract_with_limits(&archive, &target.path().join("total"), &small_total);
+        assert!(matches!(
+            total,
+            Err(StorageError::InvalidData { ref field, .. }) if field == "zeros.bin"
+        ));
+    }
+}
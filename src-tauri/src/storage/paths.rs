@@ -1,6 +1,10 @@
+use crate::storage::atomic_write::write_atomic;
+use crate::storage::error::StorageError;
+use serde::{Deserialize, Serialize};
 use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{PoisonError, RwLock};
 
 #[cfg(test)]
 use std::cell::RefCell;
@@ -12,8 +16,39 @@ pub const TODOS_FILE: &str = "todos.json";
 pub const CLIPBOARD_FILE: &str = "clipboard.json";
 pub const ACTIONS_FILE: &str = "actions.json";
 pub const MIGRATIONS_FILE: &str = "migrations.json";
+/// 记录当前使用的存储后端
+pub const STORAGE_FILE: &str = "storage.json";
+/// SQLite 存储后端的数据库文件
+pub const DATABASE_FILE: &str = "data.db";
+/// 程序目录中存在该文件时使用便携模式，数据保存在程序目录的 `data` 文件夹中
+pub const PORTABLE_MARKER_FILE: &str = "portable.txt";
+const PORTABLE_DATA_DIR: &str = "data";
+/// 数据目录被移动后，在默认数据目录中记录新的位置
+pub const LOCATION_FILE: &str = "data-location.json";
 const DATA_DIR_ENV: &str = "PROGRAM_MANAGER_DATA_DIR";
 
+/// 数据目录的来源，优先级从高到低
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataDirSource {
+    /// `PROGRAM_MANAGER_DATA_DIR` 环境变量
+    Env,
+    /// 程序目录中的便携模式标记文件
+    Portable,
+    /// 用户移动过数据目录
+    Relocated,
+    Default,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataLocation {
+    data_dir: PathBuf,
+}
+
+/// 启动后第一次解析的数据目录，移动数据目录后更新
+static RESOLVED_DATA_DIR: RwLock<Option<(PathBuf, DataDirSource)>> = RwLock::new(None);
+
 #[cfg(test)]
 thread_local! {
     static TEST_DATA_DIR: RefCell<Option<PathBuf>> = RefCell::new(None);
@@ -46,16 +81,94 @@ pub fn data_dir() -> PathBuf {
         return dir;
     }
 
+    let (dir, _) = data_dir_location();
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// 当前数据目录及其来源
+pub fn data_dir_location() -> (PathBuf, DataDirSource) {
+    if let Some(resolved) = RESOLVED_DATA_DIR
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+    {
+        return resolved;
+    }
+
+    let resolved = resolve_data_dir();
+    *RESOLVED_DATA_DIR
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(resolved.clone());
+    resolved
+}
+
+fn resolve_data_dir() -> (PathBuf, DataDirSource) {
     if let Some(dir) = env_data_dir() {
-        let _ = fs::create_dir_all(&dir);
-        return dir;
+        return (dir, DataDirSource::Env);
+    }
+    if let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().and_then(portable_data_dir))
+    {
+        return (dir, DataDirSource::Portable);
     }
 
-    let dir = dirs::config_dir()
+    let default_dir = default_data_dir();
+    match read_location(&default_dir) {
+        Some(dir) => (dir, DataDirSource::Relocated),
+        None => (default_dir, DataDirSource::Default),
+    }
+}
+
+/// 未设置环境变量、不是便携模式且没有移动过时使用的数据目录
+pub fn default_data_dir() -> PathBuf {
+    dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
-        .join("program-manager");
-    let _ = fs::create_dir_all(&dir);
-    dir
+        .join("program-manager")
+}
+
+fn portable_data_dir(exe_dir: &Path) -> Option<PathBuf> {
+    exe_dir
+        .join(PORTABLE_MARKER_FILE)
+        .is_file()
+        .then(|| exe_dir.join(PORTABLE_DATA_DIR))
+}
+
+fn read_location(default_dir: &Path) -> Option<PathBuf> {
+    let content = fs::read(default_dir.join(LOCATION_FILE)).ok()?;
+    let location: DataLocation = serde_json::from_slice(&content).ok()?;
+    Some(location.data_dir).filter(|dir| dir.is_absolute())
+}
+
+/// 记录移动后的数据目录，之后的读写和下次启动都使用新位置；移回默认目录时删除记录
+pub fn set_relocated_data_dir(dir: &Path) -> Result<(), StorageError> {
+    write_location(&default_data_dir(), dir)?;
+    let source = if dir == default_data_dir() {
+        DataDirSource::Default
+    } else {
+        DataDirSource::Relocated
+    };
+    *RESOLVED_DATA_DIR
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some((dir.to_path_buf(), source));
+    Ok(())
+}
+
+fn write_location(default_dir: &Path, dir: &Path) -> Result<(), StorageError> {
+    let path = default_dir.join(LOCATION_FILE);
+    if dir == default_dir {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+
+    fs::create_dir_all(default_dir)?;
+    let location = DataLocation {
+        data_dir: dir.to_path_buf(),
+    };
+    write_atomic(&path, &serde_json::to_vec_pretty(&location)?)
 }
 
 fn env_data_dir() -> Option<PathBuf> {
@@ -112,6 +225,12 @@ pub fn backups_dir() -> PathBuf {
     dir
 }
 
+pub fn quarantine_dir() -> PathBuf {
+    let dir = data_dir().join("quarantine");
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,4 +250,35 @@ mod tests {
             Some(expected)
         );
     }
+
+    #[test]
+    fn 程序目录有标记文件时使用便携数据目录() {
+        let exe_dir =
+            std::env::temp_dir().join(format!("program-manager-portable-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&exe_dir);
+        fs::create_dir_all(&exe_dir).expect("应能创建程序目录");
+        assert_eq!(portable_data_dir(&exe_dir), None);
+
+        fs::write(exe_dir.join(PORTABLE_MARKER_FILE), "").expect("应能写入标记文件");
+        assert_eq!(portable_data_dir(&exe_dir), Some(exe_dir.join("data")));
+
+        let _ = fs::remove_dir_all(exe_dir);
+    }
+
+    #[test]
+    fn 记录移动后的数据目录且移回默认目录时删除记录() {
+        let default_dir =
+            std::env::temp_dir().join(format!("program-manager-location-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&default_dir);
+        let moved = std::env::temp_dir().join("program-manager-moved");
+
+        write_location(&default_dir, &moved).expect("应能记录新位置");
+        assert_eq!(read_location(&default_dir), Some(moved));
+
+        write_location(&default_dir, &default_dir).expect("应能删除记录");
+        assert_eq!(read_location(&default_dir), None);
+        assert!(!default_dir.join(LOCATION_FILE).exists());
+
+        let _ = fs::remove_dir_all(default_dir);
+    }
 }
@@ -0,0 +1,341 @@
+use crate::models::data;
+use crate::storage::backend;
+use crate::storage::error::StorageError;
+use crate::storage::events;
+use crate::storage::history;
+use crate::storage::json_store::{self, DataEnvelope};
+use crate::storage::paths;
+use crate::storage::recovery;
+use crate::storage::schema::{self, SchemaRegistry};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PersistedDataType {
+    Scenes,
+    Notes,
+    Todos,
+    Clipboard,
+    Actions,
+}
+
+impl PersistedDataType {
+    pub const ALL: [PersistedDataType; 5] = [
+        PersistedDataType::Scenes,
+        PersistedDataType::Notes,
+        PersistedDataType::Todos,
+        PersistedDataType::Clipboard,
+        PersistedDataType::Actions,
+    ];
+
+    pub fn path(self) -> PathBuf {
+        paths::data_file(self.file_name())
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            PersistedDataType::Scenes => paths::SCENES_FILE,
+            PersistedDataType::Notes => paths::NOTES_FILE,
+            PersistedDataType::Todos => paths::TODOS_FILE,
+            PersistedDataType::Clipboard => paths::CLIPBOARD_FILE,
+            PersistedDataType::Actions => paths::ACTIONS_FILE,
+        }
+    }
+
+    pub fn schema(self) -> &'static SchemaRegistry {
+        match self {
+            PersistedDataType::Scenes => &schema::SCENES_SCHEMA,
+            PersistedDataType::Notes => &schema::NOTES_SCHEMA,
+            PersistedDataType::Todos => &schema::TODOS_SCHEMA,
+            PersistedDataType::Clipboard => &schema::CLIPBOARD_SCHEMA,
+            PersistedDataType::Actions => &schema::ACTIONS_SCHEMA,
+        }
+    }
+}
+
+/// 读取数据文件，旧版本会先备份再升级到当前结构并回写。
+///
+/// 文件损坏时会被移入隔离目录并尝试从最近的备份恢复；没有可用备份时按文件不存在处理。
+pub fn read_persisted(
+    data_type: PersistedDataType,
+) -> Result<Option<DataEnvelope<Value>>, StorageError> {
+    let backend = backend::current()?;
+    let name = data_type.file_name();
+    let stored = match backend.read(name) {
+        Ok(Some(value)) => serde_json::from_value::<DataEnvelope<Value>>(value),
+        Ok(None) => return Ok(None),
+        Err(StorageError::Json(error)) => Err(error),
+        Err(error) => return Err(error),
+    };
+    let stored = match stored {
+        Ok(stored) => stored,
+        Err(error) => {
+            let Some(path) = backend.file_path(name) else {
+                return Err(StorageError::Json(error));
+            };
+            let report = recovery::recover_corrupt_file(
+                &path,
+                &StorageError::Json(error).to_string(),
+                &recovery::is_valid_envelope,
+            );
+            let restored = report.restored_from_backup.is_some();
+            recovery::record_report(report);
+            if !restored {
+                return Ok(None);
+            }
+            json_store::read_enveloped_json(&path)?
+        }
+    };
+    let registry = data_type.schema();
+    let upgrade = registry.upgrade(stored.schema_version, stored.data)?;
+    if !upgrade.upgraded() {
+        return Ok(Some(DataEnvelope {
+            schema_version: stored.schema_version,
+            updated_at: stored.updated_at,
+            data: upgrade.value,
+        }));
+    }
+
+    schema::backup_before_upgrade(registry, &upgrade)?;
+    let envelope = DataEnvelope {
+        schema_version: upgrade.to_version,
+        updated_at: stored.updated_at,
+        data: upgrade.value.clone(),
+    };
+    backend.write(name, &serde_json::to_value(&envelope)?)?;
+    schema::record_upgrade(registry, &upgrade)?;
+    Ok(Some(envelope))
+}
+
+/// 串行化所有数据写入，保证比较 `updatedAt` 与写入之间不会插入其他窗口的写入
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 暂停所有数据写入直到返回值被释放，用于整体复制或替换数据的操作
+pub fn lock_writes() -> MutexGuard<'static, ()> {
+    WRITE_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// 以当前结构版本写入数据文件，已有更新版本的数据时拒绝覆盖
+pub fn write_persisted(
+    data_type: PersistedDataType,
+    data: Value,
+) -> Result<DataEnvelope<Value>, StorageError> {
+    write_persisted_if(data_type, data, None)
+}
+
+/// 仅当已保存数据的 `updatedAt` 仍等于 `expected_updated_at` 时写入。
+///
+/// 数据不符合该类数据的结构时返回 `StorageError::InvalidData`，不会写坏数据文件。
+/// 数据在调用方读取之后被其他窗口改写时返回 `StorageError::Conflict`，其中带有当前数据，
+/// 调用方合并后可以用新的 `updatedAt` 重试；`expected_updated_at` 为 `None` 时直接覆盖。
+pub fn write_persisted_if(
+    data_type: PersistedDataType,
+    data: Value,
+    expected_updated_at: Option<u64>,
+) -> Result<DataEnvelope<Value>, StorageError> {
+    data::validate(data_type, &data)?;
+    let _lock = lock_writes();
+    let backend = backend::current()?;
+    let name = data_type.file_name();
+    let registry = data_type.schema();
+    let stored =
+        recovery::read_before_overwrite(backend.as_ref(), name, &recovery::is_valid_envelope)?;
+    ensure_not_newer(stored.as_ref(), registry)?;
+
+    let stored_updated_at = stored
+        .as_ref()
+        .and_then(|stored| stored.get("updatedAt"))
+        .and_then(Value::as_u64);
+    if expected_updated_at.is_some_and(|expected| stored_updated_at != Some(expected)) {
+        return Err(StorageError::Conflict {
+            name: registry.name.to_string(),
+            current: read_persisted(data_type)?.map(Box::new),
+        });
+    }
+
+    // 同一毫秒内的两次写入也要得到不同的 `updatedAt`，否则无法区分
+    let envelope = DataEnvelope {
+        schema_version: registry.current_version(),
+        updated_at: json_store::now_millis().max(stored_updated_at.map_or(0, |at| at + 1)),
+        data,
+    };
+    let value = serde_json::to_value(&envelope)?;
+    history::record_previous_version(name, stored.as_ref(), &value);
+    backend.write(name, &value)?;
+    events::record_write(data_type, envelope.updated_at);
+    Ok(envelope)
+}
+
+fn ensure_not_newer(stored: Option<&Value>, registry: &SchemaRegistry) -> Result<(), StorageError> {
+    let stored_version = stored
+        .and_then(|stored| stored.get("schemaVersion"))
+        .and_then(Value::as_u64);
+    match stored_version {
+        Some(found) if found > registry.current_version() as u64 => {
+            Err(StorageError::SchemaTooNew {
+                name: registry.name.to_string(),
+                found: found.min(u32::MAX as u64) as u32,
+                supported: registry.current_version(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_persisted, write_persisted, write_persisted_if, PersistedDataType};
+    use crate::storage::backend::{self, MemoryBackend, StorageBackend};
+    use crate::storage::error::StorageError;
+    use crate::storage::{json_store, paths};
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 写入使用当前结构版本并可读回() {
+        let data_dir = unique_temp_dir("persisted-roundtrip");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let written =
+            write_persisted(PersistedDataType::Notes, json!({ "notes": [] })).expect("写入应成功");
+        let read = read_persisted(PersistedDataType::Notes)
+            .expect("读取应成功")
+            .expect("文件应存在");
+
+        assert_eq!(
+            written.schema_version,
+            PersistedDataType::Notes.schema().current_version()
+        );
+        assert_eq!(read.schema_version, written.schema_version);
+        assert_eq!(read.updated_at, written.updated_at);
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 更新版本的数据文件既不能读取也不会被覆盖() {
+        let data_dir = unique_temp_dir("persisted-too-new");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        let newer = r#"{"schemaVersion":99,"updatedAt":1,"data":{"items":[]}}"#;
+        fs::write(paths::todos_path(), newer).expect("应能写入新版本数据");
+
+        let read_error = read_persisted(PersistedDataType::Todos).expect_err("读取应被拒绝");
+        let write_error = write_persisted(PersistedDataType::Todos, json!({ "items": [] }))
+            .expect_err("写入应被拒绝");
+
+        assert!(matches!(
+            read_error,
+            StorageError::SchemaTooNew { found: 99, .. }
+        ));
+        assert!(matches!(
+            write_error,
+            StorageError::SchemaTooNew { found: 99, .. }
+        ));
+        assert_eq!(
+            fs::read_to_string(paths::todos_path()).expect("应能读取原文件"),
+            newer
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 直接覆盖损坏的数据文件前会先隔离() {
+        let data_dir = unique_temp_dir("persisted-overwrite-corrupt");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).expect("应能创建测试数据目录");
+        fs::write(paths::notes_path(), "{\"schemaVersion\":").expect("应能写入损坏数据");
+
+        write_persisted(PersistedDataType::Notes, json!({ "notes": [] })).expect("写入应成功");
+
+        let quarantined: Vec<_> = fs::read_dir(paths::quarantine_dir())
+            .expect("损坏的文件应被隔离")
+            .collect();
+        assert_eq!(quarantined.len(), 1);
+        assert!(read_persisted(PersistedDataType::Notes)
+            .expect("读取应成功")
+            .is_some());
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 读写经由当前存储后端完成() {
+        let memory = Arc::new(MemoryBackend::default());
+        let _guard = backend::set_test_backend(memory.clone());
+        memory
+            .write(
+                paths::SCENES_FILE,
+                &json!({ "schemaVersion": 1, "updatedAt": 1, "data": { "scenes": [] } }),
+            )
+            .expect("写入应成功");
+
+        let read = read_persisted(PersistedDataType::Scenes)
+            .expect("读取应成功")
+            .expect("数据应存在");
+        assert_eq!(read.data, json!({ "scenes": [] }));
+
+        write_persisted(
+            PersistedDataType::Todos,
+            json!({ "items": [{ "id": "todo-1", "title": "待办", "date": "2026-05-30", "createdAt": 1, "updatedAt": 1 }] }),
+        )
+        .expect("写入应成功");
+        let stored = memory
+            .read(paths::TODOS_FILE)
+            .expect("读取应成功")
+            .expect("待办应写入内存后端");
+        assert_eq!(stored["data"]["items"][0]["id"], "todo-1");
+    }
+
+    #[test]
+    fn 基于过期的_updated_at_写入会返回冲突和当前数据() {
+        let memory = Arc::new(MemoryBackend::default());
+        let _guard = backend::set_test_backend(memory.clone());
+
+        let first = write_persisted(PersistedDataType::Notes, json!({ "notes": [] }))
+            .expect("首次写入应成功");
+        let second = write_persisted_if(
+            PersistedDataType::Notes,
+            json!({ "notes": [{ "id": "note-1", "content": "便签", "createdAt": 1, "updatedAt": 1 }] }),
+            Some(first.updated_at),
+        )
+        .expect("基于最新数据的写入应成功");
+        assert!(second.updated_at > first.updated_at);
+
+        let error = write_persisted_if(
+            PersistedDataType::Notes,
+            json!({ "notes": [{ "id": "note-2", "content": "另一条", "createdAt": 1, "updatedAt": 1 }] }),
+            Some(first.updated_at),
+        )
+        .expect_err("基于旧数据的写入应被拒绝");
+        let StorageError::Conflict { current, .. } = error else {
+            panic!("应返回冲突错误");
+        };
+        let current = current.expect("冲突应带有当前数据");
+        assert_eq!(current.updated_at, second.updated_at);
+        assert_eq!(current.data["notes"][0]["id"], "note-1");
+
+        let stored = memory
+            .read(paths::NOTES_FILE)
+            .expect("读取应成功")
+            .expect("便签应存在");
+        assert_eq!(stored["data"]["notes"][0]["id"], "note-1");
+    }
+}
@@ -0,0 +1,263 @@
+use crate::storage::atomic_write::write_atomic;
+use crate::storage::backend::{self, StorageBackend, StorageBackendKind};
+use crate::storage::backup;
+use crate::storage::error::StorageError;
+use crate::storage::json_store::{now_millis, DataEnvelope};
+use crate::storage::paths;
+use crate::storage::persisted::PersistedDataType;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError, RwLock};
+
+/// 损坏文件的处理记录，由前端通过 `take_data_recovery_reports` 取走后展示
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryReport {
+    pub file_name: String,
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quarantined_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_from_backup: Option<String>,
+    pub recovered_at: u64,
+}
+
+static PENDING_REPORTS: Mutex<Vec<RecoveryReport>> = Mutex::new(Vec::new());
+
+type ReportListener = Box<dyn Fn() + Send + Sync>;
+
+static LISTENER: RwLock<Option<ReportListener>> = RwLock::new(None);
+
+/// 设置有新处理记录时的接收者，启动时由主程序注册为提醒前端取走记录。
+///
+/// 注册之前（启动检查期间）产生的记录只保存下来，由前端准备好后主动取走。
+pub fn set_listener(listener: impl Fn() + Send + Sync + 'static) {
+    *LISTENER.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(listener));
+}
+
+pub fn record_report(report: RecoveryReport) {
+    PENDING_REPORTS.lock().unwrap().push(report);
+    if let Some(listener) = LISTENER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        listener();
+    }
+}
+
+/// 取出尚未被前端确认的处理记录
+pub fn take_reports() -> Vec<RecoveryReport> {
+    std::mem::take(&mut *PENDING_REPORTS.lock().unwrap())
+}
+
+/// 读取即将被覆盖的文档，文档损坏时先隔离并尝试从备份恢复，再返回恢复后的内容。
+///
+/// 覆盖写入前调用，损坏的文件不会不经隔离就被覆盖；无法隔离（例如 SQLite 中的文档）或读取出错时返回错误。
+pub fn read_before_overwrite(
+    backend: &dyn StorageBackend,
+    name: &str,
+    is_valid: &dyn Fn(&[u8]) -> bool,
+) -> Result<Option<Value>, StorageError> {
+    let error = match backend.read(name) {
+        Err(StorageError::Json(error)) => error,
+        result => return result,
+    };
+    let Some(path) = backend.file_path(name) else {
+        return Err(StorageError::Json(error));
+    };
+    let report = recover_corrupt_file(&path, &StorageError::Json(error).to_string(), is_valid);
+    let restored = report.restored_from_backup.is_some();
+    record_report(report);
+    if restored {
+        backend.read(name)
+    } else {
+        Ok(None)
+    }
+}
+
+/// 记录一个无法读取但保持原样的文件，例如版本号无法识别的配置；文件不会被移动或覆盖
+pub fn report_unreadable(file_name: &str, error: &str) {
+    record_report(RecoveryReport {
+        file_name: file_name.to_string(),
+        error: error.to_string(),
+        quarantined_to: None,
+        restored_from_backup: None,
+        recovered_at: now_millis(),
+    });
+}
+
+/// 把损坏的文件移入隔离目录，并尝试用最近一份备份中的有效副本替换。
+///
+/// `is_valid` 用来判断备份中的副本能否正常解析，无效的副本会被跳过。
+pub fn recover_corrupt_file(
+    path: &Path,
+    error: &str,
+    is_valid: &dyn Fn(&[u8]) -> bool,
+) -> RecoveryReport {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut report = RecoveryReport {
+        file_name: file_name.clone(),
+        error: error.to_string(),
+        quarantined_to: None,
+        restored_from_backup: None,
+        recovered_at: now_millis(),
+    };
+
+    match quarantine_file(path) {
+        Ok(target) => report.quarantined_to = Some(target.to_string_lossy().to_string()),
+        Err(quarantine_error) => {
+            report.error = format!("{}；隔离损坏文件失败: {}", error, quarantine_error);
+            return report;
+        }
+    }
+
+    match restore_from_latest_backup(&file_name, path, is_valid) {
+        Ok(restored) => report.restored_from_backup = restored,
+        Err(restore_error) => {
+            report.error = format!("{}；从备份恢复失败: {}", error, restore_error);
+        }
+    }
+    report
+}
+
+pub fn quarantine_file(path: &Path) -> Result<PathBuf, StorageError> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let target = paths::quarantine_dir().join(format!("{}.{}.corrupt", file_name, now_millis()));
+    if fs::rename(path, &target).is_err() {
+        fs::copy(path, &target)?;
+        fs::remove_file(path)?;
+    }
+    Ok(target)
+}
+
+fn restore_from_latest_backup(
+    file_name: &str,
+    target: &Path,
+    is_valid: &dyn Fn(&[u8]) -> bool,
+) -> Result<Option<String>, StorageError> {
+    for summary in backup::list_backups()? {
+        let candidate = PathBuf::from(&summary.dir).join(file_name);
+        let Ok(content) = fs::read(&candidate) else {
+            continue;
+        };
+        if is_valid(&content) {
+            write_atomic(target, &content)?;
+            return Ok(Some(summary.id));
+        }
+    }
+    Ok(None)
+}
+
+pub fn is_valid_envelope(content: &[u8]) -> bool {
+    serde_json::from_slice::<DataEnvelope<Value>>(content).is_ok()
+}
+
+/// 启动时检查所有统一外壳数据文件，损坏的文件会被隔离并尝试从备份恢复。
+///
+/// 数据保存在 SQLite 中时没有这些文件，不做检查。
+pub fn check_envelope_files() -> Vec<RecoveryReport> {
+    let mut reports = Vec::new();
+    if backend::current_kind() != StorageBackendKind::Json {
+        return reports;
+    }
+    for data_type in PersistedDataType::ALL {
+        let path = data_type.path();
+        let Ok(content) = fs::read(&path) else {
+            continue;
+        };
+        if let Err(error) = serde_json::from_slice::<DataEnvelope<Value>>(&content) {
+            let report = recover_corrupt_file(
+                &path,
+                &StorageError::Json(error).to_string(),
+                &is_valid_envelope,
+            );
+            record_report(report.clone());
+            reports.push(report);
+        }
+    }
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_envelope_files, is_valid_envelope, recover_corrupt_file};
+    use crate::storage::{backup, json_store, paths};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 损坏文件会被隔离并从最近的有效备份恢复() {
+        let data_dir = unique_temp_dir("recovery-restore");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let valid = r#"{"schemaVersion":1,"updatedAt":1,"data":{"notes":[{"id":"note-1"}]}}"#;
+        fs::write(paths::notes_path(), valid).expect("应能写入便签");
+        let backup = backup::create_full_backup("测试备份").expect("备份应成功");
+        fs::create_dir_all(paths::backups_dir().join("full-99999999999999"))
+            .expect("应能创建较新的备份");
+        fs::write(
+            paths::backups_dir()
+                .join("full-99999999999999")
+                .join(paths::NOTES_FILE),
+            "{broken",
+        )
+        .expect("应能写入损坏的备份副本");
+        fs::write(paths::notes_path(), "{not json").expect("应能写入损坏文件");
+
+        let reports = check_envelope_files();
+
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.file_name, paths::NOTES_FILE);
+        assert_eq!(
+            report.restored_from_backup.as_deref(),
+            backup.dir.file_name().and_then(|name| name.to_str())
+        );
+        let quarantined = PathBuf::from(report.quarantined_to.as_ref().expect("应已隔离"));
+        assert_eq!(
+            fs::read_to_string(quarantined).expect("应能读取隔离文件"),
+            "{not json"
+        );
+        assert_eq!(
+            fs::read_to_string(paths::notes_path()).expect("应能读取恢复文件"),
+            valid
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 没有可用备份时只隔离损坏文件() {
+        let data_dir = unique_temp_dir("recovery-no-backup");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::write(paths::todos_path(), "{not json").expect("应能写入损坏文件");
+
+        let report = recover_corrupt_file(&paths::todos_path(), "解析失败", &is_valid_envelope);
+
+        assert!(report.quarantined_to.is_some());
+        assert!(report.restored_from_backup.is_none());
+        assert!(!paths::todos_path().exists());
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
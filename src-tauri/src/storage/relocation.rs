@@ -0,0 +1,270 @@
+use crate::storage::backend;
+use crate::storage::error::StorageError;
+use crate::storage::package;
+use crate::storage::paths::{self, DataDirSource};
+use crate::storage::persisted;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirInfo {
+    pub path: String,
+    pub source: DataDirSource,
+    /// 环境变量和便携模式决定的数据目录不能移动
+    pub can_relocate: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirRelocation {
+    pub from: String,
+    pub to: String,
+    /// 复制并校验过的文件，以 `/` 分隔的相对路径
+    pub files: Vec<String>,
+    /// 已复制到新位置但没能从原位置删除的文件，通常是仍被占用
+    pub leftover: Vec<String>,
+}
+
+pub fn data_dir_info() -> DataDirInfo {
+    let (path, source) = paths::data_dir_location();
+    DataDirInfo {
+        path: path.to_string_lossy().to_string(),
+        source,
+        can_relocate: matches!(source, DataDirSource::Relocated | DataDirSource::Default),
+    }
+}
+
+fn invalid_target(message: String) -> StorageError {
+    StorageError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        message,
+    ))
+}
+
+/// 把整个数据目录（配置、数据文件、图标、备份等）移动到 `target` 并记录新位置。
+///
+/// 每个文件复制后都会比对大小和 SHA-256，全部一致才切换到新位置并删除原位置的文件；
+/// 任何一步失败时删除已复制的内容，继续使用原数据目录。
+/// 整个过程中暂停数据写入，复制之后的修改不会只留在原位置而丢失。
+pub fn relocate_data_dir(target: &Path) -> Result<DataDirRelocation, StorageError> {
+    let (source, kind) = paths::data_dir_location();
+    match kind {
+        DataDirSource::Env => {
+            return Err(invalid_target(
+                "数据目录由 PROGRAM_MANAGER_DATA_DIR 环境变量指定，不能移动".to_string(),
+            ))
+        }
+        DataDirSource::Portable => {
+            return Err(invalid_target(
+                "便携模式的数据目录固定在程序目录中，不能移动".to_string(),
+            ))
+        }
+        DataDirSource::Relocated | DataDirSource::Default => {}
+    }
+
+    let _writes = persisted::lock_writes();
+    // 关闭数据库连接，原位置的数据库文件才能在复制后删除；切换后在新位置重新打开
+    backend::close_current();
+    let files = copy_data_dir(&source, target)?;
+    paths::set_relocated_data_dir(target)?;
+    let leftover = remove_copied_files(&source, &files);
+    Ok(DataDirRelocation {
+        from: source.to_string_lossy().to_string(),
+        to: target.to_string_lossy().to_string(),
+        files,
+        leftover,
+    })
+}
+
+/// 复制数据目录并逐个校验，返回复制的文件；失败时清理目标目录
+fn copy_data_dir(source: &Path, target: &Path) -> Result<Vec<String>, StorageError> {
+    validate_target(source, target)?;
+    let created = !target.exists();
+    let files: Vec<String> = package::list_files(source)?
+        .into_iter()
+        .filter(|name| name != paths::LOCATION_FILE)
+        .collect();
+
+    let result = files.iter().try_for_each(|name| {
+        let from = source.join(name);
+        let to = target.join(name);
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&from, &to)?;
+        if fs::metadata(&from)?.len() != fs::metadata(&to)?.len()
+            || package::sha256_file(&from)? != package::sha256_file(&to)?
+        {
+            return Err(StorageError::Io(std::io::Error::other(format!(
+                "{} 复制后校验不一致",
+                name
+            ))));
+        }
+        Ok(())
+    });
+
+    if let Err(error) = result {
+        if created {
+            let _ = fs::remove_dir_all(target);
+        } else {
+            remove_copied_files(target, &files);
+        }
+        return Err(error);
+    }
+    Ok(files)
+}
+
+/// 目标必须是绝对路径，不能与当前数据目录互相包含，已存在时必须为空
+fn validate_target(source: &Path, target: &Path) -> Result<(), StorageError> {
+    if !target.is_absolute() {
+        return Err(invalid_target(format!(
+            "请选择绝对路径: {}",
+            target.display()
+        )));
+    }
+    let source = fs::canonicalize(source)?;
+    let resolved = resolve_existing(target)?;
+    if resolved.starts_with(&source) || source.starts_with(&resolved) {
+        return Err(invalid_target(format!(
+            "新位置不能与当前数据目录相互包含: {}",
+            target.display()
+        )));
+    }
+    if target.exists() {
+        let occupied = fs::read_dir(target)?
+            .filter_map(Result::ok)
+            .any(|entry| entry.file_name() != paths::LOCATION_FILE);
+        if occupied {
+            return Err(invalid_target(format!(
+                "新位置必须是空文件夹: {}",
+                target.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 解析路径中已存在的部分，用于比较尚未创建的目标目录
+fn resolve_existing(path: &Path) -> Result<PathBuf, StorageError> {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    while !existing.exists() {
+        missing.push(existing.file_name().unwrap_or_default().to_os_string());
+        existing = existing
+            .parent()
+            .ok_or_else(|| invalid_target(format!("路径无效: {}", path.display())))?;
+    }
+    let mut resolved = fs::canonicalize(existing)?;
+    resolved.extend(missing.iter().rev());
+    Ok(resolved)
+}
+
+/// 删除已复制的文件和随之变空的目录，返回没能删除的文件
+fn remove_copied_files(dir: &Path, files: &[String]) -> Vec<String> {
+    let leftover: Vec<String> = files
+        .iter()
+        .filter(|name| fs::remove_file(dir.join(name)).is_err() && dir.join(name).exists())
+        .cloned()
+        .collect();
+
+    let mut parents: Vec<PathBuf> = files
+        .iter()
+        .flat_map(|name| Path::new(name).ancestors().skip(1))
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .collect();
+    parents.sort();
+    parents.dedup();
+    parents.sort_by_key(|parent| std::cmp::Reverse(parent.components().count()));
+    for parent in parents {
+        // 目录不为空时删除会失败，保留其中未复制的内容
+        let _ = fs::remove_dir(dir.join(parent));
+    }
+    leftover
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_data_dir, remove_copied_files};
+    use crate::storage::{json_store, paths};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 复制并校验整个数据目录后删除原文件() {
+        let root = unique_temp_dir("relocate");
+        let source = root.join("source");
+        let target = root.join("usb").join("program-manager");
+        fs::create_dir_all(source.join("icons")).unwrap();
+        fs::create_dir_all(source.join("backups").join("manual-1")).unwrap();
+        fs::write(source.join(paths::CONFIG_FILE), "{}").unwrap();
+        fs::write(source.join("icons").join("app.png"), [1, 2, 3]).unwrap();
+        fs::write(
+            source
+                .join("backups")
+                .join("manual-1")
+                .join(paths::NOTES_FILE),
+            "[]",
+        )
+        .unwrap();
+        fs::write(source.join(paths::LOCATION_FILE), "{}").unwrap();
+
+        let files = copy_data_dir(&source, &target).expect("复制应成功");
+        assert_eq!(
+            files,
+            vec![
+                "backups/manual-1/notes.json".to_string(),
+                paths::CONFIG_FILE.to_string(),
+                "icons/app.png".to_string(),
+            ]
+        );
+        assert_eq!(
+            fs::read(target.join("icons").join("app.png")).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(!target.join(paths::LOCATION_FILE).exists());
+
+        assert!(remove_copied_files(&source, &files).is_empty());
+        assert!(!source.join("icons").exists());
+        assert!(!source.join("backups").exists());
+        assert!(
+            source.join(paths::LOCATION_FILE).exists(),
+            "新位置记录留在原目录"
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn 拒绝非空或位于数据目录内的新位置() {
+        let root = unique_temp_dir("relocate-invalid");
+        let source = root.join("source");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join(paths::CONFIG_FILE), "{}").unwrap();
+
+        assert!(copy_data_dir(&source, &source.join("nested")).is_err());
+        assert!(copy_data_dir(&source, &root).is_err());
+
+        let occupied = root.join("occupied");
+        fs::create_dir_all(&occupied).unwrap();
+        fs::write(occupied.join("other.txt"), "x").unwrap();
+        assert!(copy_data_dir(&source, &occupied).is_err());
+        assert!(
+            occupied.join("other.txt").exists(),
+            "失败时不动目标中原有的文件"
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
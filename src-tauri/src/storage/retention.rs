@@ -0,0 +1,230 @@
+use crate::storage::backup::{self, BackupKind, BackupPurpose, BackupSummary};
+use crate::storage::error::StorageError;
+use std::collections::HashSet;
+
+const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+/// 备份保留策略，各项条件取并集：满足任一保留条件的备份都会保留，
+/// 之后再按总大小上限从最旧的备份开始裁剪。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub keep_last: Option<usize>,
+    pub keep_daily_days: Option<u32>,
+    pub max_total_bytes: Option<u64>,
+}
+
+/// 根据策略挑选需要删除的备份 id。
+///
+/// 只处理完整备份，数据结构升级前的自动备份也是完整备份，同样按策略清理；
+/// 迁移备份只在从旧版本 localStorage 迁移时创建一次，体积很小且是旧数据唯一的副本，不计入策略，始终保留。
+/// 最新的一份完整备份和最新的一份恢复前自动备份无论策略如何都不会被删除，
+/// 后者是撤销最近一次恢复的唯一途径，不能被之后的定时备份挤掉。
+pub fn select_backups_to_prune(
+    backups: &[BackupSummary],
+    policy: &RetentionPolicy,
+    now: u64,
+) -> Vec<String> {
+    let mut full_backups: Vec<(&BackupSummary, u64)> = backups
+        .iter()
+        .filter(|item| item.kind == BackupKind::Full)
+        .map(|item| (item, created_at(item)))
+        .collect();
+    full_backups.sort_by_key(|(_, timestamp)| std::cmp::Reverse(*timestamp));
+
+    let mut kept = HashSet::new();
+    if policy.keep_last.is_none() && policy.keep_daily_days.is_none() {
+        kept.extend(full_backups.iter().map(|(item, _)| item.id.clone()));
+    }
+    let mut protected: Vec<&str> = Vec::new();
+    if let Some((newest, _)) = full_backups.first() {
+        protected.push(&newest.id);
+    }
+    if let Some((safety, _)) = full_backups
+        .iter()
+        .find(|(item, _)| is_restore_safety(item))
+    {
+        protected.push(&safety.id);
+    }
+    kept.extend(protected.iter().map(|id| id.to_string()));
+    if let Some(keep_last) = policy.keep_last {
+        for (item, _) in full_backups.iter().take(keep_last) {
+            kept.insert(item.id.clone());
+        }
+    }
+    if let Some(days) = policy.keep_daily_days {
+        let oldest_day = (now / DAY_MILLIS).saturating_sub(days.saturating_sub(1) as u64);
+        let mut seen_days = HashSet::new();
+        for (item, timestamp) in &full_backups {
+            let day = timestamp / DAY_MILLIS;
+            if day >= oldest_day && seen_days.insert(day) {
+                kept.insert(item.id.clone());
+            }
+        }
+    }
+
+    let (remaining, dropped): (Vec<_>, Vec<_>) = full_backups
+        .into_iter()
+        .partition(|(item, _)| kept.contains(&item.id));
+    let mut pruned: Vec<String> = dropped
+        .into_iter()
+        .map(|(item, _)| item.id.clone())
+        .collect();
+    if let Some(max_total_bytes) = policy.max_total_bytes {
+        let mut total = 0;
+        for (item, _) in &remaining {
+            total += item.size;
+            if !protected.contains(&item.id.as_str()) && total > max_total_bytes {
+                pruned.push(item.id.clone());
+            }
+        }
+    }
+    pruned
+}
+
+/// 按策略删除多余备份，返回被删除的备份 id
+pub fn prune_backups(policy: &RetentionPolicy, now: u64) -> Result<Vec<String>, StorageError> {
+    let backups = backup::list_backups()?;
+    let pruned = select_backups_to_prune(&backups, policy, now);
+    for id in &pruned {
+        backup::delete_backup(id)?;
+    }
+    Ok(pruned)
+}
+
+fn is_restore_safety(item: &BackupSummary) -> bool {
+    item.manifest
+        .as_ref()
+        .is_some_and(|manifest| manifest.purpose == BackupPurpose::RestoreSafety)
+}
+
+fn created_at(item: &BackupSummary) -> u64 {
+    item.manifest
+        .as_ref()
+        .map(|manifest| manifest.created_at)
+        .unwrap_or_else(|| backup::backup_timestamp(&item.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{select_backups_to_prune, RetentionPolicy, DAY_MILLIS};
+    use crate::storage::backup::{BackupKind, BackupManifest, BackupPurpose, BackupSummary};
+
+    fn summary(kind: BackupKind, created_at: u64, size: u64) -> BackupSummary {
+        let prefix = match kind {
+            BackupKind::Full => "full",
+            BackupKind::Migration => "migration",
+        };
+        BackupSummary {
+            id: format!("{}-{}", prefix, created_at),
+            kind,
+            dir: String::new(),
+            size,
+            manifest: Some(BackupManifest {
+                created_at,
+                reason: "测试".to_string(),
+                files: Vec::new(),
+                purpose: BackupPurpose::Regular,
+            }),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn 保留最近_n_份完整备份且不删除迁移备份() {
+        let now = 100 * DAY_MILLIS;
+        let backups = vec![
+            summary(BackupKind::Full, now - 1, 1),
+            summary(BackupKind::Full, now - 2, 1),
+            summary(BackupKind::Full, now - 3, 1),
+            summary(BackupKind::Migration, now - 4, 1),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            ..RetentionPolicy::default()
+        };
+
+        let pruned = select_backups_to_prune(&backups, &policy, now);
+
+        assert_eq!(pruned, vec![format!("full-{}", now - 3)]);
+    }
+
+    #[test]
+    fn 按天保留时每天只保留最新一份() {
+        let now = 100 * DAY_MILLIS + 10;
+        let backups = vec![
+            summary(BackupKind::Full, now, 1),
+            summary(BackupKind::Full, now - 5, 1),
+            summary(BackupKind::Full, now - DAY_MILLIS, 1),
+            summary(BackupKind::Full, now - DAY_MILLIS - 5, 1),
+            summary(BackupKind::Full, now - 10 * DAY_MILLIS, 1),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            keep_daily_days: Some(7),
+            max_total_bytes: None,
+        };
+
+        let mut pruned = select_backups_to_prune(&backups, &policy, now);
+        pruned.sort();
+
+        let mut expected = vec![
+            format!("full-{}", now - 5),
+            format!("full-{}", now - DAY_MILLIS - 5),
+            format!("full-{}", now - 10 * DAY_MILLIS),
+        ];
+        expected.sort();
+        assert_eq!(pruned, expected);
+    }
+
+    #[test]
+    fn 超出总大小上限时从最旧的备份开始删除但保留最新一份() {
+        let now = 100 * DAY_MILLIS;
+        let backups = vec![
+            summary(BackupKind::Full, now - 1, 60),
+            summary(BackupKind::Full, now - 2, 30),
+            summary(BackupKind::Full, now - 3, 30),
+        ];
+        let policy = RetentionPolicy {
+            max_total_bytes: Some(100),
+            ..RetentionPolicy::default()
+        };
+
+        assert_eq!(
+            select_backups_to_prune(&backups, &policy, now),
+            vec![format!("full-{}", now - 3)]
+        );
+
+        let tiny = RetentionPolicy {
+            max_total_bytes: Some(10),
+            ..RetentionPolicy::default()
+        };
+        assert_eq!(select_backups_to_prune(&backups, &tiny, now).len(), 2);
+    }
+
+    #[test]
+    fn 始终保留最新的恢复前自动备份() {
+        let now = 100 * DAY_MILLIS;
+        let mut safety = summary(BackupKind::Full, now - 3, 50);
+        safety.manifest.as_mut().unwrap().purpose = BackupPurpose::RestoreSafety;
+        let mut older_safety = summary(BackupKind::Full, now - 4, 50);
+        older_safety.manifest.as_mut().unwrap().purpose = BackupPurpose::RestoreSafety;
+        let backups = vec![
+            summary(BackupKind::Full, now - 1, 50),
+            summary(BackupKind::Full, now - 2, 50),
+            safety,
+            older_safety,
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            keep_daily_days: None,
+            max_total_bytes: Some(60),
+        };
+
+        let mut pruned = select_backups_to_prune(&backups, &policy, now);
+        pruned.sort();
+
+        let mut expected = vec![format!("full-{}", now - 2), format!("full-{}", now - 4)];
+        expected.sort();
+        assert_eq!(pruned, expected);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::storage::backup::{self, BackupResult};
+use crate::storage::error::StorageError;
+use crate::storage::migration;
+use serde_json::{Map, Value};
+
+/// 单个数据结构升级步骤，把 `from_version` 版本的数据升级到下一个版本
+pub struct SchemaStep {
+    pub from_version: u32,
+    pub description: &'static str,
+    pub upgrade: fn(Value) -> Result<Value, String>,
+}
+
+/// 一类数据文件的升级步骤表。
+///
+/// 版本号从 1 开始，第 N 个步骤必须从版本 N 升级到 N + 1，
+/// 因此当前版本始终等于步骤数量加一。
+pub struct SchemaRegistry {
+    pub name: &'static str,
+    pub steps: &'static [SchemaStep],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaUpgrade {
+    pub value: Value,
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+impl SchemaUpgrade {
+    pub fn upgraded(&self) -> bool {
+        self.from_version != self.to_version
+    }
+}
+
+impl SchemaRegistry {
+    pub fn current_version(&self) -> u32 {
+        self.steps.len() as u32 + 1
+    }
+
+    /// 把指定版本的数据逐步升级到当前版本，版本高于当前版本时拒绝读取
+    pub fn upgrade(&self, version: u32, value: Value) -> Result<SchemaUpgrade, StorageError> {
+        let current = self.current_version();
+        if version > current {
+            return Err(StorageError::SchemaTooNew {
+                name: self.name.to_string(),
+                found: version,
+                supported: current,
+            });
+        }
+
+        let mut value = value;
+        let mut next_version = version;
+        while next_version < current {
+            let step = self
+                .steps
+                .iter()
+                .find(|step| step.from_version == next_version)
+                .ok_or_else(|| {
+                    StorageError::Migration(format!(
+                        "{} 缺少从版本 {} 升级的步骤",
+                        self.name, next_version
+                    ))
+                })?;
+            value = (step.upgrade)(value).map_err(|error| {
+                StorageError::Migration(format!(
+                    "{} 从版本 {} 升级失败（{}）: {}",
+                    self.name, next_version, step.description, error
+                ))
+            })?;
+            next_version += 1;
+        }
+
+        Ok(SchemaUpgrade {
+            value,
+            from_version: version,
+            to_version: current,
+        })
+    }
+}
+
+/// 升级结果落盘前先做一次完整备份，遵循“迁移前必须备份”的约定
+pub fn backup_before_upgrade(
+    registry: &SchemaRegistry,
+    upgrade: &SchemaUpgrade,
+) -> Result<BackupResult, StorageError> {
+    backup::create_full_backup(&format!(
+        "升级 {} 数据结构（{} -> {}）前自动备份",
+        registry.name, upgrade.from_version, upgrade.to_version
+    ))
+}
+
+pub fn record_upgrade(
+    registry: &SchemaRegistry,
+    upgrade: &SchemaUpgrade,
+) -> Result<(), StorageError> {
+    migration::append_record(
+        &format!("schema-{}-v{}", registry.name, upgrade.to_version),
+        true,
+        Vec::new(),
+    )?;
+    Ok(())
+}
+
+pub static SCENES_SCHEMA: SchemaRegistry = SchemaRegistry {
+    name: "scenes.json",
+    steps: &[],
+};
+
+pub static NOTES_SCHEMA: SchemaRegistry = SchemaRegistry {
+    name: "notes.json",
+    steps: &[],
+};
+
+pub static TODOS_SCHEMA: SchemaRegistry = SchemaRegistry {
+    name: "todos.json",
+    steps: &[],
+};
+
+pub static CLIPBOARD_SCHEMA: SchemaRegistry = SchemaRegistry {
+    name: "clipboard.json",
+    steps: &[],
+};
+
+pub static ACTIONS_SCHEMA: SchemaRegistry = SchemaRegistry {
+    name: "actions.json",
+    steps: &[],
+};
+
+/// `config.json` 的结构版本取 `version` 字段的主版本号，例如 "1.0" 为版本 1
+pub static CONFIG_SCHEMA: SchemaRegistry = SchemaRegistry {
+    name: "config.json",
+    steps: &[SchemaStep {
+        from_version: 1,
+        description: "规范化程序类型、状态和界面设置的取值",
+        upgrade: normalize_config_choices,
+    }],
+};
+
+/// 配置版本 1 -> 2：把大小写、空白或分隔符写法不一致的取值改为前端使用的写法。
+///
+/// 无法识别的程序类型按前端的处理方式改为 `app`，界面设置改为默认值，状态字段直接删除。
+fn normalize_config_choices(mut value: Value) -> Result<Value, String> {
+    if let Some(apps) = value.get_mut("apps").and_then(Value::as_object_mut) {
+        for app in apps.values_mut().filter_map(Value::as_object_mut) {
+            normalize_choice(app, "itemType", &["app", "folder", "file"], Some("app"));
+            normalize_choice(
+                app,
+                "validationStatus",
+                &["valid", "invalid", "unreachable"],
+                None,
+            );
+            if let Some(metadata) = app.get_mut("updateMetadata").and_then(Value::as_object_mut) {
+                normalize_choice(
+                    metadata,
+                    "updateStatus",
+                    &["none", "suspected", "confirmed"],
+                    None,
+                );
+                normalize_choice(
+                    metadata,
+                    "updateConfidence",
+                    &["high", "medium", "low"],
+                    None,
+                );
+            }
+        }
+    }
+    if let Some(settings) = value.get_mut("settings").and_then(Value::as_object_mut) {
+        normalize_choice(
+            settings,
+            "sortBy",
+            &["name", "lastLaunched", "custom"],
+            Some("lastLaunched"),
+        );
+        normalize_choice(settings, "theme", &["light", "dark", "auto"], Some("auto"));
+        normalize_choice(
+            settings,
+            "cardSize",
+            &["small", "medium", "large"],
+            Some("medium"),
+        );
+    }
+    Ok(value)
+}
+
+/// 字段存在时按忽略大小写和分隔符的方式匹配 `choices`，匹配不到时改为 `fallback`，没有默认值则删除
+fn normalize_choice(
+    object: &mut Map<String, Value>,
+    key: &str,
+    choices: &[&str],
+    fallback: Option<&str>,
+) {
+    let Some(current) = object.get(key) else {
+        return;
+    };
+    let matched = current
+        .as_str()
+        .and_then(|text| {
+            choices
+                .iter()
+                .find(|choice| choice_key(choice) == choice_key(text))
+        })
+        .copied()
+        .or(fallback);
+    match matched {
+        Some(choice) => {
+            object.insert(key.to_string(), Value::String(choice.to_string()));
+        }
+        None => {
+            object.remove(key);
+        }
+    }
+}
+
+fn choice_key(value: &str) -> String {
+    value
+        .chars()
+        .filter(|character| character.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub fn config_version_string(version: u32) -> String {
+    format!("{}.0", version)
+}
+
+/// 解析配置中的 `version` 字段，缺失时视为版本 1
+pub fn parse_config_version(value: &Value) -> Result<u32, StorageError> {
+    let Some(version) = value.get("version") else {
+        return Ok(1);
+    };
+    version
+        .as_str()
+        .and_then(|text| text.trim().split('.').next())
+        .and_then(|major| major.parse::<u32>().ok())
+        .ok_or_else(|| StorageError::Migration(format!("config.json 版本号无效: {}", version)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_config_version, SchemaRegistry, SchemaStep, CONFIG_SCHEMA};
+    use crate::storage::error::StorageError;
+    use serde_json::{json, Value};
+
+    fn rename_title(mut value: Value) -> Result<Value, String> {
+        let items = value
+            .get_mut("items")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| "缺少 items".to_string())?;
+        for item in items {
+            if let Some(title) = item.get("name").cloned() {
+                item["title"] = title;
+            }
+        }
+        Ok(value)
+    }
+
+    fn add_flag(mut value: Value) -> Result<Value, String> {
+        value["flag"] = json!(true);
+        Ok(value)
+    }
+
+    static TEST_SCHEMA: SchemaRegistry = SchemaRegistry {
+        name: "test.json",
+        steps: &[
+            SchemaStep {
+                from_version: 1,
+                description: "name 改为 title",
+                upgrade: rename_title,
+            },
+            SchemaStep {
+                from_version: 2,
+                description: "新增 flag",
+                upgrade: add_flag,
+            },
+        ],
+    };
+
+    #[test]
+    fn 旧版本数据会依次执行升级步骤() {
+        let upgraded = TEST_SCHEMA
+            .upgrade(1, json!({ "items": [{ "name": "待办" }] }))
+            .expect("升级应成功");
+
+        assert!(upgraded.upgraded());
+        assert_eq!(upgraded.to_version, 3);
+        assert_eq!(upgraded.value["items"][0]["title"], "待办");
+        assert_eq!(upgraded.value["flag"], true);
+    }
+
+    #[test]
+    fn 高于当前版本的数据会被拒绝() {
+        let error = TEST_SCHEMA
+            .upgrade(4, json!({}))
+            .expect_err("新版本数据应被拒绝");
+
+        assert!(matches!(
+            error,
+            StorageError::SchemaTooNew {
+                found: 4,
+                supported: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn 升级步骤失败时返回迁移错误() {
+        let error = TEST_SCHEMA
+            .upgrade(1, json!({}))
+            .expect_err("缺少 items 应升级失败");
+
+        assert!(matches!(error, StorageError::Migration(_)));
+    }
+
+    #[test]
+    fn 配置版本取主版本号() {
+        assert_eq!(parse_config_version(&json!({})).unwrap(), 1);
+        assert_eq!(
+            parse_config_version(&json!({ "version": "1.0" })).unwrap(),
+            1
+        );
+        assert_eq!(
+            parse_config_version(&json!({ "version": "1.1.4" })).unwrap(),
+            1
+        );
+        assert_eq!(parse_config_version(&json!({ "version": "3" })).unwrap(), 3);
+        assert!(parse_config_version(&json!({ "version": 1 })).is_err());
+        assert_eq!(CONFIG_SCHEMA.current_version(), 2);
+    }
+
+    #[test]
+    fn 配置升级到版本二时规范化取值() {
+        let upgraded = CONFIG_SCHEMA
+            .upgrade(
+                1,
+                json!({
+                    "version": "1.1.4",
+                    "apps": {
+                        "a": {
+                            "itemType": "App ",
+                            "validationStatus": "broken",
+                            "updateMetadata": { "updateStatus": "Suspected", "updateConfidence": "HIGH" }
+                        },
+                        "b": { "itemType": "shortcut" },
+                        "c": { "name": "没有类型" }
+                    },
+                    "settings": { "sortBy": "last_launched", "theme": "Dark", "cardSize": 3 }
+                }),
+            )
+            .expect("升级应成功");
+
+        let value = upgraded.value;
+        assert_eq!(value["apps"]["a"]["itemType"], "app");
+        assert!(value["apps"]["a"].get("validationStatus").is_none());
+        assert_eq!(
+            value["apps"]["a"]["updateMetadata"]["updateStatus"],
+            "suspected"
+        );
+        assert_eq!(
+            value["apps"]["a"]["updateMetadata"]["updateConfidence"],
+            "high"
+        );
+        assert_eq!(value["apps"]["b"]["itemType"], "app");
+        assert!(value["apps"]["c"].get("itemType").is_none());
+        assert_eq!(value["settings"]["sortBy"], "lastLaunched");
+        assert_eq!(value["settings"]["theme"], "dark");
+        assert_eq!(value["settings"]["cardSize"], "medium");
+    }
+}
@@ -0,0 +1,207 @@
+use crate::storage::error::StorageError;
+use crate::storage::paths;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// 统计占用的数据文件
+const DATA_FILES: &[&str] = &[
+    paths::CONFIG_FILE,
+    paths::SCENES_FILE,
+    paths::NOTES_FILE,
+    paths::TODOS_FILE,
+    paths::CLIPBOARD_FILE,
+    paths::ACTIONS_FILE,
+    paths::MIGRATIONS_FILE,
+    paths::STORAGE_FILE,
+    paths::DATABASE_FILE,
+];
+
+/// 统计占用的子目录
+const DATA_DIRS: &[&str] = &["icons", "backups", "history", "quarantine"];
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionUsage {
+    /// 数据文件名或子目录名
+    pub name: String,
+    pub is_dir: bool,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IconFile {
+    pub file_name: String,
+    pub bytes: u64,
+}
+
+/// 统计数据目录中各数据文件和子目录的占用，不存在的项目计为 0
+pub fn section_usage() -> Result<Vec<SectionUsage>, StorageError> {
+    let data_dir = paths::data_dir();
+    let mut sections = Vec::new();
+    for name in DATA_FILES {
+        let bytes = match fs::metadata(data_dir.join(name)) {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => 0,
+        };
+        sections.push(SectionUsage {
+            name: name.to_string(),
+            is_dir: false,
+            bytes,
+            files: usize::from(bytes > 0),
+        });
+    }
+    for name in DATA_DIRS {
+        let (bytes, files) = dir_usage(&data_dir.join(name))?;
+        sections.push(SectionUsage {
+            name: name.to_string(),
+            is_dir: true,
+            bytes,
+            files,
+        });
+    }
+    Ok(sections)
+}
+
+fn dir_usage(dir: &Path) -> Result<(u64, usize), StorageError> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok((0, 0));
+    };
+    let mut total = (0, 0);
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            let (bytes, files) = dir_usage(&entry.path())?;
+            total.0 += bytes;
+            total.1 += files;
+        } else {
+            total.0 += metadata.len();
+            total.1 += 1;
+        }
+    }
+    Ok(total)
+}
+
+/// 图标字段是否指向图标目录中的文件；`data:` URL 和带路径的值不算
+pub fn is_icon_file_name(icon: &str) -> bool {
+    !icon.is_empty()
+        && !icon.starts_with("data:")
+        && Path::new(icon).file_name().and_then(|name| name.to_str()) == Some(icon)
+}
+
+/// 列出图标目录中没有被 `referenced` 引用的文件
+pub fn orphaned_icons(referenced: &HashSet<&str>) -> Result<Vec<IconFile>, StorageError> {
+    let Ok(entries) = fs::read_dir(paths::icons_dir()) else {
+        return Ok(Vec::new());
+    };
+    let mut orphans = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if metadata.is_file() && !referenced.contains(file_name.as_str()) {
+            orphans.push(IconFile {
+                file_name,
+                bytes: metadata.len(),
+            });
+        }
+    }
+    orphans.sort_by(|left, right| left.file_name.cmp(&right.file_name));
+    Ok(orphans)
+}
+
+/// 图标目录中是否存在该图标文件
+pub fn icon_exists(file_name: &str) -> bool {
+    paths::icons_dir().join(file_name).is_file()
+}
+
+/// 删除孤立的图标文件，返回实际删除的文件
+pub fn remove_icons(icons: &[IconFile]) -> Vec<IconFile> {
+    let icons_dir = paths::icons_dir();
+    icons
+        .iter()
+        .filter(|icon| {
+            is_icon_file_name(&icon.file_name)
+                && fs::remove_file(icons_dir.join(&icon.file_name)).is_ok()
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_icon_file_name, orphaned_icons, remove_icons, section_usage};
+    use crate::storage::{json_store, paths};
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 统计各数据文件和子目录的占用() {
+        let data_dir = unique_temp_dir("usage-sections");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        fs::write(paths::config_path(), "{}").unwrap();
+        fs::create_dir_all(paths::backups_dir().join("manual-1")).unwrap();
+        fs::write(
+            paths::backups_dir()
+                .join("manual-1")
+                .join(paths::NOTES_FILE),
+            "[1,2]",
+        )
+        .unwrap();
+        fs::write(paths::icons_dir().join("a.png"), [0; 10]).unwrap();
+
+        let sections = section_usage().expect("应能统计占用");
+        let find = |name: &str| {
+            sections
+                .iter()
+                .find(|section| section.name == name)
+                .unwrap()
+        };
+        assert_eq!(find(paths::CONFIG_FILE).bytes, 2);
+        assert_eq!(find(paths::NOTES_FILE).bytes, 0);
+        assert_eq!(find("backups").bytes, 5);
+        assert_eq!(find("backups").files, 1);
+        assert_eq!(find("icons").bytes, 10);
+        assert_eq!(find("history").files, 0);
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 只删除没有被引用的图标() {
+        let data_dir = unique_temp_dir("usage-icons");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        fs::write(paths::icons_dir().join("used.png"), [1]).unwrap();
+        fs::write(paths::icons_dir().join("stale.png"), [1, 2]).unwrap();
+
+        let referenced = HashSet::from(["used.png", "missing.png"]);
+        let orphans = orphaned_icons(&referenced).expect("应能列出孤立图标");
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].file_name, "stale.png");
+        assert_eq!(orphans[0].bytes, 2);
+
+        assert_eq!(remove_icons(&orphans), orphans);
+        assert!(!paths::icons_dir().join("stale.png").exists());
+        assert!(paths::icons_dir().join("used.png").exists());
+
+        assert!(is_icon_file_name("app-1.png"));
+        assert!(!is_icon_file_name("data:image/png;base64,AAAA"));
+        assert!(!is_icon_file_name("../config.json"));
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
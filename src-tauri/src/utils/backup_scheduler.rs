@@ -0,0 +1,163 @@
+use crate::models::{AppSettings, AppState};
+use crate::storage::backup::{self, BackupKind};
+use crate::storage::error::StorageError;
+use crate::storage::retention::{self, RetentionPolicy};
+use crate::storage::{json_store, migration};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const SCHEDULED_BACKUP_RECORD_ID: &str = "scheduled-backup";
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const HOUR_MILLIS: u64 = 60 * 60 * 1000;
+
+/// 单次定时检查的结果
+#[derive(Debug, Default)]
+pub struct ScheduledBackupOutcome {
+    pub snapshot_dir: Option<PathBuf>,
+    pub pruned: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+pub fn retention_policy(settings: &AppSettings) -> RetentionPolicy {
+    RetentionPolicy {
+        keep_last: settings.backup_keep_last.map(|count| count as usize),
+        keep_daily_days: settings.backup_keep_daily_days,
+        max_total_bytes: settings
+            .backup_max_total_mb
+            .map(|megabytes| megabytes.saturating_mul(1024 * 1024)),
+    }
+}
+
+/// 启动后台备份线程，定期按设置创建完整快照并清理旧备份
+pub fn start_backup_scheduler(app: AppHandle) {
+    std::thread::spawn(move || {
+        std::thread::sleep(STARTUP_DELAY);
+        loop {
+            let settings = {
+                let state = app.state::<AppState>();
+                let config = state.config.lock().unwrap();
+                config.settings.clone()
+            };
+            let outcome = run_scheduled_backup(&settings, json_store::now_millis());
+            for error in &outcome.errors {
+                eprintln!("定时备份失败: {}", error);
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+/// 执行一次定时检查：快照到期时创建完整备份，然后按保留策略清理。
+///
+/// 只有实际创建了快照、删除了备份或出现错误时才写入迁移/备份记录。
+pub fn run_scheduled_backup(settings: &AppSettings, now: u64) -> ScheduledBackupOutcome {
+    let mut outcome = ScheduledBackupOutcome::default();
+
+    if settings.auto_backup_enabled.unwrap_or(true) {
+        let interval_hours = settings.auto_backup_interval_hours.unwrap_or(24).max(1);
+        match latest_full_backup_at() {
+            Ok(latest) => {
+                let due = latest
+                    .map(|created_at| {
+                        now.saturating_sub(created_at) >= interval_hours as u64 * HOUR_MILLIS
+                    })
+                    .unwrap_or(true);
+                if due {
+                    match backup::create_full_backup("定时自动备份") {
+                        Ok(result) => outcome.snapshot_dir = Some(result.dir),
+                        Err(error) => outcome.errors.push(format!("创建快照失败: {}", error)),
+                    }
+                }
+            }
+            Err(error) => outcome.errors.push(format!("读取备份列表失败: {}", error)),
+        }
+    }
+
+    match retention::prune_backups(&retention_policy(settings), now) {
+        Ok(pruned) => outcome.pruned = pruned,
+        Err(error) => outcome.errors.push(format!("清理旧备份失败: {}", error)),
+    }
+
+    if outcome.snapshot_dir.is_some() || !outcome.pruned.is_empty() || !outcome.errors.is_empty() {
+        let _ = migration::append_record(
+            SCHEDULED_BACKUP_RECORD_ID,
+            outcome.errors.is_empty(),
+            outcome.errors.clone(),
+        );
+    }
+
+    outcome
+}
+
+fn latest_full_backup_at() -> Result<Option<u64>, StorageError> {
+    Ok(backup::list_backups()?
+        .iter()
+        .filter(|item| item.kind == BackupKind::Full)
+        .map(|item| {
+            item.manifest
+                .as_ref()
+                .map(|manifest| manifest.created_at)
+                .unwrap_or_else(|| backup::backup_timestamp(&item.id))
+        })
+        .max())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_scheduled_backup;
+    use crate::models::AppSettings;
+    use crate::storage::{backup, json_store, migration, paths};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "program-manager-{}-{}-{}",
+            name,
+            std::process::id(),
+            json_store::now_millis()
+        ))
+    }
+
+    #[test]
+    fn 定时备份到期时创建快照并按保留策略清理() {
+        let data_dir = unique_temp_dir("backup-scheduler");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::write(paths::config_path(), r#"{"apps":{}}"#).expect("应能写入配置");
+        fs::create_dir_all(paths::backups_dir().join("full-1")).expect("应能创建旧备份");
+        fs::create_dir_all(paths::backups_dir().join("full-2")).expect("应能创建旧备份");
+
+        let settings = AppSettings {
+            backup_keep_last: Some(1),
+            backup_keep_daily_days: None,
+            ..AppSettings::default()
+        };
+        let now = json_store::now_millis();
+
+        let outcome = run_scheduled_backup(&settings, now);
+
+        assert!(outcome.errors.is_empty());
+        assert!(outcome
+            .snapshot_dir
+            .as_ref()
+            .map(|dir| dir.is_dir())
+            .unwrap_or(false));
+        assert_eq!(outcome.pruned.len(), 2);
+        assert_eq!(backup::list_backups().expect("应能列出备份").len(), 1);
+        assert!(migration::load_migration_log()
+            .records
+            .iter()
+            .any(|record| record.id == "scheduled-backup" && record.success));
+
+        let second = run_scheduled_backup(&settings, now + 1);
+        assert!(
+            second.snapshot_dir.is_none(),
+            "未到间隔时间不应再次创建快照"
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+}
@@ -0,0 +1,464 @@
+use crate::error::AppError;
+use crate::models::{App, Category, Config};
+use serde::Deserialize;
+
+/// 删除分类时如何处理其中的程序
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum CategoryAppsDisposal {
+    /// 移动到另一个分类，排在该分类原有程序之后
+    Move {
+        #[serde(rename = "targetId")]
+        target_id: String,
+    },
+    /// 连同分类一起删除
+    Delete,
+}
+
+fn category_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("分类名称不能为空"));
+    }
+    Ok(name.to_string())
+}
+
+fn ensure_category(config: &Config, category_id: &str) -> Result<(), AppError> {
+    if config.categories.contains_key(category_id) {
+        Ok(())
+    } else {
+        Err(AppError::not_found(format!("分类 {}", category_id)))
+    }
+}
+
+/// 按 `order` 排列的直接子分类 id，`parent_id` 为空时是顶级分类
+pub fn child_ids(config: &Config, parent_id: Option<&str>) -> Vec<String> {
+    let mut children: Vec<&Category> = config
+        .categories
+        .values()
+        .filter(|category| category.parent_id.as_deref() == parent_id)
+        .collect();
+    children.sort_by(|left, right| {
+        left.order
+            .cmp(&right.order)
+            .then_with(|| left.id.cmp(&right.id))
+    });
+    children
+        .into_iter()
+        .map(|category| category.id.clone())
+        .collect()
+}
+
+/// 把同级分类的 `order` 重新编为 0..n
+fn renumber(config: &mut Config, parent_id: Option<&str>, ids: &[String]) {
+    for (order, id) in ids.iter().enumerate() {
+        if let Some(category) = config.categories.get_mut(id) {
+            category.order = order;
+            category.parent_id = parent_id.map(str::to_string);
+        }
+    }
+}
+
+/// `ancestor_id` 是否是 `category_id` 本身或它的上级分类；上级链成环时视为是，避免继续嵌套
+pub fn is_self_or_ancestor(config: &Config, ancestor_id: &str, category_id: &str) -> bool {
+    let mut current = Some(category_id);
+    let mut visited = 0;
+    while let Some(id) = current {
+        if id == ancestor_id || visited > config.categories.len() {
+            return true;
+        }
+        visited += 1;
+        current = config
+            .categories
+            .get(id)
+            .and_then(|category| category.parent_id.as_deref());
+    }
+    false
+}
+
+pub fn add_category(
+    config: &mut Config,
+    name: &str,
+    parent_id: Option<&str>,
+) -> Result<Category, AppError> {
+    if let Some(parent_id) = parent_id {
+        ensure_category(config, parent_id)?;
+    }
+    let category = Category {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: category_name(name)?,
+        icon: None,
+        apps: Vec::new(),
+        order: child_ids(config, parent_id).len(),
+        parent_id: parent_id.map(str::to_string),
+    };
+    config
+        .categories
+        .insert(category.id.clone(), category.clone());
+    Ok(category)
+}
+
+pub fn rename_category(config: &mut Config, category_id: &str, name: &str) -> Result<(), AppError> {
+    let name = category_name(name)?;
+    let category = config
+        .categories
+        .get_mut(category_id)
+        .ok_or_else(|| AppError::not_found(format!("分类 {}", category_id)))?;
+    category.name = name;
+    Ok(())
+}
+
+/// 删除分类，返回随分类删除的程序。
+///
+/// 子分类连同其中的程序上移一级，排在原同级分类之后；
+/// 只是同时出现在该分类中的程序仅取消关联，不受 `disposal` 影响。
+/// 自动分类规则中对该分类的引用也一并处理。
+pub fn delete_category(
+    config: &mut Config,
+    category_id: &str,
+    disposal: &CategoryAppsDisposal,
+) -> Result<Vec<App>, AppError> {
+    ensure_category(config, category_id)?;
+    if let CategoryAppsDisposal::Move { target_id } = disposal {
+        if target_id == category_id {
+            return Err(AppError::invalid_input("不能把程序移动到要删除的分类"));
+        }
+        ensure_category(config, target_id)?;
+    }
+
+    let category = config.categories.remove(category_id).unwrap();
+    let parent_id = category.parent_id.as_deref();
+    let mut siblings = child_ids(config, parent_id);
+    siblings.extend(child_ids(config, Some(category_id)));
+    renumber(config, parent_id, &siblings);
+
+    let mut app_ids = category.apps.clone();
+    let mut strays: Vec<String> = config
+        .apps
+        .values()
+        .filter(|app| app.category == category_id && !app_ids.contains(&app.id))
+        .map(|app| app.id.clone())
+        .collect();
+    strays.sort();
+    app_ids.extend(strays);
+
+    for app in config.apps.values_mut() {
+        app.extra_categories.retain(|id| id != category_id);
+    }
+
+    let mut removed = Vec::new();
+    for app_id in &app_ids {
+        if config
+            .apps
+            .get(app_id)
+            .is_some_and(|app| app.category != category_id)
+        {
+            continue;
+        }
+        match disposal {
+            CategoryAppsDisposal::Move { target_id } => {
+                if let Some(app) = config.apps.get_mut(app_id) {
+                    app.category = target_id.clone();
+                    app.extra_categories.retain(|id| id != target_id);
+                    let target = config.categories.get_mut(target_id).unwrap();
+                    if !target.apps.contains(app_id) {
+                        target.apps.push(app_id.clone());
+                    }
+                }
+            }
+            CategoryAppsDisposal::Delete => removed.extend(config.apps.remove(app_id)),
+        }
+    }
+    // 被删除的程序可能还关联在其他分类中
+    for category in config.categories.values_mut() {
+        category
+            .apps
+            .retain(|app_id| !removed.iter().any(|app| app.id == *app_id));
+    }
+
+    if config.settings.last_category.as_deref() == Some(category_id) {
+        config.settings.last_category = category.parent_id.clone();
+    }
+
+    // 指向该分类的自动分类规则跟随程序改指目标分类；程序被删除时规则不再指定分类，
+    // 既没有分类也没有标签的规则随之删除
+    let rule_target = match disposal {
+        CategoryAppsDisposal::Move { target_id } => Some(target_id.clone()),
+        CategoryAppsDisposal::Delete => None,
+    };
+    for rule in &mut config.category_rules {
+        if rule.category_id.as_deref() == Some(category_id) {
+            rule.category_id = rule_target.clone();
+        }
+    }
+    config
+        .category_rules
+        .retain(|rule| rule.category_id.is_some() || !rule.tags.is_empty());
+    Ok(removed)
+}
+
+/// 按 `category_ids` 的顺序重新排列 `parent_id` 下的全部直接子分类
+pub fn reorder_categories(
+    config: &mut Config,
+    parent_id: Option<&str>,
+    category_ids: &[String],
+) -> Result<(), AppError> {
+    let mut expected = child_ids(config, parent_id);
+    let mut given = category_ids.to_vec();
+    expected.sort();
+    given.sort();
+    if expected != given {
+        return Err(AppError::invalid_input(
+            "排序列表必须恰好包含同一上级下的全部分类",
+        ));
+    }
+    renumber(config, parent_id, category_ids);
+    Ok(())
+}
+
+/// 把分类移到 `parent_id` 下，排在新同级分类之后；`parent_id` 为空时移为顶级分类
+pub fn move_category(
+    config: &mut Config,
+    category_id: &str,
+    parent_id: Option<&str>,
+) -> Result<(), AppError> {
+    ensure_category(config, category_id)?;
+    if let Some(parent_id) = parent_id {
+        ensure_category(config, parent_id)?;
+        if is_self_or_ancestor(config, category_id, parent_id) {
+            return Err(AppError::invalid_input(
+                "不能把分类移到它自己或它的子分类下",
+            ));
+        }
+    }
+
+    let old_parent = config.categories[category_id].parent_id.clone();
+    if old_parent.as_deref() == parent_id {
+        return Ok(());
+    }
+    let mut old_siblings = child_ids(config, old_parent.as_deref());
+    old_siblings.retain(|id| id != category_id);
+    renumber(config, old_parent.as_deref(), &old_siblings);
+
+    let mut new_siblings = child_ids(config, parent_id);
+    new_siblings.push(category_id.to_string());
+    renumber(config, parent_id, &new_siblings);
+    Ok(())
+}
+
+fn app_mut<'a>(config: &'a mut Config, app_id: &str) -> Result<&'a mut App, AppError> {
+    config
+        .apps
+        .get_mut(app_id)
+        .ok_or_else(|| AppError::not_found(format!("程序 {}", app_id)))
+}
+
+fn insert_member(config: &mut Config, category_id: &str, app_id: &str, index: Option<usize>) {
+    let apps = &mut config.categories.get_mut(category_id).unwrap().apps;
+    apps.retain(|id| id != app_id);
+    let index = index.unwrap_or(apps.len()).min(apps.len());
+    apps.insert(index, app_id.to_string());
+}
+
+/// 把程序的主分类改为另一个分类并排在 `index` 位置，`index` 为空或超出范围时排在最后；
+/// 也用于分类内调整顺序。程序同时出现的其他分类不变。
+pub fn move_app(
+    config: &mut Config,
+    app_id: &str,
+    category_id: &str,
+    index: Option<usize>,
+) -> Result<(), AppError> {
+    ensure_category(config, category_id)?;
+    let app = app_mut(config, app_id)?;
+    let old_category = std::mem::replace(&mut app.category, category_id.to_string());
+    app.extra_categories.retain(|id| id != category_id);
+
+    if old_category != category_id {
+        if let Some(category) = config.categories.get_mut(&old_category) {
+            category.apps.retain(|id| id != app_id);
+        }
+    }
+    insert_member(config, category_id, app_id, index);
+    Ok(())
+}
+
+/// 让程序同时出现在另一个分类中，排在该分类最后
+pub fn link_app(config: &mut Config, app_id: &str, category_id: &str) -> Result<(), AppError> {
+    ensure_category(config, category_id)?;
+    let app = app_mut(config, app_id)?;
+    if app.category == category_id || app.extra_categories.iter().any(|id| id == category_id) {
+        return Ok(());
+    }
+    app.extra_categories.push(category_id.to_string());
+    insert_member(config, category_id, app_id, None);
+    Ok(())
+}
+
+/// 取消程序与附加分类的关联；不能用来移出主分类
+pub fn unlink_app(config: &mut Config, app_id: &str, category_id: &str) -> Result<(), AppError> {
+    let app = app_mut(config, app_id)?;
+    if app.category == category_id {
+        return Err(AppError::invalid_input(
+            "不能把程序移出主分类，请改为移动到其他分类",
+        ));
+    }
+    app.extra_categories.retain(|id| id != category_id);
+    if let Some(category) = config.categories.get_mut(category_id) {
+        category.apps.retain(|id| id != app_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_category, child_ids, delete_category, link_app, move_app, move_category,
+        reorder_categories, unlink_app, CategoryAppsDisposal,
+    };
+    use crate::error::AppError;
+    use crate::models::{App, Config};
+
+    fn config_with_apps() -> (Config, String, String) {
+        let mut config = Config::default();
+        let work = add_category(&mut config, "工作", None).unwrap().id;
+        let tools = add_category(&mut config, "工具", None).unwrap().id;
+        for (app_id, category_id) in [("a", &work), ("b", &work), ("c", &tools)] {
+            let app: App = serde_json::from_value(serde_json::json!({
+                "id": app_id,
+                "name": app_id,
+                "path": format!("C:/{}.exe", app_id),
+                "category": category_id,
+                "createdAt": 1
+            }))
+            .unwrap();
+            config.apps.insert(app_id.to_string(), app);
+            config
+                .categories
+                .get_mut(category_id)
+                .unwrap()
+                .apps
+                .push(app_id.to_string());
+        }
+        (config, work, tools)
+    }
+
+    #[test]
+    fn 删除分类时移动或删除其中的程序并上移子分类() {
+        let (mut config, work, tools) = config_with_apps();
+        let child = add_category(&mut config, "子分类", Some(&work)).unwrap().id;
+        config.category_rules = vec![
+            serde_json::from_value(serde_json::json!({ "id": "r1", "itemType": "app", "categoryId": work })).unwrap(),
+            serde_json::from_value(serde_json::json!({ "id": "r2", "itemType": "url", "categoryId": tools, "tags": ["web"] })).unwrap(),
+            serde_json::from_value(serde_json::json!({ "id": "r3", "itemType": "file", "categoryId": tools })).unwrap(),
+        ];
+
+        let removed = delete_category(
+            &mut config,
+            &work,
+            &CategoryAppsDisposal::Move {
+                target_id: tools.clone(),
+            },
+        )
+        .expect("应能删除分类");
+        assert!(removed.is_empty());
+        assert_eq!(config.categories[&tools].apps, vec!["c", "a", "b"]);
+        assert_eq!(config.apps["a"].category, tools);
+        assert_eq!(config.categories[&child].parent_id, None);
+        assert_eq!(child_ids(&config, None), vec![tools.clone(), child.clone()]);
+        assert_eq!(config.category_rules[0].category_id, Some(tools.clone()));
+
+        let removed = delete_category(&mut config, &tools, &CategoryAppsDisposal::Delete)
+            .expect("应能连同程序删除分类");
+        assert_eq!(removed.len(), 3);
+        assert!(config.apps.is_empty());
+        assert_eq!(config.categories[&child].order, 0);
+        assert_eq!(config.category_rules.len(), 1);
+        assert_eq!(config.category_rules[0].id, "r2");
+        assert_eq!(config.category_rules[0].category_id, None);
+
+        assert!(matches!(
+            delete_category(&mut config, "missing", &CategoryAppsDisposal::Delete),
+            Err(AppError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn 移动程序时同步分类列表和所属分类() {
+        let (mut config, work, tools) = config_with_apps();
+
+        move_app(&mut config, "b", &tools, Some(0)).expect("应能移动程序");
+        assert_eq!(config.categories[&work].apps, vec!["a"]);
+        assert_eq!(config.categories[&tools].apps, vec!["b", "c"]);
+        assert_eq!(config.apps["b"].category, tools);
+
+        move_app(&mut config, "b", &tools, None).expect("应能在分类内调整顺序");
+        assert_eq!(config.categories[&tools].apps, vec!["c", "b"]);
+        assert!(move_app(&mut config, "b", "missing", None).is_err());
+        assert_eq!(config.apps["b"].category, tools);
+    }
+
+    #[test]
+    fn 程序可以同时出现在多个分类中() {
+        let (mut config, work, tools) = config_with_apps();
+
+        link_app(&mut config, "a", &tools).expect("应能关联分类");
+        link_app(&mut config, "a", &tools).expect("重复关联不报错");
+        assert_eq!(config.categories[&tools].apps, vec!["c", "a"]);
+        assert_eq!(config.apps["a"].extra_categories, vec![tools.clone()]);
+        assert!(unlink_app(&mut config, "a", &work).is_err());
+
+        move_app(&mut config, "c", &work, Some(0)).expect("应能移动主分类");
+        assert_eq!(
+            config.categories[&tools].apps,
+            vec!["a"],
+            "关联的程序留在分类中"
+        );
+
+        let removed = delete_category(&mut config, &tools, &CategoryAppsDisposal::Delete)
+            .expect("应能删除分类");
+        assert!(removed.is_empty(), "只是关联的程序不随分类删除");
+        assert!(config.apps["a"].extra_categories.is_empty());
+        assert_eq!(config.categories[&work].apps, vec!["c", "a", "b"]);
+
+        link_app(&mut config, "b", &work).expect("已在主分类中时忽略");
+        assert!(config.apps["b"].extra_categories.is_empty());
+    }
+
+    #[test]
+    fn 连同程序删除分类时也从关联的分类中移除() {
+        let (mut config, work, tools) = config_with_apps();
+        link_app(&mut config, "a", &tools).expect("应能关联分类");
+        assert_eq!(config.categories[&tools].apps, vec!["c", "a"]);
+
+        let removed = delete_category(&mut config, &work, &CategoryAppsDisposal::Delete)
+            .expect("应能连同程序删除分类");
+        assert_eq!(removed.len(), 2);
+        assert!(!config.apps.contains_key("a"));
+        assert_eq!(config.categories[&tools].apps, vec!["c"]);
+    }
+
+    #[test]
+    fn 嵌套分类不能成环且按同级重新排序() {
+        let (mut config, work, tools) = config_with_apps();
+        let child = add_category(&mut config, "子分类", Some(&work)).unwrap().id;
+
+        assert!(move_category(&mut config, &work, Some(&child)).is_err());
+        assert!(move_category(&mut config, &work, Some(&work)).is_err());
+
+        move_category(&mut config, &tools, Some(&work)).expect("应能移为子分类");
+        assert_eq!(
+            child_ids(&config, Some(&work)),
+            vec![child.clone(), tools.clone()]
+        );
+        assert_eq!(config.categories[&work].order, 0);
+
+        reorder_categories(&mut config, Some(&work), &[tools.clone(), child.clone()])
+            .expect("应能重新排序");
+        assert_eq!(config.categories[&tools].order, 0);
+        assert_eq!(config.categories[&child].order, 1);
+        assert!(
+            reorder_categories(&mut config, Some(&work), std::slice::from_ref(&tools)).is_err()
+        );
+        assert!(reorder_categories(&mut config, None, &[tools]).is_err());
+    }
+}
@@ -1,9 +1,15 @@
 use crate::models::Config;
+use crate::storage::backend::{self, StorageBackend};
+use crate::storage::error::StorageError;
+use crate::storage::events;
+use crate::storage::history;
+use crate::storage::paths::CONFIG_FILE;
+use crate::storage::persisted;
+use crate::storage::recovery;
+use crate::storage::schema::{self, CONFIG_SCHEMA};
+use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
-pub fn get_config_path() -> PathBuf {
-    crate::storage::paths::config_path()
-}
 
 /// 获取图标存储目录
 pub fn get_icons_dir() -> PathBuf {
@@ -16,20 +22,85 @@ pub fn get_icon_path(icon_filename: &str) -> PathBuf {
 }
 
 pub fn load_or_create_config() -> Config {
-    let config_path = get_config_path();
-
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            if let Ok(mut config) = serde_json::from_str::<Config>(&content) {
-                // 迁移旧的 base64 图标到文件存储
-                migrate_icons(&mut config);
-                return config;
-            }
+    let backend = match backend::current() {
+        Ok(backend) => backend,
+        Err(error) => {
+            eprintln!("打开数据存储失败: {}", error);
+            return Config::default();
+        }
+    };
+
+    let mut result = read_config(backend.as_ref());
+    if let Err(StorageError::Json(error)) = result {
+        let Some(config_path) = backend.file_path(CONFIG_FILE) else {
+            eprintln!("读取配置失败: {}", error);
+            return Config::default();
+        };
+        // 配置损坏时先隔离原文件并尝试从备份恢复，避免之后保存默认配置覆盖用户数据
+        let report = recovery::recover_corrupt_file(
+            &config_path,
+            &StorageError::Json(error).to_string(),
+            &|content| serde_json::from_slice::<Config>(content).is_ok(),
+        );
+        let restored = report.restored_from_backup.is_some();
+        recovery::record_report(report);
+        result = if restored {
+            read_config(backend.as_ref())
+        } else {
+            Ok(None)
+        };
+    }
+
+    match result {
+        Ok(Some(mut config)) => {
+            // 迁移旧的 base64 图标到文件存储
+            migrate_icons(&mut config);
+            config
         }
+        Ok(None) => Config::default(),
+        Err(error) => {
+            // 原文件保持不动，之后的保存会被 `ensure_config_not_newer` 拒绝，不会被默认配置覆盖
+            recovery::report_unreadable(CONFIG_FILE, &error.to_string());
+            Config::default()
+        }
+    }
+}
+
+/// 读取配置，旧结构版本会先备份再升级并回写；没有配置时返回 `None`
+pub fn read_config(backend: &dyn StorageBackend) -> Result<Option<Config>, StorageError> {
+    let Some(value) = backend.read(CONFIG_FILE)? else {
+        return Ok(None);
+    };
+    let version = schema::parse_config_version(&value)?;
+    let upgrade = CONFIG_SCHEMA.upgrade(version, value)?;
+    if !upgrade.upgraded() {
+        return Ok(Some(serde_json::from_value(upgrade.value)?));
     }
 
-    // 返回默认配置
-    Config::default()
+    let mut value = upgrade.value.clone();
+    value["version"] = Value::String(schema::config_version_string(upgrade.to_version));
+    let config: Config = serde_json::from_value(value)?;
+    schema::backup_before_upgrade(&CONFIG_SCHEMA, &upgrade)?;
+    backend.write(CONFIG_FILE, &serde_json::to_value(&config)?)?;
+    events::record_config_write();
+    schema::record_upgrade(&CONFIG_SCHEMA, &upgrade)?;
+    Ok(Some(config))
+}
+
+/// 已保存的配置由更新版本的程序写入、或版本号无法识别时拒绝覆盖，避免抹掉无法读取的数据
+fn ensure_config_not_newer(stored: Option<&Value>) -> Result<(), StorageError> {
+    let Some(value) = stored else {
+        return Ok(());
+    };
+    let found = schema::parse_config_version(value)?;
+    if found > CONFIG_SCHEMA.current_version() {
+        return Err(StorageError::SchemaTooNew {
+            name: CONFIG_SCHEMA.name.to_string(),
+            found,
+            supported: CONFIG_SCHEMA.current_version(),
+        });
+    }
+    Ok(())
 }
 
 /// 迁移 base64 图标到文件存储
@@ -76,17 +147,32 @@ fn migrate_icons(config: &mut Config) {
     }
 }
 
-pub fn save_config(config: &Config) -> Result<(), std::io::Error> {
-    let config_path = get_config_path();
-    let json = serde_json::to_string_pretty(config)?;
-    crate::storage::atomic_write::write_atomic(&config_path, json.as_bytes())
-        .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error.to_string()))?;
+pub fn save_config(config: &Config) -> Result<(), StorageError> {
+    save_config_value(&serde_json::to_value(config)?)
+}
+
+/// 写入配置的 JSON 内容，导入数据包时用它保留配置中未识别的字段。
+///
+/// 与其他数据的写入共用同一把锁，整体复制或替换数据期间不会写入配置；被覆盖的内容保留为历史版本。
+pub fn save_config_value(value: &Value) -> Result<(), StorageError> {
+    let _lock = persisted::lock_writes();
+    let backend = backend::current()?;
+    let stored = recovery::read_before_overwrite(backend.as_ref(), CONFIG_FILE, &|content| {
+        serde_json::from_slice::<Config>(content).is_ok()
+    })?;
+    ensure_config_not_newer(stored.as_ref())?;
+    history::record_previous_version(CONFIG_FILE, stored.as_ref(), value);
+    backend.write(CONFIG_FILE, value)?;
+    events::record_config_write();
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::load_or_create_config;
+    use super::{load_or_create_config, save_config};
+    use crate::models::Config;
+    use crate::storage::paths::CONFIG_FILE;
+    use crate::storage::recovery;
     use crate::storage::{json_store, paths};
     use base64::Engine;
     use std::fs;
@@ -153,4 +239,76 @@ mod tests {
 
         let _ = fs::remove_dir_all(data_dir);
     }
+
+    #[test]
+    fn 更新版本的配置不会被默认配置覆盖() {
+        let data_dir = unique_temp_dir("config-too-new");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).expect("应能创建测试数据目录");
+        let newer = r#"{"version":"99.0","categories":{},"apps":{"future-app":{}}}"#;
+        fs::write(paths::config_path(), newer).expect("应能写入新版本配置");
+
+        let loaded = load_or_create_config();
+        assert!(loaded.apps.is_empty());
+        assert!(save_config(&Config::default()).is_err());
+        assert_eq!(
+            fs::read_to_string(paths::config_path()).expect("应能读取原配置"),
+            newer
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 版本号无法识别的配置不会被覆盖并会报告() {
+        let data_dir = unique_temp_dir("config-bad-version");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).expect("应能创建测试数据目录");
+        let stored = r#"{"version":"abc","categories":{},"apps":{}}"#;
+        fs::write(paths::config_path(), stored).expect("应能写入配置");
+
+        let loaded = load_or_create_config();
+        assert!(loaded.apps.is_empty());
+        assert!(recovery::take_reports().iter().any(|report| {
+            report.file_name == CONFIG_FILE
+                && report.error.contains("abc")
+                && report.quarantined_to.is_none()
+        }));
+        assert!(save_config(&Config::default()).is_err());
+        assert_eq!(
+            fs::read_to_string(paths::config_path()).expect("应能读取原配置"),
+            stored
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
+
+    #[test]
+    fn 损坏的配置会被隔离并从备份恢复() {
+        let data_dir = unique_temp_dir("config-recovery");
+        let _guard = paths::set_test_data_dir(data_dir.clone());
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).expect("应能创建测试数据目录");
+        fs::write(
+            paths::config_path(),
+            r#"{"version":"1.0","categories":{},"apps":{"app-1":{"id":"app-1","name":"编辑器","path":"D:\\Tools\\editor.exe","category":"cat-1","itemType":"app","createdAt":1}}}"#,
+        )
+        .expect("应能写入配置");
+        crate::storage::backup::create_full_backup("测试备份").expect("备份应成功");
+        fs::write(paths::config_path(), "{\"version\":").expect("应能写入损坏配置");
+
+        let loaded = load_or_create_config();
+
+        assert!(loaded.apps.contains_key("app-1"));
+        assert_eq!(
+            fs::read_dir(paths::quarantine_dir())
+                .expect("应能读取隔离目录")
+                .count(),
+            1
+        );
+
+        let _ = fs::remove_dir_all(data_dir);
+    }
 }
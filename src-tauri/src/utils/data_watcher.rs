@@ -0,0 +1,100 @@
+use crate::storage::events;
+use crate::storage::paths;
+use crate::storage::persisted::PersistedDataType;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// 收到文件事件后等待的时间，合并原子写入产生的多次事件，也让程序自己的写入先记录内容指纹
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 正在使用的监视器及其监视的数据目录，数据目录移动后改为监视新目录
+static WATCHING: Mutex<Option<(RecommendedWatcher, Option<PathBuf>)>> = Mutex::new(None);
+
+/// 监视数据目录，数据文件或配置文件被程序之外的修改改变时通知所有窗口。
+///
+/// 使用 SQLite 存储时数据不以单独的文件保存，收到的文件事件会被忽略，见 `events::check_external_change`。
+pub fn start_data_watcher() {
+    let (sender, receiver) = mpsc::channel();
+    let watcher = match notify::recommended_watcher(sender) {
+        Ok(watcher) => watcher,
+        Err(error) => {
+            eprintln!("监视数据目录失败: {}", error);
+            return;
+        }
+    };
+    *WATCHING.lock().unwrap_or_else(PoisonError::into_inner) = Some((watcher, None));
+    if let Err(error) = watch_current_data_dir() {
+        eprintln!("监视数据目录失败: {}", error);
+    }
+    std::thread::spawn(move || handle_events(receiver));
+}
+
+/// 改为监视当前的数据目录，移动数据目录后调用；尚未开始监视时不做任何事
+pub fn watch_current_data_dir() -> notify::Result<()> {
+    let mut watching = WATCHING.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some((watcher, watched)) = watching.as_mut() else {
+        return Ok(());
+    };
+    let dir = paths::data_dir();
+    if watched.as_ref() == Some(&dir) {
+        return Ok(());
+    }
+    if let Some(previous) = watched.take() {
+        let _ = watcher.unwatch(&previous);
+    }
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    *watched = Some(dir);
+
+    // 记录当前内容，避免把已有文件当作外部修改
+    for data_type in PersistedDataType::ALL {
+        events::remember_current(data_type);
+    }
+    events::remember_current_config();
+    Ok(())
+}
+
+fn handle_events(receiver: mpsc::Receiver<notify::Result<notify::Event>>) {
+    while let Ok(first) = receiver.recv() {
+        std::thread::sleep(DEBOUNCE);
+        let mut changed = HashSet::new();
+        for result in std::iter::once(first).chain(receiver.try_iter()) {
+            match result {
+                Ok(event) => {
+                    changed.extend(event.paths.iter().filter_map(|path| watched_file_of(path)))
+                }
+                Err(error) => eprintln!("数据目录监视事件错误: {}", error),
+            }
+        }
+        for file in changed {
+            match file {
+                WatchedFile::Config => {
+                    events::check_external_config_change();
+                }
+                WatchedFile::Data(data_type) => {
+                    events::check_external_change(data_type);
+                }
+            }
+        }
+    }
+}
+
+/// 数据目录中需要检测外部修改的文件
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum WatchedFile {
+    Config,
+    Data(PersistedDataType),
+}
+
+fn watched_file_of(path: &Path) -> Option<WatchedFile> {
+    let file_name = path.file_name()?.to_str()?;
+    if file_name == paths::CONFIG_FILE {
+        return Some(WatchedFile::Config);
+    }
+    PersistedDataType::ALL
+        .into_iter()
+        .find(|data_type| data_type.file_name() == file_name)
+        .map(WatchedFile::Data)
+}
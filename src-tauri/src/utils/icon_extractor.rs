@@ -1,9 +1,10 @@
+use crate::error::AppError;
 use image::{ImageBuffer, Rgba};
 use std::fs;
 
 /// 提取图标并保存到文件，返回图标文件名
 #[cfg(target_os = "windows")]
-pub fn extract_icon_to_file(exe_path: &str, app_id: &str) -> Result<String, String> {
+pub fn extract_icon_to_file(exe_path: &str, app_id: &str) -> Result<String, AppError> {
     use std::ffi::OsStr;
     use std::os::windows::ffi::OsStrExt;
     use windows::core::PWSTR;
@@ -30,14 +31,14 @@ pub fn extract_icon_to_file(exe_path: &str, app_id: &str) -> Result<String, Stri
         );
 
         if hicon.is_invalid() {
-            return Err("无法提取图标".to_string());
+            return Err(AppError::internal("无法提取图标"));
         }
 
         // 获取图标信息
         let mut icon_info = ICONINFO::default();
         if GetIconInfo(hicon, &mut icon_info).is_err() {
             let _ = DestroyIcon(hicon);
-            return Err("无法获取图标信息".to_string());
+            return Err(AppError::internal("无法获取图标信息"));
         }
 
         // 获取位图尺寸
@@ -94,7 +95,7 @@ pub fn extract_icon_to_file(exe_path: &str, app_id: &str) -> Result<String, Stri
             let _ = DeleteObject(icon_info.hbmMask);
             let _ = DestroyIcon(hicon);
             let _ = DeleteDC(hdc);
-            return Err("无法获取位图数据".to_string());
+            return Err(AppError::internal("无法获取位图数据"));
         }
 
         // 处理 alpha 通道 - 从遮罩位图获取
@@ -130,7 +131,7 @@ pub fn extract_icon_to_file(exe_path: &str, app_id: &str) -> Result<String, Stri
 
         // 创建图像
         let img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_raw(width, height, pixels)
-            .ok_or_else(|| "Failed to create image buffer".to_string())?;
+            .ok_or_else(|| AppError::internal("Failed to create image buffer"))?;
 
         // 编码为 PNG
         let mut png_data: Vec<u8> = Vec::new();
@@ -138,26 +139,27 @@ pub fn extract_icon_to_file(exe_path: &str, app_id: &str) -> Result<String, Stri
             &mut std::io::Cursor::new(&mut png_data),
             image::ImageFormat::Png,
         )
-        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to encode PNG: {}", e)))?;
 
         // 保存到文件
         let icon_filename = format!("{}.png", app_id);
         let icon_path = crate::utils::config::get_icon_path(&icon_filename);
 
-        fs::write(&icon_path, &png_data).map_err(|e| format!("Failed to save icon: {}", e))?;
+        fs::write(&icon_path, &png_data)
+            .map_err(|e| AppError::internal(format!("Failed to save icon: {}", e)))?;
 
         Ok(icon_filename)
     }
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn extract_icon_to_file(_exe_path: &str, _app_id: &str) -> Result<String, String> {
-    Err("图标提取仅支持 Windows".to_string())
+pub fn extract_icon_to_file(_exe_path: &str, _app_id: &str) -> Result<String, AppError> {
+    Err(AppError::platform_unsupported("图标提取"))
 }
 
 /// 兼容旧接口：提取图标并返回 base64（已弃用，仅用于兼容）
 #[cfg(target_os = "windows")]
-pub fn extract_icon_from_exe(exe_path: &str) -> Result<String, String> {
+pub fn extract_icon_from_exe(exe_path: &str) -> Result<String, AppError> {
     use std::ffi::OsStr;
     use std::os::windows::ffi::OsStrExt;
     use windows::core::PWSTR;
@@ -284,7 +286,7 @@ pub fn extract_icon_from_exe(exe_path: &str) -> Result<String, String> {
 
         // 创建图像
         let img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_raw(width, height, pixels)
-            .ok_or_else(|| "Failed to create image buffer".to_string())?;
+            .ok_or_else(|| AppError::internal("Failed to create image buffer"))?;
 
         // 编码为 PNG
         let mut png_data: Vec<u8> = Vec::new();
@@ -292,7 +294,7 @@ pub fn extract_icon_from_exe(exe_path: &str) -> Result<String, String> {
             &mut std::io::Cursor::new(&mut png_data),
             image::ImageFormat::Png,
         )
-        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to encode PNG: {}", e)))?;
 
         // 转换为 base64
         use base64::Engine;
@@ -303,7 +305,7 @@ pub fn extract_icon_from_exe(exe_path: &str) -> Result<String, String> {
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn extract_icon_from_exe(_exe_path: &str) -> Result<String, String> {
+pub fn extract_icon_from_exe(_exe_path: &str) -> Result<String, AppError> {
     Ok(get_placeholder_icon())
 }
 
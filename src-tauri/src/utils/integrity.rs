@@ -0,0 +1,580 @@
+use crate::models::data::{self, ScenesData, TodosData};
+use crate::models::{Category, Config};
+use crate::storage::error::StorageError;
+use crate::storage::paths;
+use crate::storage::persisted::PersistedDataType;
+use crate::utils::categories;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// 修复时收纳分类缺失的程序
+pub const UNCATEGORIZED_ID: &str = "uncategorized";
+pub const UNCATEGORIZED_NAME: &str = "未分类";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IntegrityIssueKind {
+    /// 数据文件不符合预期结构，无法逐项检查
+    InvalidStructure,
+    /// `apps` 中条目的 `id` 与键不一致
+    AppIdMismatch,
+    /// 分类的 `apps` 列出了不存在的程序
+    CategoryAppMissing,
+    /// 程序的 `category` 或 `extraCategories` 指向不存在的分类
+    AppCategoryMissing,
+    /// 程序没有出现在所属或关联分类的 `apps` 中
+    AppNotInCategory,
+    /// 分类的上级分类不存在，或上级关系成环
+    CategoryParentInvalid,
+    /// 场景动作引用了已删除的程序
+    SceneAppMissing,
+    /// 待办的日期不是有效的 `YYYY-MM-DD`
+    TodoDateInvalid,
+    /// 待办的开始或结束时间不是有效的 `HH:MM`
+    TodoTimeInvalid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub file: String,
+    pub kind: IntegrityIssueKind,
+    /// 出问题的条目 id
+    pub item_id: String,
+    pub message: String,
+    pub repairable: bool,
+    pub repaired: bool,
+}
+
+impl IntegrityIssue {
+    fn new(
+        file: &str,
+        kind: IntegrityIssueKind,
+        item_id: &str,
+        message: String,
+        repairable: bool,
+        repair: bool,
+    ) -> Self {
+        Self {
+            file: file.to_string(),
+            kind,
+            item_id: item_id.to_string(),
+            message,
+            repairable,
+            repaired: repairable && repair,
+        }
+    }
+}
+
+/// 检查程序和分类之间的引用关系，`repair` 为 `true` 时就地修复。
+///
+/// 修复方式：以 `apps` 的键为准改正 `id`；从分类中移除不存在的程序；
+/// 分类不存在的程序移入“未分类”；把程序补回所属分类的列表；上级无效的分类改为顶级分类。
+pub fn check_config(config: &mut Config, repair: bool) -> Vec<IntegrityIssue> {
+    let file = paths::CONFIG_FILE;
+    let mut issues = Vec::new();
+
+    let mut app_ids: Vec<String> = config.apps.keys().cloned().collect();
+    app_ids.sort();
+    for key in &app_ids {
+        let app = config.apps.get_mut(key).unwrap();
+        if app.id != *key {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::AppIdMismatch,
+                key,
+                format!("程序 {} 的 id 为 {}，与键 {} 不一致", app.name, app.id, key),
+                true,
+                repair,
+            ));
+            if repair {
+                app.id = key.clone();
+            }
+        }
+    }
+
+    let mut category_ids: Vec<String> = config.categories.keys().cloned().collect();
+    category_ids.sort();
+    for category_id in &category_ids {
+        let category = &config.categories[category_id];
+        let Some(parent_id) = category.parent_id.as_deref() else {
+            continue;
+        };
+        if !config.categories.contains_key(parent_id)
+            || categories::is_self_or_ancestor(config, category_id, parent_id)
+        {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::CategoryParentInvalid,
+                category_id,
+                format!(
+                    "分类 {} 的上级分类 {} 不存在或形成循环",
+                    category.name, parent_id
+                ),
+                true,
+                repair,
+            ));
+            if repair {
+                let order = categories::child_ids(config, None).len();
+                let category = config.categories.get_mut(category_id).unwrap();
+                category.parent_id = None;
+                category.order = order;
+            }
+        }
+    }
+
+    for category_id in &category_ids {
+        let category = config.categories.get_mut(category_id).unwrap();
+        for app_id in &category.apps {
+            if !config.apps.contains_key(app_id) {
+                issues.push(IntegrityIssue::new(
+                    file,
+                    IntegrityIssueKind::CategoryAppMissing,
+                    category_id,
+                    format!("分类 {} 中的程序 {} 不存在", category.name, app_id),
+                    true,
+                    repair,
+                ));
+            }
+        }
+        if repair {
+            category
+                .apps
+                .retain(|app_id| config.apps.contains_key(app_id));
+        }
+    }
+
+    for key in &app_ids {
+        let app = &config.apps[key];
+        let Some(category) = config.categories.get(&app.category) else {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::AppCategoryMissing,
+                key,
+                format!("程序 {} 所属的分类 {} 不存在", app.name, app.category),
+                true,
+                repair,
+            ));
+            if repair {
+                let category_id = ensure_uncategorized(config);
+                config.apps.get_mut(key).unwrap().category = category_id.clone();
+                config
+                    .categories
+                    .get_mut(&category_id)
+                    .unwrap()
+                    .apps
+                    .push(key.clone());
+            }
+            continue;
+        };
+        if !category.apps.contains(key) {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::AppNotInCategory,
+                key,
+                format!("程序 {} 不在所属分类 {} 的列表中", app.name, category.name),
+                true,
+                repair,
+            ));
+            if repair {
+                let category_id = app.category.clone();
+                config
+                    .categories
+                    .get_mut(&category_id)
+                    .unwrap()
+                    .apps
+                    .push(key.clone());
+            }
+        }
+    }
+
+    for key in &app_ids {
+        let app = &config.apps[key];
+        let mut dangling = Vec::new();
+        let mut unlisted = Vec::new();
+        for category_id in &app.extra_categories {
+            match config.categories.get(category_id) {
+                None => dangling.push(category_id.clone()),
+                Some(category) if !category.apps.contains(key) => {
+                    unlisted.push(category_id.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        for category_id in &dangling {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::AppCategoryMissing,
+                key,
+                format!("程序 {} 关联的分类 {} 不存在", app.name, category_id),
+                true,
+                repair,
+            ));
+        }
+        for category_id in &unlisted {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::AppNotInCategory,
+                key,
+                format!(
+                    "程序 {} 不在关联分类 {} 的列表中",
+                    app.name, config.categories[category_id].name
+                ),
+                true,
+                repair,
+            ));
+        }
+        if repair {
+            config
+                .apps
+                .get_mut(key)
+                .unwrap()
+                .extra_categories
+                .retain(|category_id| !dangling.contains(category_id));
+            for category_id in unlisted {
+                config
+                    .categories
+                    .get_mut(&category_id)
+                    .unwrap()
+                    .apps
+                    .push(key.clone());
+            }
+        }
+    }
+
+    issues
+}
+
+/// 返回“未分类”分类的 id，不存在时创建
+pub fn ensure_uncategorized(config: &mut Config) -> String {
+    if let Some(category) = config
+        .categories
+        .values()
+        .find(|category| category.id == UNCATEGORIZED_ID || category.name == UNCATEGORIZED_NAME)
+    {
+        return category.id.clone();
+    }
+    let category = Category {
+        id: UNCATEGORIZED_ID.to_string(),
+        name: UNCATEGORIZED_NAME.to_string(),
+        icon: None,
+        apps: Vec::new(),
+        order: config.categories.len(),
+        parent_id: None,
+    };
+    config
+        .categories
+        .insert(category.id.clone(), category.clone());
+    category.id
+}
+
+/// 检查场景动作引用的程序是否存在，修复时删除引用已删除程序的动作
+pub fn check_scenes(
+    scenes: &mut Value,
+    app_ids: &HashSet<&str>,
+    repair: bool,
+) -> Vec<IntegrityIssue> {
+    let file = paths::SCENES_FILE;
+    let mut parsed: ScenesData = match data::parse(PersistedDataType::Scenes, scenes) {
+        Ok(parsed) => parsed,
+        Err(error) => return vec![invalid_structure(file, error)],
+    };
+
+    let mut issues = Vec::new();
+    for scene in &mut parsed.scenes {
+        let missing: Vec<String> = scene
+            .actions
+            .iter()
+            .filter_map(|action| action.params.app_id.clone())
+            .filter(|app_id| !app_ids.contains(app_id.as_str()))
+            .collect();
+        for app_id in &missing {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::SceneAppMissing,
+                &scene.id,
+                format!("场景 {} 的动作引用了已删除的程序 {}", scene.name, app_id),
+                true,
+                repair,
+            ));
+        }
+        if repair {
+            scene.actions.retain(|action| {
+                action
+                    .params
+                    .app_id
+                    .as_ref()
+                    .is_none_or(|app_id| !missing.contains(app_id))
+            });
+        }
+    }
+    write_back(scenes, &parsed, &issues);
+    issues
+}
+
+/// 检查待办的日期和时间格式，修复时去掉无效的时间；日期无效时无法推断，只报告
+pub fn check_todos(todos: &mut Value, repair: bool) -> Vec<IntegrityIssue> {
+    let file = paths::TODOS_FILE;
+    let mut parsed: TodosData = match data::parse(PersistedDataType::Todos, todos) {
+        Ok(parsed) => parsed,
+        Err(error) => return vec![invalid_structure(file, error)],
+    };
+
+    let mut issues = Vec::new();
+    for todo in &mut parsed.items {
+        if !is_valid_date(&todo.date) {
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::TodoDateInvalid,
+                &todo.id,
+                format!("待办 {} 的日期无效: {}", todo.title, todo.date),
+                false,
+                repair,
+            ));
+        }
+        for time in [&mut todo.start_time, &mut todo.end_time] {
+            let Some(value) = time.as_deref().filter(|value| !value.is_empty()) else {
+                continue;
+            };
+            if is_valid_time(value) {
+                continue;
+            }
+            issues.push(IntegrityIssue::new(
+                file,
+                IntegrityIssueKind::TodoTimeInvalid,
+                &todo.id,
+                format!("待办 {} 的时间无效: {}", todo.title, value),
+                true,
+                repair,
+            ));
+            if repair {
+                *time = None;
+            }
+        }
+    }
+    write_back(todos, &parsed, &issues);
+    issues
+}
+
+/// 有问题被修复时用修复后的数据替换原值，未识别的字段随结构一起写回
+fn write_back<T: Serialize>(target: &mut Value, parsed: &T, issues: &[IntegrityIssue]) {
+    if !issues.iter().any(|issue| issue.repaired) {
+        return;
+    }
+    if let Ok(value) = serde_json::to_value(parsed) {
+        *target = value;
+    }
+}
+
+/// 只检查数据是否符合该类数据的结构，用于没有逐项检查的便签、剪贴板和动作等数据
+pub fn check_structure(data_type: PersistedDataType, data: &Value) -> Vec<IntegrityIssue> {
+    match data::validate(data_type, data) {
+        Ok(()) => Vec::new(),
+        Err(error) => vec![invalid_structure(data_type.file_name(), error)],
+    }
+}
+
+fn invalid_structure(file: &str, error: StorageError) -> IntegrityIssue {
+    IntegrityIssue::new(
+        file,
+        IntegrityIssueKind::InvalidStructure,
+        "",
+        error.to_string(),
+        false,
+        false,
+    )
+}
+
+fn parse_digits(value: &str) -> Option<u32> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// `YYYY-MM-DD` 且是日历上存在的日期
+fn is_valid_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Some(year), Some(month), Some(day)) =
+        (parse_digits(year), parse_digits(month), parse_digits(day))
+    else {
+        return false;
+    };
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+/// `HH:MM`，24 小时制
+fn is_valid_time(value: &str) -> bool {
+    let Some((hour, minute)) = value.split_once(':') else {
+        return false;
+    };
+    hour.len() == 2
+        && minute.len() == 2
+        && parse_digits(hour).is_some_and(|hour| hour < 24)
+        && parse_digits(minute).is_some_and(|minute| minute < 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_config, check_scenes, check_structure, check_todos, is_valid_date,
+        IntegrityIssueKind, UNCATEGORIZED_ID,
+    };
+    use crate::models::Config;
+    use crate::storage::persisted::PersistedDataType;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn broken_config() -> Config {
+        serde_json::from_value(json!({
+            "categories": {
+                "tools": { "id": "tools", "name": "工具", "apps": ["a", "ghost"], "order": 0 },
+                "x": { "id": "x", "name": "X", "apps": [], "order": 0, "parentId": "y" },
+                "y": { "id": "y", "name": "Y", "apps": [], "order": 0, "parentId": "x" }
+            },
+            "apps": {
+                "a": { "id": "a", "name": "A", "path": "C:\\a.exe", "category": "tools", "extraCategories": ["x", "gone"], "createdAt": 1 },
+                "b": { "id": "old-b", "name": "B", "path": "C:\\b.exe", "category": "tools", "createdAt": 1 },
+                "c": { "id": "c", "name": "C", "path": "C:\\c.exe", "category": "deleted", "createdAt": 1 }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn 检查并修复程序与分类的引用() {
+        let mut config = broken_config();
+        let issues = check_config(&mut config, false);
+        let kinds: Vec<IntegrityIssueKind> = issues.iter().map(|issue| issue.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                IntegrityIssueKind::AppIdMismatch,
+                IntegrityIssueKind::CategoryParentInvalid,
+                IntegrityIssueKind::CategoryParentInvalid,
+                IntegrityIssueKind::CategoryAppMissing,
+                IntegrityIssueKind::AppNotInCategory,
+                IntegrityIssueKind::AppCategoryMissing,
+                IntegrityIssueKind::AppCategoryMissing,
+                IntegrityIssueKind::AppNotInCategory,
+            ]
+        );
+        assert!(issues.iter().all(|issue| !issue.repaired));
+        assert_eq!(config.apps["b"].id, "old-b", "只检查时不修改数据");
+
+        let repaired = check_config(&mut config, true);
+        assert!(repaired.iter().all(|issue| issue.repaired));
+        assert_eq!(config.apps["b"].id, "b");
+        assert_eq!(config.categories["tools"].apps, vec!["a", "b"]);
+        assert_eq!(config.apps["c"].category, UNCATEGORIZED_ID);
+        assert_eq!(config.categories[UNCATEGORIZED_ID].apps, vec!["c"]);
+        assert_eq!(config.categories["x"].parent_id, None);
+        assert_eq!(config.apps["a"].extra_categories, vec!["x"]);
+        assert_eq!(config.categories["x"].apps, vec!["a"]);
+        assert_eq!(
+            config.categories["y"].parent_id.as_deref(),
+            Some("x"),
+            "断开循环后其余分类保留上级"
+        );
+        assert!(check_config(&mut config, false).is_empty());
+    }
+
+    #[test]
+    fn 删除场景中引用已删除程序的动作() {
+        let mut scenes = json!({
+            "scenes": [{
+                "id": "morning",
+                "name": "上班",
+                "icon": "sun",
+                "actions": [
+                    { "id": "1", "type": "launch", "params": { "appId": "a" } },
+                    { "id": "2", "type": "launch", "params": { "appId": "gone" } },
+                    { "id": "3", "type": "delay", "params": { "seconds": 2 } }
+                ]
+            }]
+        });
+        let app_ids = HashSet::from(["a"]);
+
+        let issues = check_scenes(&mut scenes, &app_ids, true);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IntegrityIssueKind::SceneAppMissing);
+        assert_eq!(issues[0].item_id, "morning");
+        let ids: Vec<&str> = scenes["scenes"][0]["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|action| action["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["1", "3"]);
+        assert_eq!(scenes["scenes"][0]["icon"], "sun", "保留其余字段");
+
+        let mut invalid = json!({ "scenes": "oops" });
+        assert_eq!(
+            check_scenes(&mut invalid, &app_ids, true)[0].kind,
+            IntegrityIssueKind::InvalidStructure
+        );
+    }
+
+    #[test]
+    fn 报告格式错误的待办日期并去掉无效时间() {
+        let mut todos = json!({
+            "items": [
+                { "id": "ok", "title": "正常", "date": "2024-02-29", "startTime": "09:00", "createdAt": 1, "updatedAt": 1 },
+                { "id": "bad-date", "title": "日期", "date": "2023-02-29", "createdAt": 1, "updatedAt": 1 },
+                { "id": "bad-time", "title": "时间", "date": "2024-01-01", "startTime": "25:00", "endTime": "", "createdAt": 1, "updatedAt": 1, "tags": ["x"] }
+            ]
+        });
+
+        let issues = check_todos(&mut todos, true);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].kind, IntegrityIssueKind::TodoDateInvalid);
+        assert!(!issues[0].repaired, "无效日期无法自动修复");
+        assert_eq!(issues[1].kind, IntegrityIssueKind::TodoTimeInvalid);
+        assert!(issues[1].repaired);
+        assert!(todos["items"][2].get("startTime").is_none());
+        assert_eq!(todos["items"][2]["tags"], json!(["x"]), "保留未识别的字段");
+        assert_eq!(todos["items"][0]["startTime"], "09:00");
+
+        assert!(!is_valid_date("2024-13-01"));
+        assert!(!is_valid_date("2024-1-01"));
+        assert!(is_valid_date("2000-02-29"));
+    }
+
+    #[test]
+    fn 报告结构无效的便签和剪贴板数据() {
+        let notes = json!({ "notes": [{ "id": "n1", "content": "缺少时间" }] });
+        let issues = check_structure(PersistedDataType::Notes, &notes);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].file, PersistedDataType::Notes.file_name());
+        assert_eq!(issues[0].kind, IntegrityIssueKind::InvalidStructure);
+        assert!(
+            issues[0].message.contains("notes[0]"),
+            "{}",
+            issues[0].message
+        );
+        assert!(!issues[0].repairable);
+
+        let clipboard = json!({ "items": [{ "id": "c1", "content": "x", "contentType": "text", "createdAt": 1 }] });
+        assert!(check_structure(PersistedDataType::Clipboard, &clipboard).is_empty());
+        let clipboard = json!({ "items": {} });
+        assert_eq!(
+            check_structure(PersistedDataType::Clipboard, &clipboard)[0].kind,
+            IntegrityIssueKind::InvalidStructure
+        );
+    }
+}
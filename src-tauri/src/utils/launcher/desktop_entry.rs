@@ -0,0 +1,309 @@
+use crate::error::AppError;
+
+/// `.desktop` 文件 `[Desktop Entry]` 组中启动时用到的键
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DesktopEntry {
+    pub entry_type: String,
+    pub name: String,
+    pub exec: Option<String>,
+    pub path: Option<String>,
+    pub icon: Option<String>,
+    pub terminal: bool,
+    pub url: Option<String>,
+}
+
+impl DesktopEntry {
+    /// 只读取 `[Desktop Entry]` 组，`[Desktop Action ...]` 等其他组和 `Name[zh_CN]` 这类本地化的键都会忽略
+    pub fn parse(content: &str) -> Result<Self, AppError> {
+        let mut entry = DesktopEntry::default();
+        let mut in_entry_group = false;
+        let mut has_entry_group = false;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_entry_group = line == "[Desktop Entry]";
+                has_entry_group |= in_entry_group;
+                continue;
+            }
+            if !in_entry_group {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = unescape(value.trim_start());
+            let non_empty = |value: String| Some(value).filter(|value| !value.is_empty());
+            match key.trim_end() {
+                "Type" => entry.entry_type = value,
+                "Name" => entry.name = value,
+                "Exec" => entry.exec = non_empty(value),
+                "Path" => entry.path = non_empty(value),
+                "Icon" => entry.icon = non_empty(value),
+                "Terminal" => entry.terminal = value == "true",
+                "URL" => entry.url = non_empty(value),
+                _ => {}
+            }
+        }
+        if !has_entry_group {
+            return Err(AppError::invalid_input(
+                "不是有效的 .desktop 文件：缺少 [Desktop Entry] 组",
+            ));
+        }
+        Ok(entry)
+    }
+
+    /// 展开 `Exec` 中的字段代码，得到要运行的程序和参数。
+    ///
+    /// `files` 对应 `%f %F %u %U`，`%f %u` 只取第一个；`Exec` 中没有这些字段代码时追加在最后。
+    pub fn command_line(
+        &self,
+        desktop_file: &str,
+        files: &[String],
+    ) -> Result<Vec<String>, AppError> {
+        let exec = self
+            .exec
+            .as_deref()
+            .ok_or_else(|| AppError::invalid_input(".desktop 文件缺少 Exec"))?;
+        let mut argv = Vec::new();
+        let mut uses_files = false;
+        for arg in split_exec(exec)? {
+            // 规范不允许在加引号的参数中使用字段代码，按原样传递
+            if arg.quoted {
+                argv.push(arg.text);
+                continue;
+            }
+            match arg.text.as_str() {
+                "%F" | "%U" => {
+                    uses_files = true;
+                    argv.extend(files.iter().cloned());
+                    continue;
+                }
+                "%i" => {
+                    if let Some(icon) = &self.icon {
+                        argv.push("--icon".to_string());
+                        argv.push(icon.clone());
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let mut expanded = String::new();
+            let mut has_field_code = false;
+            let mut chars = arg.text.chars();
+            while let Some(ch) = chars.next() {
+                if ch != '%' {
+                    expanded.push(ch);
+                    continue;
+                }
+                let code = chars.next();
+                has_field_code |= code != Some('%');
+                match code {
+                    Some('%') => expanded.push('%'),
+                    Some('f' | 'u') => {
+                        uses_files = true;
+                        if let Some(file) = files.first() {
+                            expanded.push_str(file);
+                        }
+                    }
+                    Some('c') => expanded.push_str(&self.name),
+                    Some('k') => expanded.push_str(desktop_file),
+                    // 已废弃的字段代码，规范要求忽略
+                    Some('d' | 'D' | 'n' | 'N' | 'v' | 'm') => {}
+                    Some(code @ ('F' | 'U' | 'i')) => {
+                        return Err(AppError::invalid_input(format!(
+                            "Exec 中的 %{} 只能单独作为一个参数",
+                            code
+                        )));
+                    }
+                    Some(code) => {
+                        return Err(AppError::invalid_input(format!(
+                            "Exec 中有无效的字段代码 %{}",
+                            code
+                        )));
+                    }
+                    None => return Err(AppError::invalid_input("Exec 以单独的 % 结尾")),
+                }
+            }
+            // 只由字段代码组成且展开为空的参数整个去掉
+            if !(has_field_code && expanded.is_empty()) {
+                argv.push(expanded);
+            }
+        }
+        if !uses_files {
+            argv.extend(files.iter().cloned());
+        }
+        if argv.first().is_none_or(|program| program.is_empty()) {
+            return Err(AppError::invalid_input(".desktop 文件的 Exec 中没有程序"));
+        }
+        Ok(argv)
+    }
+}
+
+/// 字符串类型的值中 `\s \n \t \r \\` 表示的字符
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+#[derive(Default)]
+struct ExecArg {
+    text: String,
+    quoted: bool,
+}
+
+/// 按空格拆分 `Exec`；双引号内的空格不拆分，`\" \` \$ \\` 表示引号内的这些字符
+fn split_exec(exec: &str) -> Result<Vec<ExecArg>, AppError> {
+    let unterminated = || AppError::invalid_input(format!("Exec 中的引号没有闭合: {}", exec));
+    let mut args = Vec::new();
+    let mut current: Option<ExecArg> = None;
+    let mut chars = exec.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ' ' | '\t' => args.extend(current.take()),
+            '"' => {
+                let arg = current.get_or_insert_with(ExecArg::default);
+                arg.quoted = true;
+                loop {
+                    match chars.next().ok_or_else(unterminated)? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or_else(unterminated)? {
+                            escaped @ ('"' | '`' | '$' | '\\') => arg.text.push(escaped),
+                            other => {
+                                arg.text.push('\\');
+                                arg.text.push(other);
+                            }
+                        },
+                        other => arg.text.push(other),
+                    }
+                }
+            }
+            _ => current.get_or_insert_with(ExecArg::default).text.push(ch),
+        }
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DesktopEntry;
+
+    fn files(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn 只读取主组中未本地化的键并处理转义() {
+        let entry = DesktopEntry::parse(
+            r#"# 注释
+[Desktop Entry]
+Type=Application
+Name=Text Editor
+Name[zh_CN]=文本编辑器
+Exec = "/opt/My Editor/bin/editor" --title "say \\"hi\\"" %F
+Path=/opt/My\sEditor
+Icon=editor
+Terminal=false
+
+[Desktop Action new-window]
+Name=New Window
+Exec=editor --new-window
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(entry.entry_type, "Application");
+        assert_eq!(entry.name, "Text Editor");
+        assert_eq!(entry.path.as_deref(), Some("/opt/My Editor"));
+        assert_eq!(entry.icon.as_deref(), Some("editor"));
+        assert!(!entry.terminal);
+        assert_eq!(
+            entry
+                .command_line("/a.desktop", &files(&["/tmp/a b.txt", "/tmp/c.txt"]))
+                .unwrap(),
+            vec![
+                "/opt/My Editor/bin/editor",
+                "--title",
+                r#"say "hi""#,
+                "/tmp/a b.txt",
+                "/tmp/c.txt"
+            ]
+        );
+
+        assert!(DesktopEntry::parse("Exec=editor").is_err());
+    }
+
+    #[test]
+    fn 展开字段代码() {
+        let entry = DesktopEntry {
+            entry_type: "Application".to_string(),
+            name: "Browser".to_string(),
+            exec: Some("browser --class=%c %i %k --url=%u 100%% %d \"%f\"".to_string()),
+            icon: Some("web".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            entry
+                .command_line("/apps/browser.desktop", &files(&["https://a.test", "b"]))
+                .unwrap(),
+            vec![
+                "browser",
+                "--class=Browser",
+                "--icon",
+                "web",
+                "/apps/browser.desktop",
+                "--url=https://a.test",
+                "100%",
+                "%f"
+            ]
+        );
+
+        // 没有文件时单独的 %f 去掉；Exec 不接收文件时参数追加在最后
+        let entry = DesktopEntry {
+            exec: Some("tool %f".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(entry.command_line("", &[]).unwrap(), vec!["tool"]);
+        let entry = DesktopEntry {
+            exec: Some("tool --verbose".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            entry
+                .command_line("", &files(&["--profile", "work"]))
+                .unwrap(),
+            vec!["tool", "--verbose", "--profile", "work"]
+        );
+
+        for exec in ["tool %x", "tool --files=%F", "tool %", "\"tool", "%f"] {
+            let entry = DesktopEntry {
+                exec: Some(exec.to_string()),
+                ..Default::default()
+            };
+            assert!(entry.command_line("", &[]).is_err(), "{:?}", exec);
+        }
+    }
+}
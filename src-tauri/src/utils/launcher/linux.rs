@@ -0,0 +1,168 @@
+use super::desktop_entry::DesktopEntry;
+use super::Launcher;
+use crate::error::AppError;
+use crate::models::{App, ItemType};
+use std::ffi::OsString;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+
+/// 可执行文件直接运行，`.desktop` 启动器按 `Exec` 运行，其余交给 `xdg-open`
+pub struct LinuxLauncher;
+
+/// 启动后在后台线程等待子进程退出，避免留下僵尸进程
+fn spawn(mut command: Command) -> Result<(), AppError> {
+    let mut child = command.spawn().map_err(|e| {
+        AppError::internal(format!(
+            "启动 {} 失败: {}",
+            command.get_program().to_string_lossy(),
+            e
+        ))
+    })?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// 以 `-` 开头的相对路径会被 xdg-open 当成选项，前面补上 `./`
+fn xdg_open(target: &str) -> Command {
+    let target = if target.starts_with('-') {
+        OsString::from(format!("./{}", target))
+    } else {
+        OsString::from(target)
+    };
+    let mut command = Command::new("xdg-open");
+    command.arg(target);
+    command
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+fn is_desktop_file(app: &App) -> bool {
+    app.item_type == ItemType::App
+        && Path::new(&app.path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("desktop"))
+}
+
+/// `Terminal=true` 的启动器在终端模拟器中运行：优先使用 `$TERMINAL`，否则使用 Debian 系的 `x-terminal-emulator`，
+/// 两者都支持以 `-e` 后跟要运行的命令
+fn terminal_emulator() -> OsString {
+    std::env::var_os("TERMINAL")
+        .filter(|terminal| !terminal.is_empty())
+        .unwrap_or_else(|| OsString::from("x-terminal-emulator"))
+}
+
+fn apply_options(command: &mut Command, app: &App, default_dir: Option<&str>) {
+    if let Some(dir) = app.working_dir.as_deref().or(default_dir) {
+        command.current_dir(dir);
+    }
+    command.envs(&app.env);
+}
+
+/// 启动参数作为 `%f %u` 等字段代码对应的文件传入；工作目录未设置时使用 `Path`
+fn launch_desktop_entry(app: &App) -> Result<(), AppError> {
+    let entry = DesktopEntry::parse(&std::fs::read_to_string(&app.path)?)?;
+    match entry.entry_type.as_str() {
+        "Application" => {}
+        "Link" => {
+            let url = entry
+                .url
+                .ok_or_else(|| AppError::invalid_input(".desktop 链接缺少 URL"))?;
+            return spawn(xdg_open(&url));
+        }
+        other => {
+            return Err(AppError::invalid_input(format!(
+                "不支持的 .desktop 类型: {}",
+                other
+            )));
+        }
+    }
+    spawn(desktop_entry_command(&entry, app)?)
+}
+
+fn desktop_entry_command(entry: &DesktopEntry, app: &App) -> Result<Command, AppError> {
+    let argv = entry.command_line(&app.path, &app.args)?;
+    let mut command = if entry.terminal {
+        let mut command = Command::new(terminal_emulator());
+        command.arg("-e").args(&argv);
+        command
+    } else {
+        let mut command = Command::new(&argv[0]);
+        command.args(&argv[1..]);
+        command
+    };
+    apply_options(&mut command, app, entry.path.as_deref());
+    Ok(command)
+}
+
+impl Launcher for LinuxLauncher {
+    /// 窗口状态由窗口管理器决定，这里忽略 `window_state`，见 `Launcher::launch`
+    fn launch(&self, app: &App) -> Result<(), AppError> {
+        if is_desktop_file(app) {
+            return launch_desktop_entry(app);
+        }
+        let path = Path::new(&app.path);
+        let mut command = if app.item_type == ItemType::App && is_executable(path) {
+            let mut command = Command::new(path);
+            command.args(&app.args);
+            command
+        } else if app.args.is_empty() {
+            xdg_open(&app.path)
+        } else {
+            return Err(AppError::invalid_input(
+                "启动参数只能用于可执行文件和 .desktop 启动器",
+            ));
+        };
+        apply_options(&mut command, app, None);
+        spawn(command)
+    }
+
+    fn open(&self, target: &str) -> Result<(), AppError> {
+        spawn(xdg_open(target))
+    }
+
+    fn reveal(&self, path: &str) -> Result<(), AppError> {
+        let parent = Path::new(path)
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .ok_or_else(|| AppError::internal("无法获取父目录"))?;
+        spawn(xdg_open(&parent.to_string_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{desktop_entry_command, terminal_emulator};
+    use crate::utils::launcher::desktop_entry::DesktopEntry;
+    use crate::utils::launcher::tests::app;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn 需要终端的启动器在终端模拟器中运行() {
+        let mut entry = DesktopEntry {
+            entry_type: "Application".to_string(),
+            exec: Some("htop --tree".to_string()),
+            path: Some("/tmp".to_string()),
+            ..Default::default()
+        };
+        let target = app("/usr/share/applications/htop.desktop", "app");
+
+        let command = desktop_entry_command(&entry, &target).unwrap();
+        assert_eq!(command.get_program(), "htop");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["--tree"]);
+        assert_eq!(command.get_current_dir(), Some("/tmp".as_ref()));
+
+        entry.terminal = true;
+        let command = desktop_entry_command(&entry, &target).unwrap();
+        assert_eq!(command.get_program(), terminal_emulator());
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["-e", "htop", "--tree"]
+        );
+    }
+}
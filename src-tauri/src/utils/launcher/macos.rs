@@ -0,0 +1,52 @@
+use super::Launcher;
+use crate::error::AppError;
+use crate::models::{App, ItemType};
+use std::path::Path;
+use std::process::Command;
+
+/// 可执行文件直接运行，应用包和其他文件交给 `open`
+pub struct MacLauncher;
+
+fn spawn(mut command: Command) -> Result<(), AppError> {
+    let mut child = command
+        .spawn()
+        .map_err(|e| AppError::internal(format!("启动失败: {}", e)))?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+impl Launcher for MacLauncher {
+    fn launch(&self, app: &App) -> Result<(), AppError> {
+        let mut command = if app.item_type == ItemType::App && Path::new(&app.path).is_file() {
+            let mut command = Command::new(&app.path);
+            command.args(&app.args);
+            command
+        } else {
+            let mut command = Command::new("open");
+            command.arg(&app.path);
+            if !app.args.is_empty() {
+                command.arg("--args").args(&app.args);
+            }
+            command
+        };
+        if let Some(dir) = &app.working_dir {
+            command.current_dir(dir);
+        }
+        command.envs(&app.env);
+        spawn(command)
+    }
+
+    fn open(&self, target: &str) -> Result<(), AppError> {
+        let mut command = Command::new("open");
+        command.arg(target);
+        spawn(command)
+    }
+
+    fn reveal(&self, path: &str) -> Result<(), AppError> {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        spawn(command)
+    }
+}
@@ -0,0 +1,63 @@
+use super::{LaunchOptions, Launcher};
+use crate::error::AppError;
+use crate::models::App;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaunchCall {
+    Launch {
+        app_id: String,
+        elevated: bool,
+        options: LaunchOptions,
+    },
+    Open(String),
+    Reveal(String),
+}
+
+/// 只记录调用、不启动任何进程的启动器，供测试使用
+#[derive(Default)]
+pub struct MockLauncher {
+    calls: Mutex<Vec<LaunchCall>>,
+}
+
+impl MockLauncher {
+    pub fn calls(&self) -> Vec<LaunchCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn record_launch(&self, app: &App, elevated: bool) {
+        self.calls.lock().unwrap().push(LaunchCall::Launch {
+            app_id: app.id.clone(),
+            elevated,
+            options: LaunchOptions::of(app),
+        });
+    }
+}
+
+impl Launcher for MockLauncher {
+    fn launch(&self, app: &App) -> Result<(), AppError> {
+        self.record_launch(app, false);
+        Ok(())
+    }
+
+    fn launch_elevated(&self, app: &App) -> Result<(), AppError> {
+        self.record_launch(app, true);
+        Ok(())
+    }
+
+    fn open(&self, target: &str) -> Result<(), AppError> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(LaunchCall::Open(target.to_string()));
+        Ok(())
+    }
+
+    fn reveal(&self, path: &str) -> Result<(), AppError> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(LaunchCall::Reveal(path.to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+#[cfg(any(all(unix, not(target_os = "macos")), test))]
+mod desktop_entry;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(test)]
+mod mock;
+#[cfg(any(target_os = "windows", test))]
+mod win32;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use linux::LinuxLauncher;
+#[cfg(target_os = "macos")]
+pub use macos::MacLauncher;
+#[cfg(test)]
+pub use mock::{LaunchCall, MockLauncher};
+#[cfg(target_os = "windows")]
+pub use win32::Win32Launcher;
+
+use crate::error::AppError;
+use crate::models::{App, WindowState};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 程序的启动选项，对应 `App` 中的同名字段
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOptions {
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub window_state: Option<WindowState>,
+}
+
+impl LaunchOptions {
+    pub fn of(app: &App) -> Self {
+        Self {
+            args: app.args.clone(),
+            working_dir: app.working_dir.clone(),
+            env: app.env.clone(),
+            window_state: app.window_state.clone(),
+        }
+    }
+
+    /// 检查后写入程序；空白的工作目录视为未设置
+    pub fn apply_to(mut self, app: &mut App) -> Result<(), AppError> {
+        self.working_dir = self
+            .working_dir
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty());
+        self.validate()?;
+        app.args = self.args;
+        app.working_dir = self.working_dir;
+        app.env = self.env;
+        app.window_state = self.window_state;
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        if self.args.iter().any(|arg| arg.contains('\0')) {
+            return Err(AppError::invalid_input("启动参数不能包含空字符"));
+        }
+        if self
+            .working_dir
+            .as_deref()
+            .is_some_and(|dir| dir.contains('\0'))
+        {
+            return Err(AppError::invalid_input("工作目录不能包含空字符"));
+        }
+        for (key, value) in &self.env {
+            if key.is_empty() || key.contains(['=', '\0']) {
+                return Err(AppError::invalid_input(format!(
+                    "环境变量名无效: {:?}",
+                    key
+                )));
+            }
+            if value.contains('\0') {
+                return Err(AppError::invalid_input(format!(
+                    "环境变量 {} 的值不能包含空字符",
+                    key
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 各平台启动程序和打开文件的方式。
+///
+/// 命令通过 `AppState` 中的实例调用，测试时替换为 `MockLauncher`。
+pub trait Launcher: Send + Sync {
+    /// 按程序的启动参数、工作目录、环境变量和窗口状态启动。
+    ///
+    /// 窗口状态只是建议：Windows 按其显示新窗口；Linux 和 macOS 无法可靠地控制其他程序的窗口，
+    /// 会忽略该值并照常启动，不会因此返回错误。
+    fn launch(&self, app: &App) -> Result<(), AppError>;
+
+    /// 以管理员身份启动，只有 Windows 支持
+    fn launch_elevated(&self, _app: &App) -> Result<(), AppError> {
+        Err(AppError::platform_unsupported("管理员启动"))
+    }
+
+    /// 用系统关联的程序打开文件、文件夹或网址
+    fn open(&self, target: &str) -> Result<(), AppError>;
+
+    /// 在文件管理器中显示文件所在的位置
+    fn reveal(&self, path: &str) -> Result<(), AppError>;
+}
+
+/// 当前平台的启动器
+pub fn system() -> Arc<dyn Launcher> {
+    #[cfg(target_os = "windows")]
+    let launcher = Win32Launcher;
+    #[cfg(target_os = "macos")]
+    let launcher = MacLauncher;
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let launcher = LinuxLauncher;
+    Arc::new(launcher)
+}
+
+fn check_working_dir(app: &App) -> Result<(), AppError> {
+    if let Some(dir) = &app.working_dir {
+        if !Path::new(dir).is_dir() {
+            return Err(AppError::not_found(format!("工作目录 {}", dir)));
+        }
+    }
+    Ok(())
+}
+
+/// 检查工作目录后交给启动器；`elevated` 为 true 时以管理员身份运行
+pub fn launch(launcher: &dyn Launcher, app: &App, elevated: bool) -> Result<(), AppError> {
+    check_working_dir(app)?;
+    if elevated {
+        launcher.launch_elevated(app)
+    } else {
+        launcher.launch(app)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{launch, LaunchCall, LaunchOptions, MockLauncher};
+    use crate::error::AppError;
+    use crate::models::{App, WindowState};
+    use std::collections::BTreeMap;
+
+    pub(super) fn app(path: &str, item_type: &str) -> App {
+        serde_json::from_value(serde_json::json!({
+            "id": "a",
+            "name": "a",
+            "path": path,
+            "category": "c",
+            "itemType": item_type,
+            "createdAt": 1
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn 保存启动选项前检查取值() {
+        let mut target = app(r"C:\a\b.exe", "app");
+        let options = LaunchOptions {
+            args: vec!["--profile".to_string(), "a b".to_string()],
+            working_dir: Some("  ".to_string()),
+            env: BTreeMap::from([("MODE".to_string(), "work".to_string())]),
+            window_state: Some(WindowState::Maximized),
+        };
+        options.apply_to(&mut target).unwrap();
+        assert_eq!(target.args, vec!["--profile", "a b"]);
+        assert_eq!(target.working_dir, None);
+        assert_eq!(target.window_state, Some(WindowState::Maximized));
+        assert_eq!(LaunchOptions::of(&target).env["MODE"], "work");
+
+        for env_key in ["", "A=B", "A\0"] {
+            let options = LaunchOptions {
+                env: BTreeMap::from([(env_key.to_string(), "1".to_string())]),
+                ..Default::default()
+            };
+            assert!(options.apply_to(&mut target).is_err(), "{:?}", env_key);
+        }
+        let options = LaunchOptions {
+            args: vec!["a\0b".to_string()],
+            ..Default::default()
+        };
+        assert!(options.apply_to(&mut target).is_err());
+        assert_eq!(target.args, vec!["--profile", "a b"]);
+    }
+
+    #[test]
+    fn 工作目录不存在时不交给启动器() {
+        let launcher = MockLauncher::default();
+        let mut target = app("/opt/tool/run", "app");
+        target.working_dir = Some("/nonexistent/program-manager-dir".to_string());
+        assert!(matches!(
+            launch(&launcher, &target, false),
+            Err(AppError::NotFound { .. })
+        ));
+        assert!(launcher.calls().is_empty());
+
+        target.working_dir = Some(std::env::temp_dir().to_string_lossy().to_string());
+        launch(&launcher, &target, true).unwrap();
+        assert_eq!(
+            launcher.calls(),
+            vec![LaunchCall::Launch {
+                app_id: "a".to_string(),
+                elevated: true,
+                options: LaunchOptions::of(&target),
+            }]
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use crate::error::AppError;
+use crate::models::{App, ItemType};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::Path;
+
+#[cfg(target_os = "windows")]
+pub use native::Win32Launcher;
+
+/// 启动方式：可执行程序直接创建进程，批处理交给 cmd，其余交给系统关联的程序打开
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchKind {
+    Executable,
+    Batch,
+    Shell,
+}
+
+pub fn launch_kind(app: &App) -> LaunchKind {
+    if app.item_type != ItemType::App {
+        return LaunchKind::Shell;
+    }
+    let extension = Path::new(&app.path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("exe" | "com") => LaunchKind::Executable,
+        Some("bat" | "cmd") => LaunchKind::Batch,
+        _ => LaunchKind::Shell,
+    }
+}
+
+/// 按 `CommandLineToArgvW` 的规则给单个参数加引号，使目标程序拆分后得到原样的参数
+pub fn quote_windows_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\u{b}', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for ch in arg.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(ch);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// 拼接参数部分的命令行
+pub fn windows_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| quote_windows_arg(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 可执行程序的完整命令行，程序路径总是加引号（路径中不会出现引号）
+pub fn executable_command_line(path: &str, args: &[String]) -> String {
+    let mut command_line = format!("\"{}\"", path);
+    if !args.is_empty() {
+        command_line.push(' ');
+        command_line.push_str(&windows_args(args));
+    }
+    command_line
+}
+
+/// 批处理经由 `cmd /c` 运行，cmd 会再次解释 `%`、`&`、`|` 等字符，
+/// 因此参数中不允许出现这些字符，避免参数被当成命令执行
+pub fn batch_command_line(path: &str, args: &[String]) -> Result<String, AppError> {
+    const CMD_SPECIAL: [char; 10] = ['%', '!', '&', '|', '<', '>', '^', '"', '\r', '\n'];
+    if let Some(arg) = args.iter().find(|arg| arg.contains(CMD_SPECIAL)) {
+        return Err(AppError::invalid_input(format!(
+            "批处理的启动参数不能包含 % ! & | < > ^ \" 或换行: {}",
+            arg
+        )));
+    }
+    Ok(format!(
+        "cmd.exe /d /s /c \"{}\"",
+        executable_command_line(path, args)
+    ))
+}
+
+/// 在当前环境变量上追加或覆盖 `overrides`，变量名不区分大小写，结果按变量名排序
+pub fn merge_env(
+    base: impl IntoIterator<Item = (OsString, OsString)>,
+    overrides: &BTreeMap<String, String>,
+) -> Vec<(OsString, OsString)> {
+    let upper = |key: &OsString| key.to_string_lossy().to_uppercase();
+    let mut merged: Vec<(OsString, OsString)> = base
+        .into_iter()
+        .filter(|(key, _)| {
+            let key = upper(key);
+            !overrides
+                .keys()
+                .any(|override_key| override_key.to_uppercase() == key)
+        })
+        .collect();
+    merged.extend(
+        overrides
+            .iter()
+            .map(|(key, value)| (OsString::from(key), OsString::from(value))),
+    );
+    merged.sort_by_key(|(key, _)| upper(key));
+    merged
+}
+
+#[cfg(target_os = "windows")]
+mod native {
+    use super::{
+        batch_command_line, executable_command_line, launch_kind, merge_env, windows_args,
+        LaunchKind,
+    };
+    use crate::error::AppError;
+    use crate::models::{App, WindowState};
+    use crate::utils::launcher::Launcher;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::{HSTRING, PCWSTR, PWSTR};
+    use windows::Win32::Foundation::{CloseHandle, BOOL};
+    use windows::Win32::System::Threading::{
+        CreateProcessW, CREATE_UNICODE_ENVIRONMENT, PROCESS_INFORMATION, STARTF_USESHOWWINDOW,
+        STARTUPINFOW,
+    };
+    use windows::Win32::UI::Shell::{
+        ShellExecuteExW, SEE_MASK_FLAG_NO_UI, SEE_MASK_NOASYNC, SHELLEXECUTEINFOW,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SHOW_WINDOW_CMD, SW_SHOWMAXIMIZED, SW_SHOWMINNOACTIVE, SW_SHOWNORMAL,
+    };
+
+    /// 可执行程序和批处理用 `CreateProcessW` 启动，其余交给 `ShellExecuteExW`
+    pub struct Win32Launcher;
+
+    fn show_command(window_state: Option<&WindowState>) -> SHOW_WINDOW_CMD {
+        match window_state {
+            Some(WindowState::Minimized) => SW_SHOWMINNOACTIVE,
+            Some(WindowState::Maximized) => SW_SHOWMAXIMIZED,
+            _ => SW_SHOWNORMAL,
+        }
+    }
+
+    fn wide(value: &str) -> Vec<u16> {
+        value.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    fn optional_pcwstr(value: Option<&HSTRING>) -> PCWSTR {
+        value.map_or(PCWSTR::null(), |value| PCWSTR(value.as_ptr()))
+    }
+
+    /// `KEY=VALUE\0...\0\0` 形式的 UTF-16 环境块
+    fn environment_block(app: &App) -> Vec<u16> {
+        let mut block = Vec::new();
+        for (key, value) in merge_env(std::env::vars_os(), &app.env) {
+            block.extend(key.encode_wide());
+            block.push('=' as u16);
+            block.extend(value.encode_wide());
+            block.push(0);
+        }
+        block.push(0);
+        block
+    }
+
+    fn create_process(
+        app: &App,
+        application: Option<&str>,
+        command_line: &str,
+    ) -> Result<(), AppError> {
+        let application = application.map(HSTRING::from);
+        let working_dir = app.working_dir.as_deref().map(HSTRING::from);
+        let mut command_line = wide(command_line);
+        let environment = (!app.env.is_empty()).then(|| environment_block(app));
+
+        let startup_info = STARTUPINFOW {
+            cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+            dwFlags: STARTF_USESHOWWINDOW,
+            wShowWindow: show_command(app.window_state.as_ref()).0 as u16,
+            ..Default::default()
+        };
+        let mut process_info = PROCESS_INFORMATION::default();
+
+        unsafe {
+            CreateProcessW(
+                optional_pcwstr(application.as_ref()),
+                PWSTR(command_line.as_mut_ptr()),
+                None,
+                None,
+                BOOL::from(false),
+                CREATE_UNICODE_ENVIRONMENT,
+                environment
+                    .as_ref()
+                    .map(|block| block.as_ptr() as *const std::ffi::c_void),
+                optional_pcwstr(working_dir.as_ref()),
+                &startup_info,
+                &mut process_info,
+            )
+            .map_err(|e| AppError::internal(format!("启动失败: {}", e)))?;
+            let _ = CloseHandle(process_info.hThread);
+            let _ = CloseHandle(process_info.hProcess);
+        }
+        Ok(())
+    }
+
+    fn shell_execute(
+        verb: &str,
+        file: &str,
+        parameters: Option<&str>,
+        working_dir: Option<&str>,
+        show: SHOW_WINDOW_CMD,
+    ) -> Result<(), AppError> {
+        let verb = HSTRING::from(verb);
+        let file = HSTRING::from(file);
+        let parameters = parameters.map(HSTRING::from);
+        let working_dir = working_dir.map(HSTRING::from);
+
+        let mut info = SHELLEXECUTEINFOW {
+            cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+            fMask: SEE_MASK_NOASYNC | SEE_MASK_FLAG_NO_UI,
+            lpVerb: PCWSTR(verb.as_ptr()),
+            lpFile: PCWSTR(file.as_ptr()),
+            lpParameters: optional_pcwstr(parameters.as_ref()),
+            lpDirectory: optional_pcwstr(working_dir.as_ref()),
+            nShow: show.0,
+            ..Default::default()
+        };
+        unsafe { ShellExecuteExW(&mut info) }
+            .map_err(|e| AppError::internal(format!("启动失败: {}", e)))
+    }
+
+    fn shell_execute_app(app: &App, verb: &str) -> Result<(), AppError> {
+        if !app.env.is_empty() {
+            return Err(AppError::invalid_input(
+                "环境变量只能用于直接启动的 .exe、.com 程序和批处理，以管理员身份运行时也不能设置",
+            ));
+        }
+        let parameters = (!app.args.is_empty()).then(|| windows_args(&app.args));
+        shell_execute(
+            verb,
+            &app.path,
+            parameters.as_deref(),
+            app.working_dir.as_deref(),
+            show_command(app.window_state.as_ref()),
+        )
+    }
+
+    impl Launcher for Win32Launcher {
+        fn launch(&self, app: &App) -> Result<(), AppError> {
+            match launch_kind(app) {
+                LaunchKind::Executable => create_process(
+                    app,
+                    Some(&app.path),
+                    &executable_command_line(&app.path, &app.args),
+                ),
+                // 不指定程序名时 CreateProcess 会在系统目录中找到 cmd.exe
+                LaunchKind::Batch => {
+                    create_process(app, None, &batch_command_line(&app.path, &app.args)?)
+                }
+                LaunchKind::Shell => shell_execute_app(app, "open"),
+            }
+        }
+
+        fn launch_elevated(&self, app: &App) -> Result<(), AppError> {
+            shell_execute_app(app, "runas")
+        }
+
+        fn open(&self, target: &str) -> Result<(), AppError> {
+            shell_execute("open", target, None, None, SW_SHOWNORMAL)
+        }
+
+        fn reveal(&self, path: &str) -> Result<(), AppError> {
+            std::process::Command::new("explorer")
+                .args(["/select,", path])
+                .spawn()
+                .map_err(|e| AppError::internal(format!("打开文件位置失败: {}", e)))?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        batch_command_line, executable_command_line, launch_kind, merge_env, quote_windows_arg,
+        LaunchKind,
+    };
+    use crate::utils::launcher::tests::app;
+    use std::collections::BTreeMap;
+    use std::ffi::OsString;
+
+    #[test]
+    fn 参数按命令行拆分规则加引号() {
+        let cases = [
+            ("plain", "plain"),
+            ("", "\"\""),
+            ("with space", "\"with space\""),
+            (r#"say "hi""#, r#""say \"hi\"""#),
+            (r"C:\Program Files\", r#""C:\Program Files\\""#),
+            (r"a\\b", r"a\\b"),
+            (r#"a\"b"#, r#""a\\\"b""#),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(quote_windows_arg(arg), expected, "参数 {:?}", arg);
+        }
+
+        let args = vec!["--profile".to_string(), "Work Profile".to_string()];
+        assert_eq!(
+            executable_command_line(r"C:\Apps\chrome.exe", &args),
+            r#""C:\Apps\chrome.exe" --profile "Work Profile""#
+        );
+        assert_eq!(
+            batch_command_line(r"C:\Tools\run.bat", &args).unwrap(),
+            r#"cmd.exe /d /s /c ""C:\Tools\run.bat" --profile "Work Profile"""#
+        );
+        assert!(batch_command_line(r"C:\run.bat", &["a & calc".to_string()]).is_err());
+        assert!(batch_command_line(r"C:\run.bat", &["%PATH%".to_string()]).is_err());
+    }
+
+    #[test]
+    fn 按条目类型和扩展名选择启动方式() {
+        assert_eq!(
+            launch_kind(&app(r"C:\a\b.EXE", "app")),
+            LaunchKind::Executable
+        );
+        assert_eq!(launch_kind(&app(r"C:\a\b.cmd", "app")), LaunchKind::Batch);
+        assert_eq!(launch_kind(&app(r"C:\a\b.lnk", "app")), LaunchKind::Shell);
+        assert_eq!(launch_kind(&app(r"C:\a\b.exe", "file")), LaunchKind::Shell);
+        assert_eq!(launch_kind(&app(r"C:\a", "folder")), LaunchKind::Shell);
+    }
+
+    #[test]
+    fn 环境变量不区分大小写地覆盖并排序() {
+        let base = vec![
+            (OsString::from("Path"), OsString::from("C:/old")),
+            (OsString::from("TEMP"), OsString::from("C:/tmp")),
+        ];
+        let overrides = BTreeMap::from([
+            ("PATH".to_string(), "C:/new".to_string()),
+            ("APP_MODE".to_string(), "work".to_string()),
+        ]);
+        let merged: Vec<(String, String)> = merge_env(base, &overrides)
+            .into_iter()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().to_string(),
+                    value.to_string_lossy().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            merged,
+            vec![
+                ("APP_MODE".to_string(), "work".to_string()),
+                ("PATH".to_string(), "C:/new".to_string()),
+                ("TEMP".to_string(), "C:/tmp".to_string()),
+            ]
+        );
+    }
+}
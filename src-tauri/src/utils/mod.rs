@@ -1,6 +1,14 @@
 pub mod app_validator;
+pub mod backup_scheduler;
+pub mod categories;
 pub mod config;
+pub mod data_watcher;
 pub mod icon_extractor;
+pub mod integrity;
+pub mod launcher;
+pub mod path_remap;
 pub mod registry;
+pub mod rules;
 pub mod shortcuts;
+pub mod tags;
 pub mod update_checker;
@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::path::Path;
+
+/// 导入数据包时改写程序路径的规则，按顺序依次应用
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PathRemapRule {
+    /// 把以 `from` 开头的路径改为以 `to` 开头；忽略大小写，`/` 与 `\` 视为相同
+    Prefix { from: String, to: String },
+    /// 更换盘符，`from` 和 `to` 可写作 `D` 或 `D:`
+    Drive { from: String, to: String },
+    /// 展开 `%USERPROFILE%` 等环境变量以及开头的 `~`
+    EnvVars,
+}
+
+/// 自动探测得到的规则建议
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PathRemapSuggestion {
+    pub rule: PathRemapRule,
+    /// 应用规则后在本机存在的路径数量
+    pub matched: usize,
+    pub example_from: String,
+    pub example_to: String,
+}
+
+pub fn apply_rules(path: &str, rules: &[PathRemapRule]) -> String {
+    apply_rules_with(path, rules, &env_var)
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn apply_rules_with(
+    path: &str,
+    rules: &[PathRemapRule],
+    env: &dyn Fn(&str) -> Option<String>,
+) -> String {
+    rules
+        .iter()
+        .fold(path.to_string(), |path, rule| apply_rule(&path, rule, env))
+}
+
+fn apply_rule(path: &str, rule: &PathRemapRule, env: &dyn Fn(&str) -> Option<String>) -> String {
+    match rule {
+        PathRemapRule::Prefix { from, to } => match strip_path_prefix(path, from) {
+            Some("") => to.clone(),
+            Some(rest) => format!("{}{}", to.trim_end_matches(['\\', '/']), rest),
+            None => path.to_string(),
+        },
+        PathRemapRule::Drive { from, to } => {
+            match (drive_letter(path), parse_drive(from), parse_drive(to)) {
+                (Some(current), Some(from), Some(to)) if current.eq_ignore_ascii_case(&from) => {
+                    format!("{}{}", to.to_ascii_uppercase(), &path[1..])
+                }
+                _ => path.to_string(),
+            }
+        }
+        PathRemapRule::EnvVars => expand_env_vars(path, env),
+    }
+}
+
+/// 匹配成功时返回去掉前缀后的剩余部分（以分隔符开头或为空），前缀必须在路径分段处结束
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let prefix = prefix.trim_end_matches(['\\', '/']);
+    if prefix.is_empty() || path.len() < prefix.len() || !path.is_char_boundary(prefix.len()) {
+        return None;
+    }
+    let (head, rest) = path.split_at(prefix.len());
+    let same = head
+        .chars()
+        .zip(prefix.chars())
+        .all(|(a, b)| normalize_char(a) == normalize_char(b));
+    (same && (rest.is_empty() || rest.starts_with(['\\', '/']))).then_some(rest)
+}
+
+fn normalize_char(c: char) -> char {
+    if c == '/' {
+        '\\'
+    } else {
+        c.to_ascii_lowercase()
+    }
+}
+
+fn drive_letter(path: &str) -> Option<char> {
+    let mut chars = path.chars();
+    let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+    (chars.next() == Some(':')).then_some(letter)
+}
+
+fn parse_drive(value: &str) -> Option<char> {
+    let value = value.trim().trim_end_matches(['\\', '/']);
+    let value = value.strip_suffix(':').unwrap_or(value);
+    let mut chars = value.chars();
+    let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+    chars.next().is_none().then_some(letter)
+}
+
+fn home_dir(env: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+    env("USERPROFILE").or_else(|| env("HOME"))
+}
+
+/// 展开 `%NAME%` 形式的环境变量和开头的 `~`，未定义的变量保持原样
+fn expand_env_vars(path: &str, env: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            break;
+        };
+        let name = &after[..end];
+        expanded.push_str(&rest[..start]);
+        match env(name).filter(|_| !name.is_empty()) {
+            Some(value) => expanded.push_str(&value),
+            None => {
+                expanded.push('%');
+                expanded.push_str(name);
+                expanded.push('%');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    if let Some(rest) = expanded.strip_prefix('~') {
+        if rest.is_empty() || rest.starts_with(['\\', '/']) {
+            if let Some(home) = home_dir(env) {
+                return format!("{}{}", home.trim_end_matches(['\\', '/']), rest);
+            }
+        }
+    }
+    expanded
+}
+
+/// 针对本机不存在的路径探测可用的改写规则：环境变量展开、更换盘符、替换用户目录。
+///
+/// 只保留至少能让一个路径在本机找到的规则，按命中数量从多到少排列。
+pub fn suggest_rules(paths: &[String]) -> Vec<PathRemapSuggestion> {
+    suggest_rules_with(paths, &env_var, &|path| Path::new(path).exists())
+}
+
+fn suggest_rules_with(
+    paths: &[String],
+    env: &dyn Fn(&str) -> Option<String>,
+    exists: &dyn Fn(&str) -> bool,
+) -> Vec<PathRemapSuggestion> {
+    let missing: Vec<&String> = paths.iter().filter(|path| !exists(path)).collect();
+    let mut candidates: Vec<PathRemapRule> = Vec::new();
+    let mut push = |rule: PathRemapRule| {
+        if !candidates.contains(&rule) {
+            candidates.push(rule);
+        }
+    };
+
+    for path in &missing {
+        if path.contains('%') || path.starts_with('~') {
+            push(PathRemapRule::EnvVars);
+        }
+        if let Some(current) = drive_letter(path) {
+            for letter in 'C'..='Z' {
+                if !letter.eq_ignore_ascii_case(&current) {
+                    push(PathRemapRule::Drive {
+                        from: current.to_ascii_uppercase().to_string(),
+                        to: letter.to_string(),
+                    });
+                }
+            }
+        }
+        if let (Some(profile), Some(home)) = (user_profile_prefix(path), home_dir(env)) {
+            if strip_path_prefix(&home, &profile).is_none() {
+                push(PathRemapRule::Prefix {
+                    from: profile,
+                    to: home,
+                });
+            }
+        }
+    }
+
+    let mut suggestions: Vec<PathRemapSuggestion> = candidates
+        .into_iter()
+        .filter_map(|rule| {
+            let rules = std::slice::from_ref(&rule);
+            let mut example = None;
+            let mut matched = 0;
+            for path in &missing {
+                let rewritten = apply_rules_with(path, rules, env);
+                if rewritten != **path && exists(&rewritten) {
+                    matched += 1;
+                    example.get_or_insert_with(|| (path.to_string(), rewritten));
+                }
+            }
+            let (example_from, example_to) = example?;
+            Some(PathRemapSuggestion {
+                rule,
+                matched,
+                example_from,
+                example_to,
+            })
+        })
+        .collect();
+    suggestions.sort_by_key(|suggestion| Reverse(suggestion.matched));
+    suggestions
+}
+
+/// 识别 `C:\Users\<用户名>` 或 `/home/<用户名>` 形式的用户目录前缀
+fn user_profile_prefix(path: &str) -> Option<String> {
+    let separator_at = |index: usize| path[index..].find(['\\', '/']).map(|offset| index + offset);
+    let root_end = if drive_letter(path).is_some() { 2 } else { 0 };
+    let users_start = root_end + 1;
+    if !path[root_end..].starts_with(['\\', '/']) || path.len() <= users_start {
+        return None;
+    }
+    let users_end = separator_at(users_start)?;
+    let folder = &path[users_start..users_end];
+    if !folder.eq_ignore_ascii_case("users") && folder != "home" {
+        return None;
+    }
+    let name_end = separator_at(users_end + 1).unwrap_or(path.len());
+    (name_end > users_end + 1).then(|| path[..name_end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_rules_with, suggest_rules_with, PathRemapRule};
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "USERPROFILE" => Some("C:\\Users\\bob".to_string()),
+            "APPDATA" => Some("C:\\Users\\bob\\AppData\\Roaming".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn 按顺序应用前缀盘符和环境变量规则() {
+        let prefix = PathRemapRule::Prefix {
+            from: "d:/tools".to_string(),
+            to: "E:\\Apps\\".to_string(),
+        };
+        assert_eq!(
+            apply_rules_with("D:\\Tools\\editor.exe", std::slice::from_ref(&prefix), &env),
+            "E:\\Apps\\editor.exe"
+        );
+        assert_eq!(
+            apply_rules_with("D:\\ToolsOld\\editor.exe", &[prefix], &env),
+            "D:\\ToolsOld\\editor.exe",
+            "前缀必须在路径分段处结束"
+        );
+
+        let drive = PathRemapRule::Drive {
+            from: "d:".to_string(),
+            to: "F".to_string(),
+        };
+        assert_eq!(
+            apply_rules_with("d:\\Games\\game.exe", &[drive], &env),
+            "F:\\Games\\game.exe"
+        );
+
+        let rules = [PathRemapRule::EnvVars];
+        assert_eq!(
+            apply_rules_with("%APPDATA%\\app\\run.exe", &rules, &env),
+            "C:\\Users\\bob\\AppData\\Roaming\\app\\run.exe"
+        );
+        assert_eq!(
+            apply_rules_with("~\\bin\\tool.exe", &rules, &env),
+            "C:\\Users\\bob\\bin\\tool.exe"
+        );
+        assert_eq!(
+            apply_rules_with("%MISSING%\\a.exe", &rules, &env),
+            "%MISSING%\\a.exe"
+        );
+    }
+
+    #[test]
+    fn 探测本机存在的路径给出规则建议() {
+        let existing = [
+            "E:\\Tools\\editor.exe",
+            "E:\\Tools\\terminal.exe",
+            "C:\\Users\\bob\\Desktop\\notes.txt",
+        ];
+        let exists = |path: &str| existing.contains(&path);
+        let paths = vec![
+            "D:\\Tools\\editor.exe".to_string(),
+            "D:\\Tools\\terminal.exe".to_string(),
+            "C:\\Users\\alice\\Desktop\\notes.txt".to_string(),
+            "C:\\Windows\\missing.exe".to_string(),
+        ];
+
+        let suggestions = suggest_rules_with(&paths, &env, &exists);
+
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(
+            suggestions[0].rule,
+            PathRemapRule::Drive {
+                from: "D".to_string(),
+                to: "E".to_string()
+            }
+        );
+        assert_eq!(suggestions[0].matched, 2);
+        assert_eq!(
+            suggestions[1].rule,
+            PathRemapRule::Prefix {
+                from: "C:\\Users\\alice".to_string(),
+                to: "C:\\Users\\bob".to_string()
+            }
+        );
+        assert_eq!(
+            suggestions[1].example_to,
+            "C:\\Users\\bob\\Desktop\\notes.txt"
+        );
+    }
+}
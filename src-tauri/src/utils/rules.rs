@@ -0,0 +1,522 @@
+use crate::error::AppError;
+use crate::models::{App, CategoryRule, Config, ItemType};
+use crate::utils::{categories, tags};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// 规则匹配的结果：第一条指定了分类的规则决定分类，标签取所有匹配规则的并集
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub category_id: Option<String>,
+    pub tags: Vec<String>,
+    pub rule_ids: Vec<String>,
+}
+
+/// 对已有程序重新应用规则时会发生的变化
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleChange {
+    pub app_id: String,
+    pub app_name: String,
+    pub from_category: String,
+    pub to_category: String,
+    pub added_tags: Vec<String>,
+    pub rule_ids: Vec<String>,
+}
+
+struct CompiledRule<'a> {
+    rule: &'a CategoryRule,
+    path_glob: Option<Regex>,
+    file_name_pattern: Option<Regex>,
+}
+
+pub struct RuleSet<'a> {
+    rules: Vec<CompiledRule<'a>>,
+}
+
+/// 通配符转为正则：`**` 可以跨越目录，`*` 和 `?` 不跨越目录；其余字符按原样匹配
+fn glob_to_regex(glob: &str) -> String {
+    let glob = glob.replace('\\', "/");
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            _ => pattern.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+fn build_regex(rule: &CategoryRule, pattern: &str, field: &str) -> Result<Regex, AppError> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|error| {
+            AppError::invalid_input(format!(
+                "规则 {} 的{}无效: {}",
+                rule_label(rule),
+                field,
+                error
+            ))
+        })
+}
+
+fn rule_label(rule: &CategoryRule) -> &str {
+    if rule.name.trim().is_empty() {
+        &rule.id
+    } else {
+        &rule.name
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+fn compile_rule(rule: &CategoryRule) -> Result<CompiledRule<'_>, AppError> {
+    Ok(CompiledRule {
+        rule,
+        path_glob: rule
+            .path_glob
+            .as_deref()
+            .map(|glob| build_regex(rule, &glob_to_regex(glob), "路径通配符"))
+            .transpose()?,
+        file_name_pattern: rule
+            .file_name_pattern
+            .as_deref()
+            .map(|pattern| build_regex(rule, pattern, "文件名正则"))
+            .transpose()?,
+    })
+}
+
+impl<'a> RuleSet<'a> {
+    /// 编译规则中的通配符和正则表达式，停用的规则不参与匹配
+    pub fn compile(rules: &'a [CategoryRule]) -> Result<Self, AppError> {
+        let compiled = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .map(compile_rule)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules: compiled })
+    }
+
+    /// 与 `compile` 相同，但跳过无法编译的规则并记录日志，一条坏规则不会让其余规则失效
+    pub fn compile_valid(rules: &'a [CategoryRule]) -> Self {
+        let compiled = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match compile_rule(rule) {
+                Ok(compiled) => Some(compiled),
+                Err(error) => {
+                    eprintln!("跳过无效的自动分类规则: {}", error);
+                    None
+                }
+            })
+            .collect();
+        Self { rules: compiled }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 读取发布者较慢，只有规则用到时才需要查询
+    pub fn uses_publisher(&self) -> bool {
+        self.rules.iter().any(|rule| rule.rule.publisher.is_some())
+    }
+
+    /// 按顺序匹配规则；指向已不存在分类的规则只添加标签
+    pub fn evaluate(
+        &self,
+        config: &Config,
+        path: &str,
+        item_type: &ItemType,
+        publisher: Option<&str>,
+    ) -> RuleMatch {
+        let path = normalize_path(path);
+        let file_name = path.rsplit('/').next().unwrap_or(&path);
+        let mut result = RuleMatch::default();
+
+        for compiled in &self.rules {
+            let rule = compiled.rule;
+            let matched = compiled
+                .path_glob
+                .as_ref()
+                .is_none_or(|glob| glob.is_match(&path))
+                && compiled
+                    .file_name_pattern
+                    .as_ref()
+                    .is_none_or(|pattern| pattern.is_match(file_name))
+                && rule
+                    .item_type
+                    .as_ref()
+                    .is_none_or(|expected| expected == item_type)
+                && rule.publisher.as_deref().is_none_or(|expected| {
+                    publisher.is_some_and(|actual| {
+                        actual.to_lowercase().contains(&expected.to_lowercase())
+                    })
+                });
+            if !matched {
+                continue;
+            }
+
+            result.rule_ids.push(rule.id.clone());
+            if result.category_id.is_none() {
+                result.category_id = rule
+                    .category_id
+                    .clone()
+                    .filter(|id| config.categories.contains_key(id));
+            }
+            for tag in &rule.tags {
+                if !result
+                    .tags
+                    .iter()
+                    .any(|existing| existing.to_lowercase() == tag.to_lowercase())
+                {
+                    result.tags.push(tag.clone());
+                }
+            }
+        }
+        result
+    }
+}
+
+/// 保存前检查规则：id 不重复、至少有一个条件和一个动作、表达式可以编译、分类存在、标签合法
+pub fn validate_rules(config: &Config, rules: &[CategoryRule]) -> Result<(), AppError> {
+    let mut ids = HashSet::new();
+    for rule in rules {
+        if rule.id.trim().is_empty() {
+            return Err(AppError::invalid_input("规则 id 不能为空"));
+        }
+        if !ids.insert(rule.id.as_str()) {
+            return Err(AppError::invalid_input(format!(
+                "规则 id 重复: {}",
+                rule.id
+            )));
+        }
+        if rule.path_glob.is_none()
+            && rule.file_name_pattern.is_none()
+            && rule.item_type.is_none()
+            && rule.publisher.is_none()
+        {
+            return Err(AppError::invalid_input(format!(
+                "规则 {} 至少需要一个匹配条件",
+                rule_label(rule)
+            )));
+        }
+        if rule.category_id.is_none() && rule.tags.is_empty() {
+            return Err(AppError::invalid_input(format!(
+                "规则 {} 需要指定分类或标签",
+                rule_label(rule)
+            )));
+        }
+        if let Some(category_id) = &rule.category_id {
+            if !config.categories.contains_key(category_id) {
+                return Err(AppError::not_found(format!("分类 {}", category_id)));
+            }
+        }
+        for tag in &rule.tags {
+            tags::normalize_tag(tag)?;
+        }
+    }
+    RuleSet::compile(rules).map(drop)
+}
+
+/// 读取程序的发布者，目前只在 Windows 上能从卸载信息中读取
+pub fn lookup_publisher(path: &str, item_type: &ItemType) -> Option<String> {
+    if *item_type != ItemType::App {
+        return None;
+    }
+    #[cfg(target_os = "windows")]
+    let publisher = crate::utils::update_checker::get_publisher_from_registry(path);
+    #[cfg(not(target_os = "windows"))]
+    let publisher: Option<String> = {
+        let _ = path;
+        None
+    };
+    publisher
+}
+
+/// 用配置中的规则匹配新添加的程序，无法编译的规则会被跳过
+pub fn match_new_app(config: &Config, path: &str, item_type: &ItemType) -> RuleMatch {
+    let rules = RuleSet::compile_valid(&config.category_rules);
+    let publisher = if rules.uses_publisher() {
+        lookup_publisher(path, item_type)
+    } else {
+        None
+    };
+    rules.evaluate(config, path, item_type, publisher.as_deref())
+}
+
+/// 计算对已有程序重新应用规则会带来的变化，只列出分类或标签会改变的程序，按名称排列。
+/// `app_ids` 为空时检查全部程序。
+pub fn preview_changes(
+    config: &Config,
+    app_ids: Option<&[String]>,
+    publisher_of: impl Fn(&App) -> Option<String>,
+) -> Result<Vec<RuleChange>, AppError> {
+    let rules = RuleSet::compile(&config.category_rules)?;
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+    let uses_publisher = rules.uses_publisher();
+
+    let mut changes = Vec::new();
+    for app in config.apps.values() {
+        if app_ids.is_some_and(|ids| !ids.contains(&app.id)) {
+            continue;
+        }
+        let publisher = if uses_publisher {
+            publisher_of(app)
+        } else {
+            None
+        };
+        let matched = rules.evaluate(config, &app.path, &app.item_type, publisher.as_deref());
+        let to_category = matched.category_id.unwrap_or_else(|| app.category.clone());
+        let added_tags: Vec<String> = matched
+            .tags
+            .into_iter()
+            .filter(|tag| {
+                !app.tags
+                    .iter()
+                    .any(|existing| existing.to_lowercase() == tag.to_lowercase())
+            })
+            .collect();
+        if to_category == app.category && added_tags.is_empty() {
+            continue;
+        }
+        changes.push(RuleChange {
+            app_id: app.id.clone(),
+            app_name: app.name.clone(),
+            from_category: app.category.clone(),
+            to_category,
+            added_tags,
+            rule_ids: matched.rule_ids,
+        });
+    }
+    changes.sort_by(|left, right| {
+        left.app_name
+            .cmp(&right.app_name)
+            .then_with(|| left.app_id.cmp(&right.app_id))
+    });
+    Ok(changes)
+}
+
+pub fn apply_changes(config: &mut Config, changes: &[RuleChange]) -> Result<(), AppError> {
+    for change in changes {
+        if change.to_category != change.from_category {
+            categories::move_app(config, &change.app_id, &change.to_category, None)?;
+        }
+        if let Some(app) = config.apps.get_mut(&change.app_id) {
+            tags::add_tags(app, &change.added_tags)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_changes, glob_to_regex, match_new_app, preview_changes, validate_rules, RuleSet,
+    };
+    use crate::models::{App, Category, CategoryRule, Config, ItemType};
+    use regex::RegexBuilder;
+
+    fn rule(id: &str, value: serde_json::Value) -> CategoryRule {
+        let mut value = value;
+        value["id"] = serde_json::json!(id);
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn config_with(categories: &[&str], apps: &[(&str, &str, &str)]) -> Config {
+        let mut config = Config::default();
+        for (order, id) in categories.iter().enumerate() {
+            config.categories.insert(
+                id.to_string(),
+                Category {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    icon: None,
+                    apps: Vec::new(),
+                    order,
+                    parent_id: None,
+                },
+            );
+        }
+        for (id, path, category) in apps {
+            let app: App = serde_json::from_value(serde_json::json!({
+                "id": id,
+                "name": id,
+                "path": path,
+                "category": category,
+                "createdAt": 1
+            }))
+            .unwrap();
+            config
+                .categories
+                .get_mut(*category)
+                .unwrap()
+                .apps
+                .push(id.to_string());
+            config.apps.insert(id.to_string(), app);
+        }
+        config
+    }
+
+    #[test]
+    fn 通配符区分单层和多层目录() {
+        let matches = |glob: &str, path: &str| {
+            RegexBuilder::new(&glob_to_regex(glob))
+                .case_insensitive(true)
+                .build()
+                .unwrap()
+                .is_match(&path.replace('\\', "/"))
+        };
+        assert!(matches(
+            "C:/Program Files/JetBrains/**",
+            r"C:\Program Files\JetBrains\IDEA\bin\idea64.exe"
+        ));
+        assert!(matches("c:/games/*.exe", "C:/Games/run.exe"));
+        assert!(!matches("C:/Games/*.exe", "C:/Games/sub/run.exe"));
+        assert!(matches("**/steam.exe", "D:/Apps/Steam/steam.exe"));
+        assert!(matches("C:/tools/?.exe", "C:/tools/a.exe"));
+        assert!(!matches("C:/tools/a+b.exe", "C:/tools/aab.exe"));
+    }
+
+    #[test]
+    fn 按顺序匹配规则并合并标签() {
+        let mut config = config_with(&["dev", "games", "inbox"], &[]);
+        config.category_rules = vec![
+            rule(
+                "off",
+                serde_json::json!({ "enabled": false, "fileNamePattern": ".*", "categoryId": "inbox" }),
+            ),
+            rule(
+                "ide",
+                serde_json::json!({ "pathGlob": "C:/JetBrains/**", "categoryId": "dev", "tags": ["ide"] }),
+            ),
+            rule(
+                "exe",
+                serde_json::json!({ "fileNamePattern": r"\.exe$", "itemType": "app", "categoryId": "games", "tags": ["Exe", "ide"] }),
+            ),
+            rule(
+                "vendor",
+                serde_json::json!({ "publisher": "jetbrains", "tags": ["jetbrains"] }),
+            ),
+        ];
+        let rules = RuleSet::compile(&config.category_rules).unwrap();
+        assert!(rules.uses_publisher());
+
+        let matched = rules.evaluate(
+            &config,
+            r"C:\JetBrains\bin\idea64.EXE",
+            &ItemType::App,
+            Some("JetBrains s.r.o."),
+        );
+        assert_eq!(matched.category_id.as_deref(), Some("dev"));
+        assert_eq!(matched.tags, vec!["ide", "Exe", "jetbrains"]);
+        assert_eq!(matched.rule_ids, vec!["ide", "exe", "vendor"]);
+
+        let folder = rules.evaluate(&config, "C:/Games/run.exe", &ItemType::Folder, None);
+        assert_eq!(folder.category_id, None);
+        assert!(folder.rule_ids.is_empty());
+    }
+
+    #[test]
+    fn 添加程序时跳过无效的规则而其余规则仍然生效() {
+        let mut config = config_with(&["dev"], &[]);
+        config.category_rules = vec![
+            rule(
+                "broken",
+                serde_json::json!({ "fileNamePattern": "(", "categoryId": "dev", "tags": ["broken"] }),
+            ),
+            rule(
+                "exe",
+                serde_json::json!({ "fileNamePattern": r"\.exe$", "categoryId": "dev", "tags": ["exe"] }),
+            ),
+        ];
+        assert!(RuleSet::compile(&config.category_rules).is_err());
+
+        let matched = match_new_app(&config, "C:/Tools/run.exe", &ItemType::Folder);
+        assert_eq!(matched.category_id.as_deref(), Some("dev"));
+        assert_eq!(matched.tags, vec!["exe"]);
+        assert_eq!(matched.rule_ids, vec!["exe"]);
+    }
+
+    #[test]
+    fn 保存前校验规则() {
+        let config = config_with(&["dev"], &[]);
+        let valid = rule(
+            "a",
+            serde_json::json!({ "fileNamePattern": "code", "categoryId": "dev" }),
+        );
+        assert!(validate_rules(&config, std::slice::from_ref(&valid)).is_ok());
+
+        for invalid in [
+            vec![valid.clone(), valid.clone()],
+            vec![rule("b", serde_json::json!({ "categoryId": "dev" }))],
+            vec![rule("c", serde_json::json!({ "fileNamePattern": "code" }))],
+            vec![rule(
+                "d",
+                serde_json::json!({ "fileNamePattern": "(", "categoryId": "dev" }),
+            )],
+            vec![rule(
+                "e",
+                serde_json::json!({ "itemType": "url", "categoryId": "missing" }),
+            )],
+            vec![rule(
+                "f",
+                serde_json::json!({ "itemType": "url", "tags": ["a,b"] }),
+            )],
+        ] {
+            assert!(validate_rules(&config, &invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn 预览并应用规则到已有程序() {
+        let mut config = config_with(
+            &["inbox", "dev"],
+            &[
+                ("code", "C:/Tools/Code.exe", "inbox"),
+                ("notes", "C:/Docs/notes.txt", "inbox"),
+                ("vim", "C:/Tools/vim.exe", "dev"),
+            ],
+        );
+        config.apps.get_mut("vim").unwrap().tags = vec!["editor".to_string()];
+        config.category_rules = vec![rule(
+            "tools",
+            serde_json::json!({ "pathGlob": "C:/Tools/*.exe", "categoryId": "dev", "tags": ["Editor"] }),
+        )];
+
+        let changes = preview_changes(&config, None, |_| None).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].app_id, "code");
+        assert_eq!(changes[0].from_category, "inbox");
+        assert_eq!(changes[0].to_category, "dev");
+        assert_eq!(changes[0].added_tags, vec!["Editor"]);
+
+        let only_notes = preview_changes(&config, Some(&["notes".to_string()]), |_| None).unwrap();
+        assert!(only_notes.is_empty());
+
+        apply_changes(&mut config, &changes).unwrap();
+        assert_eq!(config.apps["code"].category, "dev");
+        assert_eq!(config.apps["code"].tags, vec!["Editor"]);
+        assert!(config.categories["inbox"].apps == vec!["notes"]);
+        assert_eq!(config.categories["dev"].apps, vec!["vim", "code"]);
+        assert!(preview_changes(&config, None, |_| None).unwrap().is_empty());
+    }
+}
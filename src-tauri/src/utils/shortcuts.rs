@@ -1,3 +1,4 @@
+use crate::error::AppError;
 use crate::models::{AppSettings, Config};
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
@@ -11,21 +12,29 @@ fn shortcut_value(value: &Option<String>, default_value: &str) -> String {
         .replace("Ctrl+", "Control+")
 }
 
-fn parse_shortcut(value: &str, label: &str) -> Result<Shortcut, String> {
+fn shortcut_error(label: &str, shortcut: &str, reason: impl ToString) -> AppError {
+    AppError::ShortcutInvalid {
+        label: label.to_string(),
+        shortcut: shortcut.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn parse_shortcut(value: &str, label: &str) -> Result<Shortcut, AppError> {
     value
         .parse::<Shortcut>()
-        .map_err(|error| format!("{} 快捷键格式无效（{}）: {}", label, value, error))
+        .map_err(|error| shortcut_error(label, value, format!("格式无效: {}", error)))
 }
 
 fn should_register_quicker_shortcuts(settings: &AppSettings) -> bool {
     settings.quicker_enabled.unwrap_or(true)
 }
 
-pub fn register_configured_shortcuts(app: &AppHandle, config: &Config) -> Result<(), String> {
+pub fn register_configured_shortcuts(app: &AppHandle, config: &Config) -> Result<(), AppError> {
     let shortcut_manager = app.global_shortcut();
     shortcut_manager
         .unregister_all()
-        .map_err(|error| format!("清理旧快捷键失败: {}", error))?;
+        .map_err(|error| AppError::internal(format!("清理旧快捷键失败: {}", error)))?;
 
     let settings = &config.settings;
     if !should_register_quicker_shortcuts(settings) {
@@ -41,7 +50,7 @@ pub fn register_configured_shortcuts(app: &AppHandle, config: &Config) -> Result
                     toggle_main_window(app);
                 }
             })
-            .map_err(|error| format!("注册主窗口快捷键失败（{}）: {}", shortcut_text, error))?;
+            .map_err(|error| shortcut_error("主窗口", &shortcut_text, error))?;
     }
 
     if settings.spotlight_search_enabled.unwrap_or(true) {
@@ -53,7 +62,7 @@ pub fn register_configured_shortcuts(app: &AppHandle, config: &Config) -> Result
                     show_or_create_search_window(app);
                 }
             })
-            .map_err(|error| format!("注册快捷搜索快捷键失败（{}）: {}", shortcut_text, error))?;
+            .map_err(|error| shortcut_error("快捷搜索", &shortcut_text, error))?;
     }
 
     if settings.quick_notes_enabled.unwrap_or(true) {
@@ -65,7 +74,7 @@ pub fn register_configured_shortcuts(app: &AppHandle, config: &Config) -> Result
                     show_or_create_notes_window(app);
                 }
             })
-            .map_err(|error| format!("注册快捷便签快捷键失败（{}）: {}", shortcut_text, error))?;
+            .map_err(|error| shortcut_error("快捷便签", &shortcut_text, error))?;
     }
 
     if settings.todo_schedule_enabled.unwrap_or(true) {
@@ -77,7 +86,7 @@ pub fn register_configured_shortcuts(app: &AppHandle, config: &Config) -> Result
                     toggle_or_create_todo_window(app);
                 }
             })
-            .map_err(|error| format!("注册待办日程表快捷键失败（{}）: {}", shortcut_text, error))?;
+            .map_err(|error| shortcut_error("待办日程表", &shortcut_text, error))?;
     }
 
     Ok(())
@@ -87,14 +96,13 @@ pub fn reload_shortcuts_or_restore(
     app: &AppHandle,
     next_config: &Config,
     previous_config: &Config,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     if let Err(error) = register_configured_shortcuts(app, next_config) {
-        let restore_result = register_configured_shortcuts(app, previous_config);
-        if let Err(restore_error) = restore_result {
-            return Err(format!("{}；恢复旧快捷键也失败: {}", error, restore_error));
+        // 返回新设置的错误，旧快捷键恢复失败时只记录日志
+        if let Err(restore_error) = register_configured_shortcuts(app, previous_config) {
+            eprintln!("恢复旧快捷键失败: {}", restore_error);
         }
-
-        return Err(format!("{}；已恢复旧快捷键设置", error));
+        return Err(error);
     }
 
     Ok(())
@@ -0,0 +1,357 @@
+use crate::error::AppError;
+use crate::models::{App, Config};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// 标签不能为空，也不能包含表达式使用的括号、引号和逗号；开头的 `#` 会被去掉
+pub fn normalize_tag(tag: &str) -> Result<String, AppError> {
+    let tag = tag.trim().trim_start_matches('#').trim();
+    if tag.is_empty() {
+        return Err(AppError::invalid_input("标签不能为空"));
+    }
+    if tag.contains(['(', ')', '"', ',']) {
+        return Err(AppError::invalid_input(format!(
+            "标签不能包含括号、引号或逗号: {}",
+            tag
+        )));
+    }
+    Ok(tag.to_string())
+}
+
+/// 标签不区分大小写
+fn same_tag(left: &str, right: &str) -> bool {
+    left.to_lowercase() == right.to_lowercase()
+}
+
+/// 给程序添加标签，已有的标签（不区分大小写）保持原样
+pub fn add_tags(app: &mut App, tags: &[String]) -> Result<(), AppError> {
+    for tag in tags {
+        let tag = normalize_tag(tag)?;
+        if !app.tags.iter().any(|existing| same_tag(existing, &tag)) {
+            app.tags.push(tag);
+        }
+    }
+    Ok(())
+}
+
+pub fn remove_tags(app: &mut App, tags: &[String]) {
+    app.tags.retain(|existing| {
+        !tags
+            .iter()
+            .any(|tag| same_tag(existing, tag.trim_start_matches('#')))
+    });
+}
+
+/// 统计各标签被多少个程序使用，按数量从多到少排列；大小写不同的写法合并计数
+pub fn tag_counts(config: &Config) -> Vec<TagCount> {
+    let mut app_ids: Vec<&String> = config.apps.keys().collect();
+    app_ids.sort();
+    let mut counts: Vec<TagCount> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for app_id in app_ids {
+        for tag in &config.apps[app_id].tags {
+            let key = tag.to_lowercase();
+            match index.get(&key) {
+                Some(&position) => counts[position].count += 1,
+                None => {
+                    index.insert(key, counts.len());
+                    counts.push(TagCount {
+                        tag: tag.clone(),
+                        count: 1,
+                    });
+                }
+            }
+        }
+    }
+    counts.sort_by(|left, right| {
+        right
+            .count
+            .cmp(&left.count)
+            .then_with(|| left.tag.to_lowercase().cmp(&right.tag.to_lowercase()))
+    });
+    counts
+}
+
+/// 标签表达式，例如 `work AND (dev OR "design tools") NOT old`。
+///
+/// 运算符 `AND`、`OR`、`NOT` 不区分大小写，`NOT` 的优先级最高、`OR` 最低；
+/// 相邻的标签视为 `AND`，`-tag` 是 `NOT tag` 的简写，含空格的标签用双引号括起。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagExpr {
+    Tag(String),
+    Not(Box<TagExpr>),
+    And(Vec<TagExpr>),
+    Or(Vec<TagExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Tag(String),
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, AppError> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '-' => {
+                chars.next();
+                tokens.push(Token::Not);
+            }
+            '"' => {
+                chars.next();
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(ch) => quoted.push(ch),
+                        None => return Err(AppError::invalid_input("标签表达式缺少右引号")),
+                    }
+                }
+                tokens.push(Token::Tag(normalize_tag(&quoted)?));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || matches!(ch, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(match word.to_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Tag(normalize_tag(&word)?),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<TagExpr, AppError> {
+        let mut terms = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            TagExpr::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<TagExpr, AppError> {
+        let mut terms = vec![self.parse_not()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                Some(Token::Open | Token::Not | Token::Tag(_)) => {}
+                _ => break,
+            }
+            terms.push(self.parse_not()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            TagExpr::And(terms)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<TagExpr, AppError> {
+        match self.next() {
+            Some(Token::Not) => Ok(TagExpr::Not(Box::new(self.parse_not()?))),
+            Some(Token::Tag(tag)) => Ok(TagExpr::Tag(tag)),
+            Some(Token::Open) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(AppError::invalid_input("标签表达式缺少右括号")),
+                }
+            }
+            Some(_) => Err(AppError::invalid_input("标签表达式中运算符的位置不正确")),
+            None => Err(AppError::invalid_input("标签表达式不完整")),
+        }
+    }
+}
+
+impl TagExpr {
+    pub fn parse(expression: &str) -> Result<Self, AppError> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            position: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Err(AppError::invalid_input("标签表达式不能为空"));
+        }
+        let expr = parser.parse_or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(AppError::invalid_input("标签表达式中有多余的右括号"));
+        }
+        Ok(expr)
+    }
+
+    pub fn matches(&self, tags: &[String]) -> bool {
+        match self {
+            TagExpr::Tag(tag) => tags.iter().any(|existing| same_tag(existing, tag)),
+            TagExpr::Not(expr) => !expr.matches(tags),
+            TagExpr::And(terms) => terms.iter().all(|term| term.matches(tags)),
+            TagExpr::Or(terms) => terms.iter().any(|term| term.matches(tags)),
+        }
+    }
+}
+
+/// 按标签表达式筛选程序，按名称排列
+pub fn query_apps<'a>(config: &'a Config, expression: &str) -> Result<Vec<&'a App>, AppError> {
+    let expr = TagExpr::parse(expression)?;
+    let mut apps: Vec<&App> = config
+        .apps
+        .values()
+        .filter(|app| expr.matches(&app.tags))
+        .collect();
+    apps.sort_by(|left, right| {
+        left.name
+            .cmp(&right.name)
+            .then_with(|| left.id.cmp(&right.id))
+    });
+    Ok(apps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_tags, remove_tags, tag_counts, TagCount, TagExpr};
+    use crate::error::AppError;
+    use crate::models::{App, Config};
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn 解析并匹配与或非标签表达式() {
+        let expr = TagExpr::parse(r#"work AND (dev OR "design tools") NOT old"#).unwrap();
+        assert!(expr.matches(&tags(&["Work", "dev"])));
+        assert!(expr.matches(&tags(&["work", "design tools"])));
+        assert!(!expr.matches(&tags(&["work", "dev", "old"])));
+        assert!(!expr.matches(&tags(&["dev"])));
+
+        let shorthand = TagExpr::parse("work -old").unwrap();
+        assert_eq!(
+            shorthand,
+            TagExpr::And(vec![
+                TagExpr::Tag("work".to_string()),
+                TagExpr::Not(Box::new(TagExpr::Tag("old".to_string()))),
+            ])
+        );
+        assert_eq!(
+            TagExpr::parse("a or b and c").unwrap(),
+            TagExpr::Or(vec![
+                TagExpr::Tag("a".to_string()),
+                TagExpr::And(vec![
+                    TagExpr::Tag("b".to_string()),
+                    TagExpr::Tag("c".to_string())
+                ]),
+            ])
+        );
+
+        for invalid in ["", "(work", "work)", "work AND", "OR work", "\"\""] {
+            assert!(TagExpr::parse(invalid).is_err(), "{} 应无法解析", invalid);
+        }
+    }
+
+    #[test]
+    fn 缺少右引号的标签表达式被拒绝() {
+        let error = TagExpr::parse(r#"work AND "design tools"#).unwrap_err();
+        assert_eq!(error.code(), "InvalidInput");
+        assert_eq!(
+            error.to_string(),
+            AppError::invalid_input("标签表达式缺少右引号").to_string()
+        );
+    }
+
+    #[test]
+    fn 增删标签不区分大小写并统计数量() {
+        let mut config = Config::default();
+        for (id, app_tags) in [
+            ("a", vec!["Work", "dev"]),
+            ("b", vec!["work"]),
+            ("c", vec![]),
+        ] {
+            let mut app: App = serde_json::from_value(serde_json::json!({
+                "id": id,
+                "name": id,
+                "path": format!("C:/{}.exe", id),
+                "category": "c",
+                "createdAt": 1
+            }))
+            .unwrap();
+            add_tags(&mut app, &tags(&app_tags)).unwrap();
+            config.apps.insert(id.to_string(), app);
+        }
+
+        let app = config.apps.get_mut("a").unwrap();
+        add_tags(app, &tags(&["#WORK", " tools "])).unwrap();
+        assert_eq!(app.tags, tags(&["Work", "dev", "tools"]));
+        assert!(add_tags(app, &tags(&["   "])).is_err());
+        remove_tags(app, &tags(&["DEV"]));
+        assert_eq!(app.tags, tags(&["Work", "tools"]));
+
+        assert_eq!(
+            tag_counts(&config),
+            vec![
+                TagCount {
+                    tag: "Work".to_string(),
+                    count: 2
+                },
+                TagCount {
+                    tag: "tools".to_string(),
+                    count: 1
+                },
+            ]
+        );
+    }
+}
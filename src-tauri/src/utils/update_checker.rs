@@ -1,3 +1,4 @@
+use crate::models::UpdateConfidence;
 use crate::utils::app_validator::get_file_metadata;
 use std::path::Path;
 
@@ -9,7 +10,7 @@ use winreg::RegKey;
 /// 更新检测结果
 pub struct UpdateCheckResult {
     pub has_update: bool,
-    pub confidence: String,
+    pub confidence: UpdateConfidence,
     pub old_version: Option<String>,
     pub new_version: Option<String>,
     pub file_changed: bool,
@@ -28,7 +29,7 @@ pub fn check_for_update(
 ) -> UpdateCheckResult {
     let mut result = UpdateCheckResult {
         has_update: false,
-        confidence: "low".to_string(),
+        confidence: UpdateConfidence::Low,
         old_version: baseline_version.clone(),
         new_version: None,
         file_changed: false,
@@ -65,7 +66,7 @@ pub fn check_for_update(
             if let Some(ref baseline_ver) = result.old_version {
                 if &current_version != baseline_ver {
                     result.has_update = true;
-                    result.confidence = "high".to_string();
+                    result.confidence = UpdateConfidence::High;
                     return result;
                 }
             }
@@ -77,9 +78,9 @@ pub fn check_for_update(
         result.has_update = true;
         // 如果大小和时间都变了，可信度较高；否则可信度低
         result.confidence = if result.size_changed && result.modified_time_changed {
-            "medium".to_string()
+            UpdateConfidence::Medium
         } else {
-            "low".to_string()
+            UpdateConfidence::Low
         };
     }
 
@@ -91,6 +92,18 @@ pub fn check_for_update(
 /// 搜索常见的注册表位置获取 DisplayVersion
 #[cfg(target_os = "windows")]
 pub fn get_version_from_registry(exe_path: &str) -> Option<String> {
+    read_uninstall_value(exe_path, "DisplayVersion")
+}
+
+/// 从 Windows 注册表读取程序的发布者（卸载信息中的 Publisher）
+#[cfg(target_os = "windows")]
+pub fn get_publisher_from_registry(exe_path: &str) -> Option<String> {
+    read_uninstall_value(exe_path, "Publisher")
+}
+
+/// 在卸载注册表项中查找与程序名称对应的条目，读取其中的 `value_name`
+#[cfg(target_os = "windows")]
+fn read_uninstall_value(exe_path: &str, value_name: &str) -> Option<String> {
     // 提取程序名称（不含扩展名）
     let app_name = Path::new(exe_path).file_stem()?.to_str()?.to_string();
 
@@ -111,8 +124,8 @@ pub fn get_version_from_registry(exe_path: &str) -> Option<String> {
 
     for path in paths {
         if let Ok(key) = hklm.open_subkey(&path) {
-            if let Ok(version) = key.get_value::<String, _>("DisplayVersion") {
-                return Some(version);
+            if let Ok(value) = key.get_value::<String, _>(value_name) {
+                return Some(value);
             }
         }
     }
@@ -129,8 +142,8 @@ pub fn get_version_from_registry(exe_path: &str) -> Option<String> {
                         .to_lowercase()
                         .contains(&app_name.to_lowercase())
                     {
-                        if let Ok(version) = subkey.get_value::<String, _>("DisplayVersion") {
-                            return Some(version);
+                        if let Ok(value) = subkey.get_value::<String, _>(value_name) {
+                            return Some(value);
                         }
                     }
                 }
@@ -41,3 +41,22 @@ describe('tauriAdapter 本地数据导入导出', () => {
     })
   })
 })
+
+describe('tauriAdapter 备份', () => {
+  beforeEach(() => {
+    mockedInvoke.mockReset()
+    mockedInvoke.mockResolvedValue(undefined)
+  })
+
+  it('恢复和删除备份时按 id 指定备份', async () => {
+    await tauriAdapter.listBackups()
+    await tauriAdapter.restoreBackup('full-1700000000000')
+    await tauriAdapter.deleteBackup('migration-1700000000000')
+
+    expect(mockedInvoke.mock.calls).toEqual([
+      ['list_backups'],
+      ['restore_backup', { backupId: 'full-1700000000000' }],
+      ['delete_backup', { backupId: 'migration-1700000000000' }]
+    ])
+  })
+})
@@ -8,6 +8,37 @@ export interface DataEnvelope<T> {
   data: T
 }
 
+// 数据写入、被程序之外修改或恢复备份后广播的 persisted-data-changed 事件；external 为 true 时窗口总是重新读取
+export interface PersistedDataChanged {
+  dataType: PersistedDataType
+  updatedAt: number
+  external: boolean
+}
+
+export type AppErrorCode =
+  | 'NotFound'
+  | 'Corrupt'
+  | 'SchemaTooNew'
+  | 'Conflict'
+  | 'PermissionDenied'
+  | 'PlatformUnsupported'
+  | 'ShortcutInvalid'
+  | 'InvalidInput'
+  | 'InvalidData'
+  | 'PassphraseRequired'
+  | 'WrongPassphrase'
+  | 'Io'
+  | 'Database'
+  | 'Migration'
+  | 'Internal'
+
+// 所有命令失败时拒绝的错误：code 用于判断和本地化，message 是后端给出的中文说明
+export interface AppError {
+  code: AppErrorCode
+  message: string
+  details: Record<string, unknown>
+}
+
 export interface LegacyLocalStoragePayload {
   scenes?: unknown
   notes?: unknown
@@ -44,11 +75,12 @@ export interface LocalDataFileEntry {
   path: string
   exists: boolean
   size?: number
+  sha256?: string
 }
 
 export interface LocalDataExportResult {
-  exportDir: string
-  manifestPath: string
+  packagePath: string
+  encrypted: boolean
   files: LocalDataFileEntry[]
 }
 
@@ -58,6 +90,33 @@ export interface LocalDataImportSectionPreview {
   available: boolean
   itemCount?: number
   error?: string
+  changes?: LocalDataCollectionDiff[]
+}
+
+export type LocalDataItemChangeKind = 'added' | 'changed' | 'removed'
+
+export interface LocalDataFieldChange {
+  field: string
+  before?: unknown
+  after?: unknown
+}
+
+export interface LocalDataItemDiff {
+  id: string
+  label: string
+  kind: LocalDataItemChangeKind
+  fields?: LocalDataFieldChange[]
+}
+
+export interface LocalDataCollectionDiff {
+  collection: string
+  items: LocalDataItemDiff[]
+}
+
+export interface LocalDataItemSelection {
+  section: LocalDataSection
+  collection: string
+  ids: string[]
 }
 
 export interface LocalDataImportPreview {
@@ -65,24 +124,172 @@ export interface LocalDataImportPreview {
   packageDir: string
   appVersion: string
   exportedAt: number
+  encrypted: boolean
   sections: LocalDataImportSectionPreview[]
+  pathSuggestions?: PathRemapSuggestion[]
   errors: string[]
 }
 
+export type PathRemapRule =
+  | { type: 'prefix'; from: string; to: string }
+  | { type: 'drive'; from: string; to: string }
+  | { type: 'envVars' }
+
+export interface PathRemapSuggestion {
+  rule: PathRemapRule
+  matched: number
+  exampleFrom: string
+  exampleTo: string
+}
+
+export type LocalDataImportMode = 'overwrite' | 'merge'
+export type MergePolicy = 'newer' | 'keepMine' | 'keepTheirs' | 'keepBoth'
+
+export interface LocalDataMergeOptions {
+  mode: LocalDataImportMode
+  conflictPolicy?: MergePolicy
+}
+
+export interface LocalDataMergeSummary {
+  section: LocalDataSection
+  collection: string
+  added: number
+  updated: number
+  skipped: number
+  removed: number
+}
+
 export interface LocalDataImportResult {
   success: boolean
   backupDir: string
   importedSections: LocalDataSection[]
+  mergeSummary?: LocalDataMergeSummary[]
   errors: string[]
 }
 
+export type StorageBackendKind = 'json' | 'sqlite'
+
+export interface StorageMigrationReport {
+  from: StorageBackendKind
+  to: StorageBackendKind
+  documents: string[]
+  backupDir?: string
+}
+
+export type DataDirSource = 'env' | 'portable' | 'relocated' | 'default'
+
+export interface DataDirInfo {
+  path: string
+  source: DataDirSource
+  canRelocate: boolean
+}
+
+export interface DataDirRelocation {
+  from: string
+  to: string
+  files: string[]
+  leftover: string[]
+}
+
+export type VersionedFileName = 'config.json' | 'scenes.json' | 'notes.json' | 'todos.json'
+
+export interface FileVersion {
+  id: string
+  replacedAt: number
+  size: number
+}
+
+export type BackupKind = 'full' | 'migration'
+
+// restoreSafety 为恢复备份前自动创建的备份，旧版本的清单没有该字段
+export type BackupPurpose = 'regular' | 'restoreSafety'
+
+export interface BackupManifest {
+  createdAt: number
+  reason: string
+  files: string[]
+  purpose?: BackupPurpose
+}
+
+export interface BackupSummary {
+  id: string
+  kind: BackupKind
+  dir: string
+  size: number
+  manifest?: BackupManifest
+  // 清单无法读取时的原因，这类备份仍可删除
+  error?: string
+}
+
+export interface BackupRestoreResult {
+  success: boolean
+  backupId: string
+  safetyBackupDir: string
+  errors: string[]
+}
+
+export interface DataRecoveryReport {
+  fileName: string
+  error: string
+  quarantinedTo?: string
+  restoredFromBackup?: string
+  recoveredAt: number
+}
+
 export const tauriAdapter = {
   readPersistedData<T>(dataType: PersistedDataType) {
     return invoke<DataEnvelope<T> | null>('read_persisted_data', { dataType })
   },
 
-  writePersistedData<T>(dataType: PersistedDataType, data: T) {
-    return invoke<DataEnvelope<T>>('write_persisted_data', { dataType, data })
+  // 传入上次读到的 updatedAt 时，数据已被其他窗口改写会以 Conflict 错误拒绝，details.current 为当前数据
+  writePersistedData<T>(dataType: PersistedDataType, data: T, expectedUpdatedAt?: number) {
+    return invoke<DataEnvelope<T>>('write_persisted_data', { dataType, data, expectedUpdatedAt })
+  },
+
+  getStorageBackend() {
+    return invoke<StorageBackendKind>('get_storage_backend')
+  },
+
+  migrateStorageBackend(target: StorageBackendKind) {
+    return invoke<StorageMigrationReport>('migrate_storage_backend', { target })
+  },
+
+  getDataDirInfo() {
+    return invoke<DataDirInfo>('get_data_dir_info')
+  },
+
+  relocateDataDir(target: string) {
+    return invoke<DataDirRelocation>('relocate_data_dir', { target })
+  },
+
+  restartApp() {
+    return invoke<void>('restart_app')
+  },
+
+  listFileVersions(fileName: VersionedFileName) {
+    return invoke<FileVersion[]>('list_file_versions', { fileName })
+  },
+
+  restoreFileVersion(fileName: VersionedFileName, versionId: string) {
+    return invoke<void>('restore_file_version', { fileName, versionId })
+  },
+
+  listBackups() {
+    return invoke<BackupSummary[]>('list_backups')
+  },
+
+  // 恢复前后端会先自动备份当前数据，恢复失败时回到恢复前的状态
+  restoreBackup(backupId: string) {
+    return invoke<BackupRestoreResult>('restore_backup', { backupId })
+  },
+
+  deleteBackup(backupId: string) {
+    return invoke<void>('delete_backup', { backupId })
+  },
+
+  // 取走后端处理过的损坏文件记录，同一条记录只会返回一次
+  takeDataRecoveryReports() {
+    return invoke<DataRecoveryReport[]>('take_data_recovery_reports')
   },
 
   getLegacyDataStatus(payload?: LegacyLocalStoragePayload) {
@@ -93,18 +300,26 @@ export const tauriAdapter = {
     return invoke<LegacyMigrationResult>('migrate_legacy_local_storage', { payload })
   },
 
-  exportLocalData(exportDir: string) {
-    return invoke<LocalDataExportResult>('export_local_data', { exportDir })
+  exportLocalData(exportDir: string, passphrase?: string) {
+    return invoke<LocalDataExportResult>('export_local_data', { exportDir, passphrase })
   },
 
-  previewLocalDataImport(manifestPath: string) {
-    return invoke<LocalDataImportPreview>('preview_local_data_import', { manifestPath })
+  previewLocalDataImport(manifestPath: string, passphrase?: string) {
+    return invoke<LocalDataImportPreview>('preview_local_data_import', { manifestPath, passphrase })
   },
 
-  importLocalData(manifestPath: string, sections: LocalDataSection[]) {
+  importLocalData(
+    manifestPath: string,
+    sections: LocalDataSection[],
+    passphrase?: string,
+    merge?: LocalDataMergeOptions,
+    items: LocalDataItemSelection[] = [],
+    pathRules: PathRemapRule[] = []
+  ) {
     return invoke<LocalDataImportResult>('import_local_data', {
       manifestPath,
-      options: { sections }
+      options: { sections, ...merge, items, pathRules },
+      passphrase
     })
   }
 }
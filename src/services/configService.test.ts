@@ -33,13 +33,75 @@ describe('configService', () => {
       category: 'cat_1',
       itemType: 'app'
     })
+    await configService.addApp({
+      name: '自动分类',
+      path: 'D:\\Tools\\auto.exe',
+      fallbackCategory: 'cat_2'
+    })
 
-    expect(mockedInvoke).toHaveBeenCalledWith('add_app', {
+    expect(mockedInvoke).toHaveBeenNthCalledWith(1, 'add_app', {
       name: '测试应用',
       path: 'D:\\Tools\\demo.exe',
       categoryId: 'cat_1',
       itemType: 'app'
     })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(2, 'add_app', {
+      name: '自动分类',
+      path: 'D:\\Tools\\auto.exe',
+      categoryId: undefined,
+      fallbackCategoryId: 'cat_2',
+      itemType: undefined
+    })
+  })
+
+  it('自动分类规则命令会传递规则和要检查的程序', async () => {
+    const rule = { id: 'r1', name: '开发工具', enabled: true, pathGlob: 'C:/JetBrains/**', categoryId: 'dev', tags: ['ide'] }
+    await configService.saveCategoryRules([rule])
+    await configService.previewCategoryRules()
+    await configService.applyCategoryRules(['app_1'])
+
+    expect(mockedInvoke).toHaveBeenNthCalledWith(1, 'save_category_rules', { rules: [rule] })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(2, 'preview_category_rules', { appIds: undefined })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(3, 'apply_category_rules', { appIds: ['app_1'] })
+  })
+
+  it('启动选项会整体传给后端，参数不拼接成命令行', async () => {
+    const options = {
+      args: ['--profile', 'Work Profile'],
+      workingDir: 'D:\\Work',
+      env: { APP_MODE: 'work' },
+      windowState: 'maximized' as const
+    }
+    await configService.updateAppLaunchOptions('app_1', options)
+    await configService.launchAppAsAdmin('app_1')
+    await configService.duplicateApp('app_1')
+
+    expect(mockedInvoke).toHaveBeenNthCalledWith(1, 'update_app_launch_options', { appId: 'app_1', options })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(2, 'launch_app_as_admin', { appId: 'app_1' })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(3, 'duplicate_app', { appId: 'app_1' })
+  })
+
+  it('删除分类时会传递分类中程序的去向', async () => {
+    mockedInvoke.mockResolvedValueOnce(['app_1'])
+
+    await expect(configService.deleteCategory('cat_1', { mode: 'delete' })).resolves.toEqual(['app_1'])
+    await configService.deleteCategory('cat_2', { mode: 'move', targetId: 'cat_3' })
+    await configService.moveAppToCategory('app_2', 'cat_3', 0)
+
+    expect(mockedInvoke).toHaveBeenNthCalledWith(1, 'delete_category', { categoryId: 'cat_1', apps: { mode: 'delete' } })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(2, 'delete_category', {
+      categoryId: 'cat_2',
+      apps: { mode: 'move', targetId: 'cat_3' }
+    })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(3, 'move_app_to_category', { appId: 'app_2', categoryId: 'cat_3', index: 0 })
+  })
+
+  it('标签命令会传递程序 id 和标签列表', async () => {
+    await configService.addAppTags('app_1', ['work'])
+    await configService.queryAppsByTags('work AND NOT old')
+
+    expect(mockedInvoke).toHaveBeenNthCalledWith(1, 'add_app_tags', { appId: 'app_1', tags: ['work'] })
+    expect(mockedInvoke).toHaveBeenNthCalledWith(2, 'query_apps_by_tags', { expression: 'work AND NOT old' })
   })
 
   it('图标文件名会使用图标目录转换为可渲染 URL', async () => {
@@ -1,10 +1,12 @@
 import { convertFileSrc, invoke } from '@tauri-apps/api/core'
-import type { App, Category, Config, ManagedItemType } from '@/types'
+import type { App, Category, CategoryAppsDisposal, CategoryRule, Config, LaunchOptions, ManagedItemType, RuleChange, TagCount } from '@/types'
 
 export interface AddAppInput {
   name: string
   path: string
-  category: string
+  // 不指定时按自动分类规则归类，规则都不匹配再使用 fallbackCategory
+  category?: string
+  fallbackCategory?: string
   itemType?: ManagedItemType
 }
 
@@ -19,8 +21,56 @@ export const configService = {
     return invoke<void>('save_config', { config })
   },
 
-  addCategory(name: string) {
-    return invoke<Category>('add_category', { name })
+  addCategory(name: string, parentId?: string) {
+    return invoke<Category>('add_category', { name, parentId })
+  },
+
+  renameCategory(categoryId: string, name: string) {
+    return invoke<void>('rename_category', { categoryId, name })
+  },
+
+  // 返回随分类一起删除的程序 id
+  deleteCategory(categoryId: string, apps: CategoryAppsDisposal) {
+    return invoke<string[]>('delete_category', { categoryId, apps })
+  },
+
+  // categoryIds 必须恰好是 parentId 下的全部直接子分类
+  reorderCategories(parentId: string | undefined, categoryIds: string[]) {
+    return invoke<void>('reorder_categories', { parentId, categoryIds })
+  },
+
+  moveCategory(categoryId: string, parentId?: string) {
+    return invoke<void>('move_category', { categoryId, parentId })
+  },
+
+  moveAppToCategory(appId: string, categoryId: string, index?: number) {
+    return invoke<void>('move_app_to_category', { appId, categoryId, index })
+  },
+
+  linkAppToCategory(appId: string, categoryId: string) {
+    return invoke<void>('link_app_to_category', { appId, categoryId })
+  },
+
+  unlinkAppFromCategory(appId: string, categoryId: string) {
+    return invoke<void>('unlink_app_from_category', { appId, categoryId })
+  },
+
+  // 返回修改后程序的全部标签
+  addAppTags(appId: string, tags: string[]) {
+    return invoke<string[]>('add_app_tags', { appId, tags })
+  },
+
+  removeAppTags(appId: string, tags: string[]) {
+    return invoke<string[]>('remove_app_tags', { appId, tags })
+  },
+
+  listTags() {
+    return invoke<TagCount[]>('list_tags')
+  },
+
+  // 例如 work AND (dev OR design) NOT old
+  queryAppsByTags(expression: string) {
+    return invoke<App[]>('query_apps_by_tags', { expression })
   },
 
   addApp(appData: AddAppInput) {
@@ -28,10 +78,24 @@ export const configService = {
       name: appData.name,
       path: appData.path,
       categoryId: appData.category,
+      fallbackCategoryId: appData.fallbackCategory,
       itemType: appData.itemType
     })
   },
 
+  saveCategoryRules(rules: CategoryRule[]) {
+    return invoke<void>('save_category_rules', { rules })
+  },
+
+  // appIds 为空时检查全部程序
+  previewCategoryRules(appIds?: string[]) {
+    return invoke<RuleChange[]>('preview_category_rules', { appIds })
+  },
+
+  applyCategoryRules(appIds?: string[]) {
+    return invoke<RuleChange[]>('apply_category_rules', { appIds })
+  },
+
   deleteApp(appId: string) {
     return invoke<void>('delete_app', { appId })
   },
@@ -40,6 +104,24 @@ export const configService = {
     return invoke<void>('launch_app', { appId })
   },
 
+  launchAppAsAdmin(appId: string) {
+    return invoke<void>('launch_app_as_admin', { appId })
+  },
+
+  updateAppLaunchOptions(appId: string, options: LaunchOptions) {
+    return invoke<App>('update_app_launch_options', { appId, options })
+  },
+
+  // 复制条目，用于同一程序的不同启动配置
+  duplicateApp(appId: string) {
+    return invoke<App>('duplicate_app', { appId })
+  },
+
+  // 用系统关联的程序打开文件、文件夹或网址
+  openPath(target: string) {
+    return invoke<void>('open_path', { target })
+  },
+
   initUpdateBaseline(appId: string) {
     return invoke<void>('init_update_baseline', { appId })
   },
@@ -0,0 +1,32 @@
+import { describe, expect, it } from 'vitest'
+import { formatError, isAppError } from './errorService'
+
+describe('errorService', () => {
+  it('按错误代码和结构化字段生成文案', () => {
+    expect(formatError({
+      code: 'PlatformUnsupported',
+      message: '当前平台不支持图标提取',
+      details: { feature: '图标提取' }
+    })).toBe('当前平台不支持图标提取')
+    expect(formatError({
+      code: 'SchemaTooNew',
+      message: '',
+      details: { name: 'notes', found: 3, supported: 2 }
+    })).toContain('版本 3')
+    expect(formatError({
+      code: 'InvalidData',
+      message: '',
+      details: { name: 'scenes.json', field: 'scenes[0].actions[1].type', reason: 'unknown variant `lunch`' }
+    })).toContain('scenes[0].actions[1].type')
+  })
+
+  it('没有对应文案时使用后端说明', () => {
+    expect(formatError({ code: 'Internal', message: '执行失败: 超时', details: {} })).toBe('执行失败: 超时')
+  })
+
+  it('兼容字符串和普通异常', () => {
+    expect(isAppError('失败')).toBe(false)
+    expect(formatError('失败')).toBe('失败')
+    expect(formatError(new Error('网络错误'))).toBe('网络错误')
+  })
+})
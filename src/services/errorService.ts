@@ -0,0 +1,40 @@
+import type { AppError, AppErrorCode } from '@/adapters/tauriAdapter'
+
+type Details = Record<string, unknown>
+
+const text = (value: unknown) => (value === undefined || value === null ? '' : String(value))
+
+// 按错误代码生成界面文案；没有列出的代码直接使用后端给出的说明
+const MESSAGES: Partial<Record<AppErrorCode, (details: Details) => string>> = {
+  NotFound: (details) => `${text(details.resource)}不存在`,
+  SchemaTooNew: (details) =>
+    `${text(details.name)} 由更新版本的程序保存（版本 ${text(details.found)}，当前最高支持 ${text(details.supported)}），请升级程序后再打开`,
+  Conflict: (details) => `${text(details.name)} 已被其他窗口修改，请刷新后重试`,
+  PermissionDenied: () => '没有权限访问该文件，请检查文件权限或以管理员身份运行',
+  PlatformUnsupported: (details) => `当前平台不支持${text(details.feature)}`,
+  ShortcutInvalid: (details) =>
+    `${text(details.label)}快捷键 ${text(details.shortcut)} 无法使用，可能格式有误或已被其他程序占用，已恢复原有设置`,
+  InvalidData: (details) =>
+    `${text(details.name)} 中的 ${text(details.field)} 不符合数据格式（${text(details.reason)}），本次修改没有保存`,
+  PassphraseRequired: () => '数据包已加密，请输入密码',
+  WrongPassphrase: () => '数据包密码错误'
+}
+
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as AppError).code === 'string' &&
+    typeof (error as AppError).message === 'string'
+  )
+}
+
+/** 把命令返回的错误转换为可以直接显示的文案 */
+export function formatError(error: unknown): string {
+  if (isAppError(error)) {
+    const format = MESSAGES[error.code]
+    return format ? format(error.details ?? {}) : error.message
+  }
+  if (error instanceof Error) return error.message
+  return String(error)
+}
@@ -1,14 +1,25 @@
 import { describe, expect, it, vi } from 'vitest'
 import type { LocalDataImportPreview, LocalDataSection } from '@/adapters/tauriAdapter'
 import {
+  backupDetailText,
+  backupTitle,
   canImportLocalData,
+  defaultImportItemSelection,
   exportLocalDataWithPicker,
+  fieldChangeText,
   importConfirmMessage,
+  importItemSelectionPayload,
   importLocalDataWithConfirmation,
+  importMergeOptions,
   importPreviewStatus,
   importSectionText,
+  mergeSummaryText,
+  pathRemapRuleText,
+  prefixRemapRule,
   previewLocalDataImportWithPicker,
+  restoreBackupConfirmMessage,
   selectableImportSections,
+  toggleImportItemSelection,
   toggleImportSectionSelection
 } from './localDataService'
 
@@ -17,6 +28,7 @@ const preview = (overrides: Partial<LocalDataImportPreview> = {}): LocalDataImpo
   packageDir: 'D:\\backup',
   appVersion: '1.1.4',
   exportedAt: 1,
+  encrypted: false,
   errors: [],
   sections: [
     { section: 'config', label: '主配置', available: true, itemCount: 2 },
@@ -67,12 +79,91 @@ describe('localDataService', () => {
 
   it('会生成覆盖导入确认文案', () => {
     expect(importConfirmMessage(3)).toBe('将覆盖导入 3 类数据。导入前会自动备份当前数据，导入失败会尝试恢复备份。')
+    expect(importConfirmMessage(2, 'merge')).toBe('将合并导入 2 类数据。导入前会自动备份当前数据，导入失败会尝试恢复备份。')
+  })
+
+  it('会汇总合并导入的新增、更新和跳过数量', () => {
+    expect(mergeSummaryText()).toBe('')
+    expect(mergeSummaryText([
+      { section: 'config', collection: 'apps', added: 2, updated: 1, skipped: 0, removed: 0 },
+      { section: 'notes', collection: 'notes', added: 1, updated: 0, skipped: 3, removed: 0 }
+    ])).toBe('新增 3 条，更新 1 条，跳过 3 条')
+    expect(mergeSummaryText([
+      { section: 'config', collection: 'apps', added: 0, updated: 1, skipped: 0, removed: 2 }
+    ])).toBe('新增 0 条，更新 1 条，跳过 0 条，删除 2 条')
+    expect(importMergeOptions('overwrite')).toEqual({ mode: 'overwrite' })
+    expect(importMergeOptions('keepBoth')).toEqual({ mode: 'merge', conflictPolicy: 'keepBoth' })
+  })
+
+  it('只为部分勾选的数据生成按条目导入参数', () => {
+    const withChanges = preview({
+      sections: [
+        {
+          section: 'config',
+          label: '主配置',
+          available: true,
+          changes: [
+            { collection: 'categories', items: [{ id: 'cat-1', label: '工具', kind: 'added' }] },
+            {
+              collection: 'apps',
+              items: [
+                { id: 'app-1', label: '编辑器', kind: 'changed', fields: [{ field: 'name', before: '旧', after: '新' }] },
+                { id: 'app-2', label: '终端', kind: 'removed' }
+              ]
+            }
+          ]
+        },
+        {
+          section: 'notes',
+          label: '便签',
+          available: true,
+          changes: [{ collection: 'notes', items: [{ id: 'note-1', label: '便签', kind: 'added' }] }]
+        }
+      ]
+    })
+    const selection = defaultImportItemSelection(withChanges)
+
+    expect(importItemSelectionPayload(withChanges, ['config', 'notes'], selection)).toEqual([])
+
+    const partial = toggleImportItemSelection(selection, 'config:apps', 'app-2', false)
+    expect(importItemSelectionPayload(withChanges, ['config', 'notes'], partial)).toEqual([
+      { section: 'config', collection: 'categories', ids: ['cat-1'] },
+      { section: 'config', collection: 'apps', ids: ['app-1'] }
+    ])
+    expect(importItemSelectionPayload(withChanges, ['notes'], partial)).toEqual([])
+    expect(fieldChangeText({ field: 'name', before: '旧', after: '新' })).toBe('name：旧 → 新')
+  })
+
+  it('会生成路径改写规则及其说明', () => {
+    expect(prefixRemapRule(' D:\\Tools ', 'E:\\Apps')).toEqual({ type: 'prefix', from: 'D:\\Tools', to: 'E:\\Apps' })
+    expect(prefixRemapRule('D:\\Tools', ' ')).toBeNull()
+    expect(pathRemapRuleText({ type: 'drive', from: 'D', to: 'E' })).toBe('盘符 D: → E:')
+    expect(pathRemapRuleText({ type: 'prefix', from: 'C:\\Users\\a', to: 'C:\\Users\\b' })).toBe('C:\\Users\\a → C:\\Users\\b')
+  })
+
+  it('会生成备份的标题、大小和恢复确认文案', () => {
+    const full = {
+      id: 'full-1',
+      kind: 'full' as const,
+      dir: 'D:\\data\\backups\\full-1',
+      size: 2048,
+      manifest: { createdAt: 1, reason: '定时自动备份', files: ['config.json'] }
+    }
+    expect(backupTitle(full)).toBe('定时自动备份')
+    expect(backupDetailText(full)).toBe('2.0 KB')
+    expect(restoreBackupConfirmMessage(full)).toContain('替换当前的全部数据')
+
+    const migration = { ...full, id: 'migration-1', kind: 'migration' as const, size: 100 }
+    expect(backupDetailText(migration)).toBe('100 B，仅包含配置')
+    expect(restoreBackupConfirmMessage(migration)).toContain('替换当前的配置')
+
+    expect(backupTitle({ ...full, manifest: undefined, error: '文件不存在' })).toBe('备份清单无法读取：文件不存在')
   })
 
   it('导出流程会处理取消、成功和失败状态', async () => {
     const exportLocalData = vi.fn().mockResolvedValue({
-      exportDir: 'D:\\backup\\program-manager-export',
-      manifestPath: 'D:\\backup\\program-manager-export\\manifest.json',
+      packagePath: 'D:\\backup\\program-manager-export.pmpkg',
+      encrypted: false,
       files: []
     })
 
@@ -88,7 +179,7 @@ describe('localDataService', () => {
     })).resolves.toMatchObject({
       completed: true,
       feedback: {
-        message: '数据包已导出：D:\\backup\\program-manager-export\\manifest.json',
+        message: '数据包已导出：D:\\backup\\program-manager-export.pmpkg',
         status: 'success'
       }
     })
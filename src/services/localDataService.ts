@@ -1,10 +1,19 @@
 import type {
+  BackupSummary,
   LocalDataExportResult,
+  LocalDataFieldChange,
+  LocalDataImportMode,
+  LocalDataItemChangeKind,
+  LocalDataItemSelection,
   LocalDataImportResult,
+  LocalDataMergeOptions,
+  LocalDataMergeSummary,
   LocalDataImportPreview,
   LocalDataImportSectionPreview,
-  LocalDataSection
+  LocalDataSection,
+  PathRemapRule
 } from '@/adapters/tauriAdapter'
+import { formatError } from './errorService'
 
 export type DataTransferStatus = 'success' | 'error'
 export type LocalDataPickerResult = string | string[] | null
@@ -85,8 +94,174 @@ export function toggleImportSectionSelection(
   return selectedSections.filter((item) => item !== section)
 }
 
-export function importConfirmMessage(selectedCount: number): string {
-  return `将覆盖导入 ${selectedCount} 类数据。导入前会自动备份当前数据，导入失败会尝试恢复备份。`
+export type LocalDataImportStrategy = 'overwrite' | 'newer' | 'keepMine' | 'keepTheirs' | 'keepBoth'
+
+export const IMPORT_STRATEGY_OPTIONS: { id: LocalDataImportStrategy; label: string }[] = [
+  { id: 'overwrite', label: '整体覆盖' },
+  { id: 'newer', label: '合并·保留较新' },
+  { id: 'keepMine', label: '合并·保留本地' },
+  { id: 'keepTheirs', label: '合并·使用导入' },
+  { id: 'keepBoth', label: '合并·保留两份' }
+]
+
+export function importMergeOptions(strategy: LocalDataImportStrategy): LocalDataMergeOptions {
+  if (strategy === 'overwrite') return { mode: 'overwrite' }
+  return { mode: 'merge', conflictPolicy: strategy }
+}
+
+export function importConfirmMessage(
+  selectedCount: number,
+  mode: LocalDataImportMode = 'overwrite'
+): string {
+  const action = mode === 'merge' ? '合并' : '覆盖'
+  return `将${action}导入 ${selectedCount} 类数据。导入前会自动备份当前数据，导入失败会尝试恢复备份。`
+}
+
+export function mergeSummaryText(summary: LocalDataMergeSummary[] = []): string {
+  if (summary.length === 0) return ''
+  const total = summary.reduce(
+    (sum, item) => ({
+      added: sum.added + item.added,
+      updated: sum.updated + item.updated,
+      skipped: sum.skipped + item.skipped,
+      removed: sum.removed + item.removed
+    }),
+    { added: 0, updated: 0, skipped: 0, removed: 0 }
+  )
+  const text = `新增 ${total.added} 条，更新 ${total.updated} 条，跳过 ${total.skipped} 条`
+  return total.removed > 0 ? `${text}，删除 ${total.removed} 条` : text
+}
+
+/** 按 `数据类型:集合` 记录勾选的条目 id */
+export type ImportItemSelection = Record<string, string[]>
+
+const COLLECTION_LABELS: Record<string, string> = {
+  categories: '分类',
+  apps: '程序',
+  scenes: '场景',
+  notes: '便签',
+  items: '条目'
+}
+
+const CHANGE_KIND_LABELS: Record<LocalDataItemChangeKind, string> = {
+  added: '新增',
+  changed: '修改',
+  removed: '删除'
+}
+
+export function importItemKey(section: LocalDataSection, collection: string): string {
+  return `${section}:${collection}`
+}
+
+export function collectionLabel(collection: string): string {
+  return COLLECTION_LABELS[collection] ?? collection
+}
+
+export function itemChangeKindText(kind: LocalDataItemChangeKind): string {
+  return CHANGE_KIND_LABELS[kind]
+}
+
+function formatFieldValue(value: unknown): string {
+  if (value === undefined) return '（无）'
+  const text = typeof value === 'string' ? value : JSON.stringify(value)
+  return text.length > 60 ? `${text.slice(0, 60)}…` : text
+}
+
+export function fieldChangeText(change: LocalDataFieldChange): string {
+  return `${change.field}：${formatFieldValue(change.before)} → ${formatFieldValue(change.after)}`
+}
+
+export function defaultImportItemSelection(preview: LocalDataImportPreview): ImportItemSelection {
+  const selection: ImportItemSelection = {}
+  for (const section of preview.sections) {
+    for (const collection of section.changes ?? []) {
+      selection[importItemKey(section.section, collection.collection)] = collection.items.map((item) => item.id)
+    }
+  }
+  return selection
+}
+
+export function toggleImportItemSelection(
+  selection: ImportItemSelection,
+  key: string,
+  id: string,
+  checked: boolean
+): ImportItemSelection {
+  const current = selection[key] ?? []
+  const next = checked
+    ? current.includes(id) ? current : [...current, id]
+    : current.filter((item) => item !== id)
+  return { ...selection, [key]: next }
+}
+
+/**
+ * 只为取消勾选了部分条目的数据生成按条目导入的参数。
+ * 全部勾选的数据仍按所选导入方式整体处理。
+ */
+export function importItemSelectionPayload(
+  preview: LocalDataImportPreview,
+  selectedSections: LocalDataSection[],
+  selection: ImportItemSelection
+): LocalDataItemSelection[] {
+  const payload: LocalDataItemSelection[] = []
+  for (const section of preview.sections) {
+    if (!selectedSections.includes(section.section)) continue
+    const collections = section.changes ?? []
+    const partial = collections.some((collection) => {
+      const ids = selection[importItemKey(section.section, collection.collection)] ?? []
+      return collection.items.some((item) => !ids.includes(item.id))
+    })
+    if (!partial) continue
+    for (const collection of collections) {
+      const ids = selection[importItemKey(section.section, collection.collection)] ?? []
+      payload.push({
+        section: section.section,
+        collection: collection.collection,
+        ids: collection.items.map((item) => item.id).filter((id) => ids.includes(id))
+      })
+    }
+  }
+  return payload
+}
+
+export function backupTitle(backup: BackupSummary): string {
+  if (backup.error) return `备份清单无法读取：${backup.error}`
+  return backup.manifest?.reason || '备份'
+}
+
+function formatBackupSize(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`
+  return `${(bytes / 1024 / 1024).toFixed(1)} MB`
+}
+
+// 数据结构升级前的备份只包含配置，恢复时也只替换配置
+export function backupDetailText(backup: BackupSummary): string {
+  const size = formatBackupSize(backup.size)
+  return backup.kind === 'migration' ? `${size}，仅包含配置` : size
+}
+
+export function restoreBackupConfirmMessage(backup: BackupSummary): string {
+  const scope = backup.kind === 'migration' ? '配置' : '全部数据'
+  return `用“${backupTitle(backup)}”替换当前的${scope}？恢复前会自动备份当前数据。`
+}
+
+export function pathRemapRuleText(rule: PathRemapRule): string {
+  if (rule.type === 'prefix') return `${rule.from} → ${rule.to}`
+  if (rule.type === 'drive') return `盘符 ${rule.from}: → ${rule.to}:`
+  return '展开环境变量（如 %USERPROFILE%、~）'
+}
+
+export function samePathRemapRule(a: PathRemapRule, b: PathRemapRule): boolean {
+  return JSON.stringify(a) === JSON.stringify(b)
+}
+
+/** 两端都填写时生成前缀改写规则 */
+export function prefixRemapRule(from: string, to: string): PathRemapRule | null {
+  const source = from.trim()
+  const target = to.trim()
+  if (!source || !target) return null
+  return { type: 'prefix', from: source, to: target }
 }
 
 export async function exportLocalDataWithPicker(options: {
@@ -104,7 +279,7 @@ export async function exportLocalDataWithPicker(options: {
       completed: true,
       result,
       feedback: {
-        message: `数据包已导出：${result.manifestPath}`,
+        message: `${result.encrypted ? '加密数据包' : '数据包'}已导出：${result.packagePath}`,
         status: 'success'
       }
     }
@@ -112,7 +287,7 @@ export async function exportLocalDataWithPicker(options: {
     return {
       completed: true,
       feedback: {
-        message: `导出失败：${String(error)}`,
+        message: `导出失败：${formatError(error)}`,
         status: 'error'
       }
     }
@@ -147,7 +322,7 @@ export async function previewLocalDataImportWithPicker(options: {
       preview: null,
       selectedSections: [],
       feedback: {
-        message: `读取数据包失败：${String(error)}`,
+        message: `读取数据包失败：${formatError(error)}`,
         status: 'error'
       }
     }
@@ -164,6 +339,7 @@ export async function importLocalDataWithConfirmation(
       sections: LocalDataSection[]
     ) => Promise<LocalDataImportResult>
     refreshImportedData: () => Promise<void>
+    mode?: LocalDataImportMode
   }
 ): Promise<ImportLocalDataFlowResult> {
   if (!preview || selectedSections.length === 0) {
@@ -173,7 +349,9 @@ export async function importLocalDataWithConfirmation(
     }
   }
 
-  const confirmed = await options.confirmImport(importConfirmMessage(selectedSections.length))
+  const confirmed = await options.confirmImport(
+    importConfirmMessage(selectedSections.length, options.mode)
+  )
   if (!confirmed) {
     return {
       completed: false,
@@ -196,12 +374,15 @@ export async function importLocalDataWithConfirmation(
     }
 
     await options.refreshImportedData()
+    const mergeText = mergeSummaryText(result.mergeSummary)
     return {
       completed: true,
       resetPreview: true,
       result,
       feedback: {
-        message: `导入完成，当前数据已备份到：${result.backupDir}`,
+        message: mergeText
+          ? `${options.mode === 'merge' ? '合并' : '导入'}完成（${mergeText}），当前数据已备份到：${result.backupDir}`
+          : `导入完成，当前数据已备份到：${result.backupDir}`,
         status: 'success'
       }
     }
@@ -210,7 +391,7 @@ export async function importLocalDataWithConfirmation(
       completed: true,
       resetPreview: false,
       feedback: {
-        message: `导入失败：${String(error)}`,
+        message: `导入失败：${formatError(error)}`,
         status: 'error'
       }
     }
@@ -0,0 +1,86 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest'
+import { tauriAdapter } from '@/adapters/tauriAdapter'
+import { handlePersistedDataChanged, mergePersistedData, persistenceService } from './persistenceService'
+
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(async () => () => {})
+}))
+
+vi.mock('@/adapters/tauriAdapter', () => ({
+  tauriAdapter: {
+    readPersistedData: vi.fn(),
+    writePersistedData: vi.fn()
+  }
+}))
+
+const mockedTauriAdapter = vi.mocked(tauriAdapter)
+
+const envelope = <T>(updatedAt: number, data: T) => ({ schemaVersion: 1, updatedAt, data })
+
+describe('persistenceService', () => {
+  beforeEach(() => {
+    persistenceService.resetKnownVersions()
+    mockedTauriAdapter.readPersistedData.mockReset()
+    mockedTauriAdapter.writePersistedData.mockReset()
+  })
+
+  it('写入时带上最近一次读到的 updatedAt', async () => {
+    mockedTauriAdapter.readPersistedData.mockResolvedValue(envelope(10, { notes: [] }))
+    mockedTauriAdapter.writePersistedData.mockResolvedValue(envelope(11, { notes: [] }))
+
+    await persistenceService.load('notes', { notes: [] })
+    await expect(persistenceService.save('notes', { notes: [] })).resolves.toBeNull()
+    await persistenceService.save('notes', { notes: [] })
+
+    expect(mockedTauriAdapter.writePersistedData).toHaveBeenNthCalledWith(1, 'notes', { notes: [] }, 10)
+    expect(mockedTauriAdapter.writePersistedData).toHaveBeenNthCalledWith(2, 'notes', { notes: [] }, 11)
+  })
+
+  it('写入冲突时与其他窗口的数据合并后重试', async () => {
+    const base = { notes: [{ id: 'a', content: '原内容' }, { id: 'b', content: '将被删除' }] }
+    const theirs = { notes: [{ id: 'a', content: '原内容' }, { id: 'b', content: '将被删除' }, { id: 'c', content: '其他窗口新增' }] }
+    const mine = { notes: [{ id: 'a', content: '本窗口修改' }] }
+    mockedTauriAdapter.readPersistedData.mockResolvedValue(envelope(10, base))
+    mockedTauriAdapter.writePersistedData
+      .mockRejectedValueOnce({ code: 'Conflict', message: '冲突', details: { name: 'notes', current: envelope(20, theirs) } })
+      .mockImplementationOnce(async (_type, data) => envelope(21, data))
+
+    await persistenceService.load('notes', { notes: [] })
+    const merged = await persistenceService.save('notes', mine)
+
+    expect(merged).toEqual({
+      notes: [{ id: 'a', content: '本窗口修改' }, { id: 'c', content: '其他窗口新增' }]
+    })
+    expect(mockedTauriAdapter.writePersistedData).toHaveBeenLastCalledWith('notes', merged, 20)
+  })
+
+  it('非冲突错误直接抛出错误信息', async () => {
+    mockedTauriAdapter.writePersistedData.mockRejectedValue({ code: 'Io', message: '磁盘已满', details: {} })
+
+    await expect(persistenceService.save('todos', { items: [] })).rejects.toBe('磁盘已满')
+    expect(mockedTauriAdapter.writePersistedData).toHaveBeenCalledTimes(1)
+  })
+
+  it('本窗口未改动的字段采用其他窗口的值', () => {
+    expect(mergePersistedData(
+      { items: [], maxItems: 100 },
+      { items: [{ id: 'x' }], maxItems: 100 },
+      { items: [{ id: 'y' }], maxItems: 50 }
+    )).toEqual({ items: [{ id: 'x' }, { id: 'y' }], maxItems: 50 })
+  })
+
+  it('只有其他窗口或程序之外的修改会通知订阅者', async () => {
+    const handler = vi.fn()
+    mockedTauriAdapter.readPersistedData.mockResolvedValue(envelope(10, { items: [] }))
+    await persistenceService.load('todos', { items: [] })
+    await persistenceService.subscribe('todos', handler)
+
+    handlePersistedDataChanged({ dataType: 'todos', updatedAt: 10, external: false })
+    expect(handler).not.toHaveBeenCalled()
+
+    handlePersistedDataChanged({ dataType: 'todos', updatedAt: 12, external: false })
+    handlePersistedDataChanged({ dataType: 'todos', updatedAt: 10, external: true })
+    handlePersistedDataChanged({ dataType: 'notes', updatedAt: 99, external: false })
+    expect(handler).toHaveBeenCalledTimes(2)
+  })
+})
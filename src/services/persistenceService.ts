@@ -1,4 +1,11 @@
-import { tauriAdapter, type PersistedDataType } from '@/adapters/tauriAdapter'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import {
+  tauriAdapter,
+  type DataEnvelope,
+  type PersistedDataChanged,
+  type PersistedDataType
+} from '@/adapters/tauriAdapter'
+import { formatError, isAppError } from './errorService'
 
 const LEGACY_STORAGE_KEYS: Record<PersistedDataType, string> = {
   scenes: 'app_scenes_config',
@@ -8,13 +15,100 @@ const LEGACY_STORAGE_KEYS: Record<PersistedDataType, string> = {
   actions: 'app_actions_config'
 }
 
+// 其他窗口持续写入时最多合并重试的次数
+const MAX_CONFLICT_RETRIES = 3
+
+// 本窗口最近一次读到或写入的数据，写入时据此检查是否被其他窗口改写，冲突时作为三方合并的基准
+const knownEnvelopes = new Map<PersistedDataType, DataEnvelope<unknown>>()
+
+type ChangeHandler = () => void | Promise<void>
+
+const changeHandlers = new Map<PersistedDataType, Set<ChangeHandler>>()
+let changeListener: Promise<UnlistenFn> | null = null
+
 const canUseLocalStorage = () => typeof localStorage !== 'undefined'
 
+type Identified = { id: string }
+
+const isPlainObject = (value: unknown): value is Record<string, unknown> =>
+  typeof value === 'object' && value !== null && !Array.isArray(value)
+
+const isIdList = (value: unknown): value is Identified[] =>
+  Array.isArray(value) && value.every((item) => isPlainObject(item) && typeof item.id === 'string')
+
+const sameJson = (left: unknown, right: unknown) => JSON.stringify(left) === JSON.stringify(right)
+
+function mergeById(base: Identified[], mine: Identified[], theirs: Identified[]): Identified[] {
+  const baseById = new Map(base.map((item) => [item.id, item]))
+  const theirsById = new Map(theirs.map((item) => [item.id, item]))
+  const mineIds = new Set(mine.map((item) => item.id))
+  const merged: Identified[] = []
+
+  for (const item of mine) {
+    const baseItem = baseById.get(item.id)
+    const theirItem = theirsById.get(item.id)
+    const changedByMe = !baseItem || !sameJson(item, baseItem)
+    if (theirItem) {
+      merged.push(changedByMe ? item : theirItem)
+    } else if (changedByMe) {
+      // 本窗口新增的条目，或其他窗口删除但本窗口修改过的条目
+      merged.push(item)
+    }
+  }
+  // 其他窗口新增的条目；本窗口删除的条目不再加回
+  for (const item of theirs) {
+    if (!mineIds.has(item.id) && !baseById.has(item.id)) {
+      merged.push(item)
+    }
+  }
+  return merged
+}
+
+function mergeValue(base: unknown, mine: unknown, theirs: unknown): unknown {
+  if (theirs === undefined) return mine
+  if (isIdList(mine) && isIdList(theirs)) {
+    return mergeById(isIdList(base) ? base : [], mine, theirs)
+  }
+  // 本窗口没有改动的字段采用其他窗口的值
+  return sameJson(mine, base) ? theirs : mine
+}
+
+/**
+ * 三方合并：以本窗口上次读到的数据为基准，保留两边各自的改动。
+ * 带 id 的条目列表按条目合并，同一条目两边都改过时以本窗口为准。
+ */
+export function mergePersistedData<T>(base: unknown, mine: T, theirs: unknown): T {
+  if (!isPlainObject(mine) || !isPlainObject(theirs)) {
+    return mergeValue(base, mine, theirs) as T
+  }
+  const baseObject = isPlainObject(base) ? base : {}
+  const merged: Record<string, unknown> = { ...theirs }
+  for (const [key, value] of Object.entries(mine)) {
+    merged[key] = mergeValue(baseObject[key], value, theirs[key])
+  }
+  return merged as T
+}
+
+/** 收到 persisted-data-changed 时，跳过本窗口自己写入的版本，其余变化交给订阅者重新读取 */
+export function handlePersistedDataChanged(change: PersistedDataChanged) {
+  const known = knownEnvelopes.get(change.dataType)
+  if (!change.external && known && change.updatedAt <= known.updatedAt) return
+
+  for (const handler of changeHandlers.get(change.dataType) ?? []) {
+    void Promise.resolve(handler()).catch((error) => {
+      console.error(`重新加载 ${change.dataType} 失败:`, error)
+    })
+  }
+}
+
 export const persistenceService = {
   async load<T>(dataType: PersistedDataType, fallback: T): Promise<T> {
     try {
       const envelope = await tauriAdapter.readPersistedData<T>(dataType)
-      if (envelope) return envelope.data
+      if (envelope) {
+        knownEnvelopes.set(dataType, envelope)
+        return envelope.data
+      }
     } catch (error) {
       console.error(`读取 ${dataType} 文件失败，尝试旧本地存储:`, error)
     }
@@ -22,13 +116,47 @@ export const persistenceService = {
     return this.loadLegacy(dataType, fallback)
   },
 
-  async save<T>(dataType: PersistedDataType, data: T): Promise<void> {
-    try {
-      await tauriAdapter.writePersistedData(dataType, data)
-    } catch (error) {
-      console.error(`保存 ${dataType} 文件失败:`, error)
-      throw error
+  /**
+   * 写入前检查数据是否已被其他窗口改写，冲突时与当前数据合并后重试。
+   * 发生合并时返回实际写入的数据，调用方应以它替换内存中的状态；没有冲突时返回 null。
+   */
+  async save<T>(dataType: PersistedDataType, data: T): Promise<T | null> {
+    let pending = data
+    let merged = false
+    for (let attempt = 0; attempt <= MAX_CONFLICT_RETRIES; attempt += 1) {
+      const known = knownEnvelopes.get(dataType)
+      try {
+        const envelope = await tauriAdapter.writePersistedData(dataType, pending, known?.updatedAt)
+        knownEnvelopes.set(dataType, envelope)
+        return merged ? pending : null
+      } catch (error) {
+        if (!isAppError(error) || error.code !== 'Conflict') {
+          console.error(`保存 ${dataType} 文件失败:`, error)
+          throw formatError(error)
+        }
+        const current = error.details.current as DataEnvelope<unknown> | null | undefined
+        pending = mergePersistedData(known?.data, pending, current?.data)
+        merged = true
+        if (current) {
+          knownEnvelopes.set(dataType, current)
+        } else {
+          knownEnvelopes.delete(dataType)
+        }
+      }
     }
+    throw new Error(`${dataType} 正在被其他窗口频繁修改，请稍后重试`)
+  },
+
+  /** 其他窗口或程序之外修改了该类数据时调用 handler，通常用于重新读取 */
+  async subscribe(dataType: PersistedDataType, handler: ChangeHandler): Promise<void> {
+    const handlers = changeHandlers.get(dataType) ?? new Set<ChangeHandler>()
+    handlers.add(handler)
+    changeHandlers.set(dataType, handlers)
+
+    changeListener ??= listen<PersistedDataChanged>('persisted-data-changed', (event) => {
+      handlePersistedDataChanged(event.payload)
+    })
+    await changeListener
   },
 
   loadLegacy<T>(dataType: PersistedDataType, fallback: T): T {
@@ -41,5 +169,11 @@ export const persistenceService = {
       console.error(`读取 ${dataType} 旧本地存储失败:`, error)
       return fallback
     }
+  },
+
+  /** 测试用：清空已知的数据版本和订阅 */
+  resetKnownVersions() {
+    knownEnvelopes.clear()
+    changeHandlers.clear()
   }
 }
@@ -6,7 +6,8 @@ import type { Scene } from '@/types'
 vi.mock('@/services/persistenceService', () => ({
   persistenceService: {
     load: vi.fn(),
-    save: vi.fn()
+    save: vi.fn(),
+    subscribe: vi.fn()
   }
 }))
 
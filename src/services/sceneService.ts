@@ -54,6 +54,10 @@ export const sceneService = {
     return persistenceService.save('scenes', { scenes })
   },
 
+  onScenesChanged(handler: () => void | Promise<void>) {
+    return persistenceService.subscribe('scenes', handler)
+  },
+
   createScene(scene: Omit<Scene, 'id' | 'createdAt' | 'updatedAt'>): Scene {
     const now = Date.now()
     return {
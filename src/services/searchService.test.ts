@@ -8,7 +8,7 @@ import { useTodoStore } from '@/stores/todoStore'
 import { DEFAULT_CONFIG, type App, type Config, type Scene } from '@/types'
 import type { ClipboardItem } from '@/types'
 import type { TodoItem } from '@/types/todo'
-import { parseSearchQuery, searchService } from './searchService'
+import { parseSearchQuery, searchService, splitTagTerms } from './searchService'
 
 vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn(),
@@ -50,6 +50,7 @@ const makeConfig = (): Config => ({
       name: '晨会工具',
       path: 'C:\\Tools\\meeting.exe',
       category: 'cat_1',
+      tags: ['Work', 'daily'],
       itemType: 'app',
       createdAt: 1
     } satisfies App
@@ -143,6 +144,19 @@ describe('searchService', () => {
     await expect(searchService.search('s:晨会')).resolves.toMatchObject([{ type: 'scene' }])
   })
 
+  it('以 # 开头的词按标签筛选应用', async () => {
+    expect(splitTagTerms('#work 晨会 #Daily')).toEqual({ tags: ['work', 'daily'], text: '晨会' })
+
+    const tagged = await searchService.search('#work 晨会')
+    expect(tagged.filter((result) => result.type === 'app')).toMatchObject([{ data: { id: 'app_1' } }])
+
+    const byTagText = await searchService.search('daily')
+    expect(byTagText.some((result) => result.type === 'app')).toBe(true)
+
+    const missing = await searchService.search('#home')
+    expect(missing.some((result) => result.type === 'app')).toBe(false)
+  })
+
   it('功能禁用后不会返回对应搜索源结果', async () => {
     useAppStore().config.settings.quickNotesEnabled = false
 
@@ -24,6 +24,7 @@ interface AppSearchIndexItem {
   id: string
   app: App
   searchableText: string
+  tags: string[]
 }
 
 const SEARCH_RESULT_LIMIT = 10
@@ -210,13 +211,27 @@ function isTodoSearchEnabled(settings: AppSettings) {
   return settings.quickerEnabled !== false && settings.todoScheduleEnabled !== false
 }
 
+// 以 # 开头的词按标签筛选，其余文字匹配名称、路径和标签
+export function splitTagTerms(query: string): { tags: string[]; text: string } {
+  const tags: string[] = []
+  const words = query.split(/\s+/).filter((word) => {
+    if (word.length > 1 && word.startsWith('#')) {
+      tags.push(word.slice(1).toLowerCase())
+      return false
+    }
+    return true
+  })
+  return { tags, text: words.join(' ').trim() }
+}
+
 function searchApps(query: string): SearchResult[] {
   const appStore = useAppStore()
-  const lowerQuery = query.toLowerCase()
+  const { tags, text } = splitTagTerms(query)
+  const lowerQuery = text.toLowerCase()
   const index = getAppSearchIndex()
 
   return index
-    .filter((item) => item.searchableText.includes(lowerQuery))
+    .filter((item) => tags.every((tag) => item.tags.includes(tag)) && item.searchableText.includes(lowerQuery))
     .sort((a, b) => {
       const aStartsWith = a.app.name.toLowerCase().startsWith(lowerQuery)
       const bStartsWith = b.app.name.toLowerCase().startsWith(lowerQuery)
@@ -238,7 +253,7 @@ function searchApps(query: string): SearchResult[] {
 function getAppSearchIndex(): AppSearchIndexItem[] {
   const apps = Object.values(useAppStore().config.apps)
   const signature = apps
-    .map((app) => `${app.id}:${app.name}:${app.path}:${app.lastLaunched ?? ''}`)
+    .map((app) => `${app.id}:${app.name}:${app.path}:${app.lastLaunched ?? ''}:${(app.tags ?? []).join(',')}`)
     .join('|')
 
   if (signature === appIndexSignature) return appIndex
@@ -247,7 +262,8 @@ function getAppSearchIndex(): AppSearchIndexItem[] {
   appIndex = apps.map((app) => ({
     id: app.id,
     app,
-    searchableText: `${app.name}\n${app.path}`.toLowerCase()
+    searchableText: `${app.name}\n${app.path}\n${(app.tags ?? []).join('\n')}`.toLowerCase(),
+    tags: (app.tags ?? []).map((tag) => tag.toLowerCase())
   }))
 
   return appIndex
@@ -7,6 +7,7 @@ import {
   type ActionTemplate
 } from '@/types'
 import { persistenceService } from '@/services/persistenceService'
+import { formatError } from '@/services/errorService'
 
 interface ActionsStorage {
   enabled?: string[]
@@ -39,6 +40,7 @@ export const useActionsStore = defineStore('actions', {
 
       await this.loadFromStorage()
       this.initialized = true
+      await persistenceService.subscribe('actions', () => this.loadFromStorage())
     },
 
     async loadFromStorage() {
@@ -57,7 +59,10 @@ export const useActionsStore = defineStore('actions', {
       const config = {
         enabled: this.enabledActions
       }
-      await persistenceService.save('actions', config)
+      const merged = await persistenceService.save('actions', config)
+      if (merged) {
+        this.enabledActions = merged.enabled
+      }
     },
 
     // 启用/禁用动作
@@ -113,7 +118,7 @@ export const useActionsStore = defineStore('actions', {
         return {
           success: false,
           output: '',
-          errorOutput: String(error)
+          errorOutput: formatError(error)
         }
       } finally {
         this.executing = false
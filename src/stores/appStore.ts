@@ -1,8 +1,9 @@
 import { defineStore } from 'pinia'
 import { listen, type UnlistenFn } from '@tauri-apps/api/event'
-import type { App, Category, Config, AppSettings, ManagedItemType } from '@/types'
+import type { App, Category, CategoryAppsDisposal, CategoryRule, Config, AppSettings, LaunchOptions, RuleChange } from '@/types'
 import { DEFAULT_CONFIG, canCheckForUpdates } from '@/types'
-import { configService } from '@/services/configService'
+import { configService, type AddAppInput } from '@/services/configService'
+import { formatError } from '@/services/errorService'
 
 let configChangedUnlisten: UnlistenFn | null = null
 
@@ -19,10 +20,22 @@ export const useAppStore = defineStore('app', {
   }),
 
   getters: {
-    // 获取所有分类（按 order 排序）
+    // 获取所有分类：同级按 order 排序，子分类紧跟在上级分类之后
     categories: (state): Category[] => {
-      return Object.values(state.config.categories)
+      const all = Object.values(state.config.categories)
+      const ids = new Set(all.map(category => category.id))
+      const childrenOf = (parentId?: string) => all
+        .filter(category => (category.parentId && ids.has(category.parentId) ? category.parentId : undefined) === parentId)
         .sort((a, b) => a.order - b.order)
+      const ordered: Category[] = []
+      const visit = (parentId?: string) => {
+        for (const category of childrenOf(parentId)) {
+          ordered.push(category)
+          visit(category.id)
+        }
+      }
+      visit()
+      return ordered
     },
 
     // 获取当前分类的应用（支持搜索过滤）
@@ -167,7 +180,7 @@ export const useAppStore = defineStore('app', {
         this.lastSaveError = null
       } catch (error) {
         console.error('保存配置失败:', error)
-        this.lastSaveError = String(error)
+        this.lastSaveError = formatError(error)
         if (options.throwOnError) {
           throw error
         }
@@ -201,30 +214,37 @@ export const useAppStore = defineStore('app', {
       this.debouncedSaveConfig()
     },
 
-    async addCategory(name: string): Promise<Category> {
-      const category = await configService.addCategory(name)
-      this.config.categories[category.id] = category
+    // 分类和标签相关修改由后端完成并保持 Category.apps 与程序所属分类一致，完成后重新读取配置
+    async runConfigCommand<T>(command: () => Promise<T>): Promise<T> {
+      await this.flushPendingSave()
+      const result = await command()
+      this.applyConfig(await configService.loadConfig())
+      return result
+    },
+
+    async addCategory(name: string, parentId?: string): Promise<Category> {
+      const category = await this.runConfigCommand(() => configService.addCategory(name, parentId))
       if (!this.currentCategory) this.currentCategory = category.id
-      await this.saveConfig()
       return category
     },
 
-    async deleteCategory(categoryId: string) {
-      const category = this.config.categories[categoryId]
-      if (category) {
-        category.apps.forEach(appId => delete this.config.apps[appId])
-        delete this.config.categories[categoryId]
-        if (this.currentCategory === categoryId) {
-          this.currentCategory = this.categories[0]?.id || null
-        }
-        await this.saveConfig()
-      }
+    async renameCategory(categoryId: string, name: string) {
+      await this.runConfigCommand(() => configService.renameCategory(categoryId, name))
+    },
+
+    async deleteCategory(categoryId: string, apps: CategoryAppsDisposal = { mode: 'delete' }) {
+      if (!this.config.categories[categoryId]) return
+      const removedAppIds = await this.runConfigCommand(() => configService.deleteCategory(categoryId, apps))
+      removedAppIds.forEach(appId => delete this.iconUrlCache[appId])
+    },
+
+    async moveCategory(categoryId: string, parentId?: string) {
+      await this.runConfigCommand(() => configService.moveCategory(categoryId, parentId))
     },
 
-    async addApp(appData: { name: string; path: string; category: string; itemType?: ManagedItemType }): Promise<App> {
-      const app = await configService.addApp(appData)
-      this.config.apps[app.id] = app
-      this.config.categories[appData.category]?.apps.push(app.id)
+    async addApp(appData: AddAppInput): Promise<App> {
+      // 后端可能按自动分类规则改变分类、添加标签或创建“未分类”，添加后重新读取配置
+      const app = await this.runConfigCommand(() => configService.addApp(appData))
 
       // 预加载新应用的图标 URL
       if (app.icon) {
@@ -242,7 +262,6 @@ export const useAppStore = defineStore('app', {
         })
       }
 
-      await this.saveConfig()
       return app
     },
 
@@ -272,6 +291,23 @@ export const useAppStore = defineStore('app', {
       }
     },
 
+    async launchAppAsAdmin(appId: string) {
+      await configService.launchAppAsAdmin(appId)
+      const app = this.config.apps[appId]
+      if (app) {
+        app.lastLaunched = Date.now()
+        this.debouncedSaveConfig()
+      }
+    },
+
+    async setLaunchOptions(appId: string, options: LaunchOptions) {
+      await this.runConfigCommand(() => configService.updateAppLaunchOptions(appId, options))
+    },
+
+    async duplicateApp(appId: string): Promise<App> {
+      return this.runConfigCommand(() => configService.duplicateApp(appId))
+    },
+
     setSearchQuery(query: string) {
       this.searchQuery = query
     },
@@ -298,56 +334,69 @@ export const useAppStore = defineStore('app', {
       this.debouncedSaveConfig() // 重排序使用防抖保存
     },
 
+    // 按分类列表中的位置调整顺序，只在同一上级分类下生效
     async reorderCategories(fromIndex: number, toIndex: number) {
       const categoryList = this.categories
       if (fromIndex < 0 || fromIndex >= categoryList.length || toIndex < 0 || toIndex >= categoryList.length) {
         return
       }
 
-      // 获取移动的分类
       const movedCategory = categoryList[fromIndex]
+      const targetCategory = categoryList[toIndex]
+      if (movedCategory.parentId !== targetCategory.parentId) return
 
-      // 更新所有分类的 order
-      if (fromIndex < toIndex) {
-        // 向后移动
-        for (let i = fromIndex + 1; i <= toIndex; i++) {
-          categoryList[i].order--
-        }
-      } else {
-        // 向前移动
-        for (let i = toIndex; i < fromIndex; i++) {
-          categoryList[i].order++
-        }
-      }
-
-      movedCategory.order = toIndex
+      const siblingIds = categoryList
+        .filter(category => category.parentId === movedCategory.parentId && category.id !== movedCategory.id)
+        .map(category => category.id)
+      const targetIndex = siblingIds.indexOf(targetCategory.id)
+      siblingIds.splice(fromIndex < toIndex ? targetIndex + 1 : targetIndex, 0, movedCategory.id)
 
-      this.debouncedSaveConfig() // 重排序使用防抖保存
+      await this.runConfigCommand(() => configService.reorderCategories(movedCategory.parentId, siblingIds))
     },
 
-    async moveAppToCategory(appId: string, targetCategoryId: string) {
+    async moveAppToCategory(appId: string, targetCategoryId: string, index?: number) {
       const app = this.config.apps[appId]
       if (!app) return
 
-      const oldCategoryId = app.category
+      // 目标分类和当前分类相同且未指定位置时不做任何操作
+      if (app.category === targetCategoryId && index === undefined) return
 
-      // 如果目标分类和当前分类相同，不做任何操作
-      if (oldCategoryId === targetCategoryId) return
+      await this.runConfigCommand(() => configService.moveAppToCategory(appId, targetCategoryId, index))
+    },
 
-      // 从原分类中移除
-      const oldCategory = this.config.categories[oldCategoryId]
-      if (oldCategory) {
-        oldCategory.apps = oldCategory.apps.filter(id => id !== appId)
-      }
+    async linkAppToCategory(appId: string, categoryId: string) {
+      await this.runConfigCommand(() => configService.linkAppToCategory(appId, categoryId))
+    },
 
-      // 添加到新分类
-      const newCategory = this.config.categories[targetCategoryId]
-      if (newCategory) {
-        newCategory.apps.push(appId)
-        app.category = targetCategoryId
-      }
+    async unlinkAppFromCategory(appId: string, categoryId: string) {
+      await this.runConfigCommand(() => configService.unlinkAppFromCategory(appId, categoryId))
+    },
+
+    // 把程序的标签改为 tags，比较时不区分大小写
+    async saveCategoryRules(rules: CategoryRule[]) {
+      await this.runConfigCommand(() => configService.saveCategoryRules(rules))
+    },
+
+    async applyCategoryRules(appIds?: string[]): Promise<RuleChange[]> {
+      return this.runConfigCommand(() => configService.applyCategoryRules(appIds))
+    },
 
-      await this.saveConfig()
+    async setAppTags(appId: string, tags: string[]) {
+      const app = this.config.apps[appId]
+      if (!app) return
+
+      const current = app.tags ?? []
+      const lower = (values: string[]) => new Set(values.map(tag => tag.toLowerCase()))
+      const wanted = lower(tags)
+      const existing = lower(current)
+      const added = tags.filter(tag => !existing.has(tag.toLowerCase()))
+      const removed = current.filter(tag => !wanted.has(tag.toLowerCase()))
+      if (added.length === 0 && removed.length === 0) return
+
+      await this.runConfigCommand(async () => {
+        if (removed.length > 0) await configService.removeAppTags(appId, removed)
+        if (added.length > 0) await configService.addAppTags(appId, added)
+      })
     },
 
     // 检查应用是否已存在（根据路径）
@@ -79,6 +79,9 @@ export const useClipboardStore = defineStore('clipboard', {
       // 从统一 JSON 文件加载历史，旧 localStorage 仅作为兜底来源
       await this.loadFromStorage()
       this.initialized = true
+      await persistenceService.subscribe('clipboard', () => {
+        if (!this.isDirty) return this.loadFromStorage()
+      })
       // 仅在功能启用时开始监控剪贴板
       if (this.isEnabled) {
         this.startMonitoring()
@@ -112,10 +115,14 @@ export const useClipboardStore = defineStore('clipboard', {
 
     async saveToStorage() {
       try {
-        await persistenceService.save('clipboard', {
+        const merged = await persistenceService.save('clipboard', {
           items: this.items,
           maxItems: this.maxItems
         })
+        if (merged) {
+          this.items = merged.items
+          this.maxItems = merged.maxItems
+        }
         this.isDirty = false
       } catch (error) {
         console.error('保存剪贴板历史失败:', error)
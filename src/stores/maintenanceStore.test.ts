@@ -241,4 +241,57 @@ describe('useMaintenanceStore', () => {
     expect(logText).toContain('已更新程序')
     expect(logText).toContain('1.0.0 -> 1.1.0')
   })
+  it('清理图标后重新读取配置和占用统计', async () => {
+    const config = makeConfig()
+    const usage = {
+      dataDir: '/data',
+      sections: [{ name: 'icons', isDir: true, bytes: 10, files: 1 }],
+      totalBytes: 10,
+      orphanedIcons: [],
+      missingIcons: []
+    }
+    mockedInvoke.mockImplementation(async (command) => {
+      if (command === 'collect_icon_garbage') {
+        return { removed: [{ fileName: 'old.png', bytes: 4 }], freedBytes: 4, reextracted: ['browser'], cleared: [], errors: [] }
+      }
+      if (command === 'get_storage_usage') return usage
+      if (command === 'load_config') return config
+      return undefined
+    })
+
+    const maintenanceStore = useMaintenanceStore()
+    const result = await maintenanceStore.collectIconGarbage()
+
+    expect(result.freedBytes).toBe(4)
+    expect(mockedInvoke).toHaveBeenCalledWith('load_config')
+    expect(maintenanceStore.storageUsage).toEqual(usage)
+    expect(maintenanceStore.batchOperating).toBe(false)
+  })
+  it('完整性修复后重新读取配置', async () => {
+    const config = makeConfig()
+    const report = {
+      issues: [{
+        file: 'config.json',
+        kind: 'appCategoryMissing',
+        itemId: 'browser',
+        message: '程序 BitBrowser 所属的分类 deleted 不存在',
+        repairable: true,
+        repaired: true
+      }],
+      repaired: 1
+    }
+    mockedInvoke.mockImplementation(async (command) => {
+      if (command === 'check_data_integrity') return report
+      if (command === 'load_config') return config
+      return undefined
+    })
+
+    const maintenanceStore = useMaintenanceStore()
+    await maintenanceStore.checkDataIntegrity(true)
+
+    expect(mockedInvoke).toHaveBeenCalledWith('check_data_integrity', { repair: true })
+    expect(mockedInvoke).toHaveBeenCalledWith('load_config')
+    expect(maintenanceStore.integrityReport).toEqual(report)
+    expect(maintenanceStore.checkingIntegrity).toBe(false)
+  })
 })
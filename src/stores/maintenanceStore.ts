@@ -8,10 +8,14 @@ import type {
   BatchOperationResult,
   MaintenanceLogEntry,
   MaintenanceOperation,
-  MaintenanceProgressEvent
+  MaintenanceProgressEvent,
+  StorageUsage,
+  IconGcResult,
+  IntegrityReport
 } from '@/types'
 import { canCheckForUpdates } from '@/types'
 import { configService } from '@/services/configService'
+import { formatError } from '@/services/errorService'
 
 let maintenanceProgressUnlisten: UnlistenFn | null = null
 
@@ -48,6 +52,14 @@ export const useMaintenanceStore = defineStore('maintenance', {
     batchProgress: 0,
     batchTotal: 0,
 
+    // 数据完整性检查
+    checkingIntegrity: false,
+    integrityReport: null as IntegrityReport | null,
+
+    // 存储占用
+    loadingStorageUsage: false,
+    storageUsage: null as StorageUsage | null,
+
     // 进度事件与导出日志
     progressMessage: '',
     maintenanceLogs: [] as MaintenanceLogEntry[],
@@ -276,6 +288,46 @@ export const useMaintenanceStore = defineStore('maintenance', {
       }
     },
 
+    // 检查数据完整性；repair 为 true 时修复后重新读取配置
+    async checkDataIntegrity(repair = false) {
+      this.checkingIntegrity = true
+      try {
+        const report = await invoke<IntegrityReport>('check_data_integrity', { repair })
+        if (report.repaired > 0) {
+          await useAppStore().reloadConfig()
+        }
+        this.integrityReport = report
+        return report
+      } finally {
+        this.checkingIntegrity = false
+      }
+    },
+
+    async loadStorageUsage() {
+      this.loadingStorageUsage = true
+      try {
+        this.storageUsage = await invoke<StorageUsage>('get_storage_usage')
+        return this.storageUsage
+      } finally {
+        this.loadingStorageUsage = false
+      }
+    },
+
+    // 重新提取缺失的图标并删除孤立图标，完成后刷新配置和占用统计
+    async collectIconGarbage() {
+      this.batchOperating = true
+      try {
+        const result = await invoke<IconGcResult>('collect_icon_garbage')
+        if (result.reextracted.length > 0 || result.cleared.length > 0) {
+          await useAppStore().reloadConfig()
+        }
+        await this.loadStorageUsage()
+        return result
+      } finally {
+        this.batchOperating = false
+      }
+    },
+
     async acceptCurrentBaseline(appIds: string[]) {
       const appStore = useAppStore()
       const targetIds = appIds.filter((appId, index) => appIds.indexOf(appId) === index)
@@ -304,7 +356,7 @@ export const useMaintenanceStore = defineStore('maintenance', {
             message: '已接受当前状态为新基准'
           })
         } catch (error) {
-          errors.push({ appId, error: String(error) })
+          errors.push({ appId, error: formatError(error) })
           this.batchProgress++
           this.recordProgress({
             operation: 'baseline',
@@ -315,7 +367,7 @@ export const useMaintenanceStore = defineStore('maintenance', {
             succeeded: this.batchProgress - errors.length,
             failed: errors.length,
             status: 'failed',
-            message: String(error)
+            message: formatError(error)
           })
         }
       }
@@ -390,6 +442,8 @@ export const useMaintenanceStore = defineStore('maintenance', {
       this.updateResults = []
       this.maintenanceLogs = []
       this.progressMessage = ''
+      this.storageUsage = null
+      this.integrityReport = null
       this.showOnlyInvalid = false
       this.showOnlyUpdates = false
     },
@@ -59,6 +59,10 @@ export const useNotesStore = defineStore('notes', {
       if (this.initialized) return
       await this.loadFromStorage()
       this.initialized = true
+      // 有未保存的修改时先不重新读取，保存时会与其他窗口的修改合并
+      await persistenceService.subscribe('notes', () => {
+        if (!this.isDirty) return this.loadFromStorage()
+      })
     },
 
     async loadFromStorage() {
@@ -72,9 +76,12 @@ export const useNotesStore = defineStore('notes', {
 
     async saveToStorage() {
       try {
-        await persistenceService.save('notes', {
+        const merged = await persistenceService.save('notes', {
           notes: this.notes
         })
+        if (merged) {
+          this.notes = merged.notes
+        }
         this.isDirty = false
       } catch (error) {
         console.error('保存便签失败:', error)
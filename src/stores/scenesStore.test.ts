@@ -17,7 +17,8 @@ vi.mock('@tauri-apps/api/event', () => ({
 vi.mock('@/services/persistenceService', () => ({
   persistenceService: {
     load: vi.fn(),
-    save: vi.fn()
+    save: vi.fn(),
+    subscribe: vi.fn()
   }
 }))
 
@@ -113,6 +114,28 @@ describe('useScenesStore', () => {
     expect(mockedInvoke).not.toHaveBeenCalled()
   })
 
+  it('打开网址、文件夹和文件时原样传给后端，不拼接成脚本', async () => {
+    const store = useScenesStore()
+    store.scenes = [
+      makeScene({
+        actions: [
+          { id: 'url', type: 'open_url', params: { url: 'https://example.com/?q="a"' } },
+          { id: 'folder', type: 'open_folder', params: { path: '/home/user/My Docs' } },
+          { id: 'file', type: 'open_file', params: { path: '/home/user/a$(b).txt' } }
+        ]
+      })
+    ]
+
+    const result = await store.executeScene('scene_1')
+
+    expect(result).toMatchObject({ success: true, completedActions: 3 })
+    expect(mockedInvoke.mock.calls).toEqual([
+      ['open_path', { target: 'https://example.com/?q="a"' }],
+      ['open_path', { target: '/home/user/My Docs' }],
+      ['open_path', { target: '/home/user/a$(b).txt' }]
+    ])
+  })
+
   it('复制场景会生成新 ID 并复制动作列表', () => {
     const store = useScenesStore()
     store.scenes = [
@@ -5,6 +5,8 @@ import type { Scene, SceneAction, SceneActionExecutionLog, SceneFailureStrategy
 import { canUseProcessActions } from '@/types'
 import { getSceneActionName } from '@/services/sceneActionRegistry'
 import { sceneService } from '@/services/sceneService'
+import { configService } from '@/services/configService'
+import { formatError } from '@/services/errorService'
 
 export const useScenesStore = defineStore('scenes', {
   state: () => ({
@@ -42,6 +44,7 @@ export const useScenesStore = defineStore('scenes', {
 
       await this.loadFromStorage()
       this.initialized = true
+      await sceneService.onScenesChanged(() => this.loadFromStorage())
     },
 
     async loadFromStorage() {
@@ -54,7 +57,10 @@ export const useScenesStore = defineStore('scenes', {
 
     // 保存配置到统一 JSON 文件
     async saveConfig() {
-      await sceneService.saveScenes(this.scenes)
+      const merged = await sceneService.saveScenes(this.scenes)
+      if (merged) {
+        this.scenes = merged.scenes
+      }
     },
 
     // 添加场景
@@ -233,11 +239,7 @@ if ($process) {
                 return { success: true, skipped: true }
               }
             }
-            await invoke('execute_action_template', {
-              scriptContent: 'Start-Process -FilePath $env:APP_PATH -Verb RunAs',
-              appPath: app.path,
-              appName: app.name
-            })
+            await appStore.launchAppAsAdmin(action.params.appId)
             // 执行附属操作
             if (processName && (action.params.waitWindow || action.params.sendKeys)) {
               const result = await this.executeLaunchOptions(action, processName)
@@ -252,11 +254,7 @@ if ($process) {
             if (!action.params.url) {
               return { success: false, error: '未指定网址' }
             }
-            await invoke('execute_action_template', {
-              scriptContent: `Start-Process "${action.params.url}"`,
-              appPath: '',
-              appName: ''
-            })
+            await configService.openPath(action.params.url)
             return { success: true }
           }
 
@@ -264,11 +262,7 @@ if ($process) {
             if (!action.params.path) {
               return { success: false, error: '未指定文件夹路径' }
             }
-            await invoke('execute_action_template', {
-              scriptContent: `Start-Process explorer "${action.params.path}"`,
-              appPath: '',
-              appName: ''
-            })
+            await configService.openPath(action.params.path)
             return { success: true }
           }
 
@@ -276,11 +270,7 @@ if ($process) {
             if (!action.params.path) {
               return { success: false, error: '未指定文件路径' }
             }
-            await invoke('execute_action_template', {
-              scriptContent: `Start-Process "${action.params.path}"`,
-              appPath: '',
-              appName: ''
-            })
+            await configService.openPath(action.params.path)
             return { success: true }
           }
 
@@ -358,7 +348,7 @@ $toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
             return { success: false, error: '未知的动作类型' }
         }
       } catch (error) {
-        return { success: false, error: String(error) }
+        return { success: false, error: formatError(error) }
       }
     },
 
@@ -518,7 +508,7 @@ $toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
           success: false,
           completedActions,
           totalActions,
-          error: String(error)
+          error: formatError(error)
         }
       } finally {
         this.executing = false
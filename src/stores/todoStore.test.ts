@@ -7,7 +7,8 @@ import { persistenceService } from '@/services/persistenceService'
 vi.mock('@/services/persistenceService', () => ({
   persistenceService: {
     load: vi.fn(),
-    save: vi.fn()
+    save: vi.fn(),
+    subscribe: vi.fn()
   }
 }))
 
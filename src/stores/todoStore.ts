@@ -65,6 +65,7 @@ export const useTodoStore = defineStore('todoSchedule', {
       this.selectedDate = getTodayDateKey()
       await this.loadFromStorage()
       this.initialized = true
+      await persistenceService.subscribe('todos', () => this.loadFromStorage())
     },
 
     async loadFromStorage() {
@@ -78,7 +79,10 @@ export const useTodoStore = defineStore('todoSchedule', {
     },
 
     async saveToStorage() {
-      await persistenceService.save('todos', { items: this.items })
+      const merged = await persistenceService.save('todos', { items: this.items })
+      if (merged) {
+        this.items = merged.items
+      }
     },
 
     setSelectedDate(date: string) {
@@ -18,8 +18,14 @@ export interface App {
   id: string
   name: string
   path: string
-  category: string
+  category: string  // 主分类
+  extraCategories?: string[]  // 同时出现的其他分类
+  tags?: string[]
   itemType?: ManagedItemType
+  args?: string[]  // 启动参数，逐个传给程序
+  workingDir?: string
+  env?: Record<string, string>  // 追加或覆盖的环境变量
+  windowState?: WindowState
   icon?: string  // base64 编码的图标
   lastLaunched?: number
   createdAt: number
@@ -30,15 +36,61 @@ export interface App {
   lastValidatedAt?: number
 }
 
+// 启动时的窗口状态，仅 Windows 生效
+export type WindowState = 'normal' | 'minimized' | 'maximized'
+
+// 程序的启动选项
+export interface LaunchOptions {
+  args: string[]
+  workingDir?: string
+  env: Record<string, string>
+  windowState?: WindowState
+}
+
 // 分类数据模型
 export interface Category {
   id: string
   name: string
   icon?: string
   apps: string[]  // App IDs
-  order: number
+  order: number  // 同级分类之间的顺序
+  parentId?: string  // 上级分类，为空时是顶级分类
+}
+
+// 标签及使用它的程序数量
+export interface TagCount {
+  tag: string
+  count: number
+}
+
+// 自动分类规则：设置了的条件全部满足时归入 categoryId 并添加 tags
+export interface CategoryRule {
+  id: string
+  name: string
+  enabled: boolean
+  pathGlob?: string  // * 不跨越目录，** 可以跨越目录
+  fileNamePattern?: string  // 匹配文件名的正则表达式
+  itemType?: ManagedItemType
+  publisher?: string  // 发布者名称包含的文字，仅 Windows
+  categoryId?: string
+  tags?: string[]
 }
 
+// 重新应用规则时某个程序的变化
+export interface RuleChange {
+  appId: string
+  appName: string
+  fromCategory: string
+  toCategory: string
+  addedTags: string[]
+  ruleIds: string[]
+}
+
+// 删除分类时其中程序的去向
+export type CategoryAppsDisposal =
+  | { mode: 'move'; targetId: string }
+  | { mode: 'delete' }
+
 // 剪贴板历史项
 export interface ClipboardItem {
   id: string
@@ -81,6 +133,10 @@ export interface AppSettings {
   // 待办日程表
   todoScheduleEnabled?: boolean  // 待办日程表开关
   todoShortcut?: string  // 待办日程表快捷键（默认 Alt+T）
+  // 备份保留策略，各项满足其一即保留；留空时使用默认值，总大小留空表示不限制
+  backupKeepLast?: number  // 保留最近的备份份数（默认 10）
+  backupKeepDailyDays?: number  // 最近几天每天保留一份（默认 7）
+  backupMaxTotalMb?: number  // 备份总大小上限（MB）
 }
 
 // 完整配置
@@ -89,6 +145,7 @@ export interface Config {
   categories: Record<string, Category>
   apps: Record<string, App>
   settings: AppSettings
+  categoryRules?: CategoryRule[]  // 按顺序匹配
 }
 
 // 卡片尺寸配置
@@ -112,7 +169,7 @@ export const DEFAULT_THEME_COLORS = [
 
 // 默认配置
 export const DEFAULT_CONFIG: Config = {
-  version: '1.0',
+  version: '2.0',
   categories: {},
   apps: {},
   settings: {
@@ -139,7 +196,10 @@ export const DEFAULT_CONFIG: Config = {
     calculatorEnabled: true,
     // 待办日程表默认设置
     todoScheduleEnabled: true,
-    todoShortcut: 'Alt+T'
+    todoShortcut: 'Alt+T',
+    // 备份保留默认设置
+    backupKeepLast: 10,
+    backupKeepDailyDays: 7
   }
 }
 
@@ -436,6 +496,68 @@ export interface UpdateCheckResult {
   }
 }
 
+// 数据目录占用
+export interface SectionUsage {
+  name: string
+  isDir: boolean
+  bytes: number
+  files: number
+}
+
+export interface IconFile {
+  fileName: string
+  bytes: number
+}
+
+export interface MissingIcon {
+  appId: string
+  appName: string
+  icon: string
+}
+
+export interface StorageUsage {
+  dataDir: string
+  sections: SectionUsage[]
+  totalBytes: number
+  orphanedIcons: IconFile[]
+  missingIcons: MissingIcon[]
+}
+
+// 图标清理结果
+export interface IconGcResult {
+  removed: IconFile[]
+  freedBytes: number
+  reextracted: string[]
+  cleared: string[]
+  errors: { appId: string; error: string }[]
+}
+
+// 数据完整性检查
+export type IntegrityIssueKind =
+  | 'invalidStructure'
+  | 'appIdMismatch'
+  | 'categoryAppMissing'
+  | 'appCategoryMissing'
+  | 'appNotInCategory'
+  | 'categoryParentInvalid'
+  | 'sceneAppMissing'
+  | 'todoDateInvalid'
+  | 'todoTimeInvalid'
+
+export interface IntegrityIssue {
+  file: string
+  kind: IntegrityIssueKind
+  itemId: string
+  message: string
+  repairable: boolean
+  repaired: boolean
+}
+
+export interface IntegrityReport {
+  issues: IntegrityIssue[]
+  repaired: number
+}
+
 export type MaintenanceOperation = 'validation' | 'baseline' | 'update' | 'delete'
 
 export type MaintenanceProgressStatus = 'success' | 'failed' | 'warning' | 'skipped'